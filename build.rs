@@ -1,7 +1,19 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR")?);
+
     tonic_build::configure()
         .build_server(true)
         .build_client(true)
-        .compile_protos(&["proto/wallet.proto", "proto/mfa.proto"], &["proto"])?;
+        .file_descriptor_set_path(out_dir.join("auth_service_descriptor.bin"))
+        .compile_protos(
+            &[
+                "proto/wallet.proto",
+                "proto/mfa.proto",
+                "proto/auth.proto",
+                "proto/job.proto",
+                "proto/user_admin.proto",
+            ],
+            &["proto"],
+        )?;
     Ok(())
 }
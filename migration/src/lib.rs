@@ -17,6 +17,13 @@ mod m20251122_070153_add_column_pdf_schema_to_docuemnt;
 mod m20251209_170606_update_table_certificate;
 mod m20251210_145112_update_table_user;
 mod m20251211_104058_add_column_expired_at;
+mod m20261018_090000_create_table_wallet_export_audit;
+mod m20261018_100000_create_table_outbox_event;
+mod m20261018_110000_create_table_processed_message;
+mod m20261018_120000_add_column_priority_to_outbox_event;
+mod m20261018_130000_create_table_notification;
+mod m20261018_140000_add_column_mfa_grace_started_at_to_user;
+mod m20261018_150000_add_import_values_to_notification_kind;
 
 pub struct Migrator;
 
@@ -41,6 +48,13 @@ impl MigratorTrait for Migrator {
             Box::new(m20251209_170606_update_table_certificate::Migration),
             Box::new(m20251210_145112_update_table_user::Migration),
             Box::new(m20251211_104058_add_column_expired_at::Migration),
+            Box::new(m20261018_090000_create_table_wallet_export_audit::Migration),
+            Box::new(m20261018_100000_create_table_outbox_event::Migration),
+            Box::new(m20261018_110000_create_table_processed_message::Migration),
+            Box::new(m20261018_120000_add_column_priority_to_outbox_event::Migration),
+            Box::new(m20261018_130000_create_table_notification::Migration),
+            Box::new(m20261018_140000_add_column_mfa_grace_started_at_to_user::Migration),
+            Box::new(m20261018_150000_add_import_values_to_notification_kind::Migration),
         ]
     }
 }
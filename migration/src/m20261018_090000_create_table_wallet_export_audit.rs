@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WalletExportAudit::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WalletExportAudit::AuditId)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT gen_random_uuid()".to_string()),
+                    )
+                    // No foreign keys: audit rows must outlive the users they mention
+                    .col(ColumnDef::new(WalletExportAudit::UserId).uuid().not_null())
+                    .col(ColumnDef::new(WalletExportAudit::ActorUserId).uuid().null())
+                    .col(ColumnDef::new(WalletExportAudit::PeerAddr).string().null())
+                    .col(
+                        ColumnDef::new(WalletExportAudit::Wrapped)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(WalletExportAudit::Outcome).string().not_null())
+                    .col(ColumnDef::new(WalletExportAudit::Reason).string().null())
+                    .col(
+                        ColumnDef::new(WalletExportAudit::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_string()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_wallet_export_audit_user_id")
+                    .table(WalletExportAudit::Table)
+                    .col(WalletExportAudit::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WalletExportAudit::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum WalletExportAudit {
+    Table,
+    AuditId,
+    UserId,
+    ActorUserId,
+    PeerAddr,
+    Wrapped,
+    Outcome,
+    Reason,
+    CreatedAt,
+}
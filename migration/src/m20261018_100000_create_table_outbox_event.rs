@@ -0,0 +1,86 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OutboxEvent::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OutboxEvent::EventId)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT gen_random_uuid()".to_string()),
+                    )
+                    .col(ColumnDef::new(OutboxEvent::Queue).string().not_null())
+                    .col(
+                        ColumnDef::new(OutboxEvent::Payload)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxEvent::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(OutboxEvent::LastError).string().null())
+                    .col(
+                        ColumnDef::new(OutboxEvent::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_string()),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxEvent::NextAttemptAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_string()),
+                    )
+                    .col(ColumnDef::new(OutboxEvent::SentAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // The relay only ever scans unsent rows that are due
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_outbox_event_pending")
+                    .table(OutboxEvent::Table)
+                    .col(OutboxEvent::SentAt)
+                    .col(OutboxEvent::NextAttemptAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OutboxEvent::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum OutboxEvent {
+    Table,
+    EventId,
+    Queue,
+    Payload,
+    Attempts,
+    LastError,
+    CreatedAt,
+    NextAttemptAt,
+    SentAt,
+}
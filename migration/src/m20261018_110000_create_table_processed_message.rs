@@ -0,0 +1,57 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProcessedMessage::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProcessedMessage::IdempotencyKey)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ProcessedMessage::Queue).string().not_null())
+                    .col(
+                        ColumnDef::new(ProcessedMessage::Outcome)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ProcessedMessage::Error).string().null())
+                    .col(
+                        ColumnDef::new(ProcessedMessage::ProcessedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_string()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProcessedMessage::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProcessedMessage {
+    Table,
+    IdempotencyKey,
+    Queue,
+    Outcome,
+    Error,
+    ProcessedAt,
+}
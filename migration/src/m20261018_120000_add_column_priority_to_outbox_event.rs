@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Events already in the outbox keep the priority every publish used to get
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OutboxEvent::Table)
+                    .add_column(
+                        ColumnDef::new(OutboxEvent::Priority)
+                            .small_integer()
+                            .not_null()
+                            .default(5),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OutboxEvent::Table)
+                    .drop_column(OutboxEvent::Priority)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OutboxEvent {
+    Table,
+    Priority,
+}
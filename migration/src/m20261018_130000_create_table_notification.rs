@@ -0,0 +1,160 @@
+use crate::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(NotificationKind::Table)
+                    .values(NotificationKind::values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Notification::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Notification::NotificationId)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT gen_random_uuid()".to_string()),
+                    )
+                    .col(ColumnDef::new(Notification::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Notification::Kind)
+                            .enumeration(NotificationKind::Table, NotificationKind::values())
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Notification::Message).text().not_null())
+                    .col(
+                        ColumnDef::new(Notification::Data)
+                            .json_binary()
+                            .not_null()
+                            .extra("DEFAULT '{}'::jsonb".to_string()),
+                    )
+                    .col(ColumnDef::new(Notification::ReadAt).timestamp().null())
+                    .col(
+                        ColumnDef::new(Notification::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_string()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_notification_user")
+                            .from_tbl(Notification::Table)
+                            .from_col(Notification::UserId)
+                            .to_tbl(User::Table)
+                            .to_col(User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Lists are per user, newest first, optionally unread only
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_notification_user_created_at")
+                    .table(Notification::Table)
+                    .col(Notification::UserId)
+                    .col(Notification::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_notification_user_read_at")
+                    .table(Notification::Table)
+                    .col(Notification::UserId)
+                    .col(Notification::ReadAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Notification::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(NotificationKind::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Notification {
+    Table,
+    NotificationId,
+    UserId,
+    Kind,
+    Message,
+    Data,
+    ReadAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum NotificationKind {
+    Table,
+    StudentRegistered,
+    StudentRegistrationFailed,
+    StudentsBatchRegistered,
+    StudentsBatchRegistrationFailed,
+    StudentActivated,
+    StudentActivationFailed,
+    StudentDeactivated,
+    StudentDeactivationFailed,
+    ManagerRegistered,
+    ManagerRegistrationFailed,
+    ManagerRemoved,
+    ManagerRemovalFailed,
+    RoleAssigned,
+    RoleAssignmentFailed,
+}
+
+impl NotificationKind {
+    fn values() -> [Self; 14] {
+        [
+            Self::StudentRegistered,
+            Self::StudentRegistrationFailed,
+            Self::StudentsBatchRegistered,
+            Self::StudentsBatchRegistrationFailed,
+            Self::StudentActivated,
+            Self::StudentActivationFailed,
+            Self::StudentDeactivated,
+            Self::StudentDeactivationFailed,
+            Self::ManagerRegistered,
+            Self::ManagerRegistrationFailed,
+            Self::ManagerRemoved,
+            Self::ManagerRemovalFailed,
+            Self::RoleAssigned,
+            Self::RoleAssignmentFailed,
+        ]
+    }
+}
@@ -0,0 +1,39 @@
+use crate::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Outcomes of the CSV import, which creates users before any blockchain job runs
+        for value in [
+            NotificationKind::UserImportRowFailed,
+            NotificationKind::UsersImportCompleted,
+        ] {
+            manager
+                .alter_type(
+                    Type::alter()
+                        .name(NotificationKind::Table)
+                        .add_value(value)
+                        .if_not_exists()
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // Postgres cannot drop enum values; unused ones are harmless
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum NotificationKind {
+    Table,
+    UserImportRowFailed,
+    UsersImportCompleted,
+}
@@ -10,6 +10,7 @@ use utoipa::openapi::security::SecurityScheme;
         crate::routes::health::route::health_check,
         crate::routes::auth::route::login,
         crate::routes::auth::route::logout,
+        crate::routes::auth::route::step_up,
         crate::routes::auth::route::forgot_password,
         crate::routes::auth::route::reset_password,
         crate::routes::auth::route::change_password,
@@ -53,6 +54,9 @@ use utoipa::openapi::security::SecurityScheme;
         crate::routes::user_mfa::route::req_enable_mfa,
         crate::routes::user_mfa::route::enable_mfa,
         crate::routes::user_mfa::route::verify_mfa_code_test,
+        crate::routes::user_mfa::route::get_trusted_devices,
+        crate::routes::user_mfa::route::revoke_trusted_device,
+        crate::routes::user_mfa::route::revoke_all_trusted_devices,
         crate::routes::stats::route::get_user_stats,
         crate::routes::stats::route::get_document_stats,
         crate::routes::documents::route::get_document_data,
@@ -65,12 +69,22 @@ use utoipa::openapi::security::SecurityScheme;
         crate::routes::requests::route::get_my_requests,
         crate::routes::requests::route::schedule_request,
         crate::routes::requests::route::get_all_requests,
+        crate::routes::notifications::route::get_my_notifications,
+        crate::routes::notifications::route::mark_notification_read,
+        crate::routes::notifications::route::mark_all_notifications_read,
+        crate::routes::dead_letters::route::list_dead_letter_queues,
+        crate::routes::dead_letters::route::get_dead_letters,
+        crate::routes::dead_letters::route::replay_dead_letters,
+        crate::routes::dead_letters::route::discard_dead_letters,
     ),
     components(
         schemas(
             crate::routes::auth::dto::LoginRequest,
             crate::routes::auth::dto::LoginResponse,
             crate::routes::auth::dto::LogoutResponse,
+            crate::routes::auth::dto::StepUpRequest,
+            crate::routes::auth::dto::StepUpResponse,
+            crate::extractor::MfaErrorResponse,
             crate::routes::auth::dto::ForgotPasswordRequest,
             crate::routes::auth::dto::ForgotPasswordResponse,
             crate::routes::auth::dto::ResetPasswordRequest,
@@ -108,11 +122,15 @@ use utoipa::openapi::security::SecurityScheme;
             crate::routes::students::dto::StudentIdResponse,
             crate::routes::students::dto::SystemInfoResponse,
             crate::routes::user_mfa::dto::MfaStatusResponseDto,
+            crate::middleware::mfa_policy::MfaPolicyState,
             crate::routes::user_mfa::dto::EnableMfaRequestDto,
             crate::routes::user_mfa::dto::EnableMfaResponseDto,
             crate::routes::user_mfa::dto::ReqEnableMfaResponseDto,
             crate::routes::user_mfa::dto::VerifyMfaCodeTestRequestDto,
             crate::routes::user_mfa::dto::VerifyMfaCodeTestResponseDto,
+            crate::routes::user_mfa::dto::TrustedDeviceResponseDto,
+            crate::routes::user_mfa::dto::TrustedDeviceListResponseDto,
+            crate::routes::user_mfa::dto::RevokeTrustedDeviceResponseDto,
             crate::routes::upload::route::UploadChunkResponse,
             crate::routes::upload::route::UploadProgressResponse,
             crate::routes::upload::route::ChunkUploadProgressResponse,
@@ -141,12 +159,24 @@ use utoipa::openapi::security::SecurityScheme;
             crate::routes::requests::dto::ScheduleRequestResponse,
             crate::routes::requests::dto::RequestListResponse,
             crate::routes::requests::dto::RequestQueryParams,
+            crate::routes::notifications::dto::NotificationResponse,
+            crate::routes::notifications::dto::NotificationListResponse,
+            crate::routes::notifications::dto::NotificationQueryParams,
+            crate::routes::notifications::dto::MarkAllReadResponse,
+            crate::routes::dead_letters::dto::DeadLetterQueueResponse,
+            crate::routes::dead_letters::dto::DeadLetterQueueListResponse,
+            crate::routes::dead_letters::dto::DeadLetterResponse,
+            crate::routes::dead_letters::dto::DeadLetterListResponse,
+            crate::routes::dead_letters::dto::ReplayDeadLettersRequest,
+            crate::routes::dead_letters::dto::DiscardDeadLettersRequest,
+            crate::routes::dead_letters::dto::DeadLetterActionResponse,
             crate::routes::stats::dto::DateRangeQuery,
             crate::routes::stats::dto::TimeSeriesPoint,
             crate::routes::stats::dto::UserStatsResponse,
             crate::routes::stats::dto::DocumentStatsResponse,
             crate::entities::sea_orm_active_enums::RoleEnum,
             crate::entities::sea_orm_active_enums::RequestStatus,
+            crate::entities::sea_orm_active_enums::NotificationKind,
         ),
     ),
     modifiers(&SecurityModifier),
@@ -163,6 +193,8 @@ use utoipa::openapi::security::SecurityScheme;
         (name = "security-settings", description = "Security settings and MFA endpoints"),
         (name = "Documents", description = "Document data endpoints"),
         (name = "Requests", description = "Request management endpoints"),
+        (name = "Notifications", description = "Stored outcomes of background jobs and their read state"),
+        (name = "Dead Letters", description = "Inspect, replay and discard failed background jobs"),
         (name = "health", description = "Health check endpoints")
     ),
 )]
@@ -3,6 +3,7 @@ use crate::config::APP_CONFIG;
 use crate::middleware::http_logger::http_logger;
 use crate::routes;
 use crate::routes::health::route::create_route;
+use crate::utils::step_up_token::STEP_UP_TOKEN_HEADER;
 use axum::Router;
 use axum::middleware;
 use http::header;
@@ -31,7 +32,9 @@ pub async fn create_app() -> anyhow::Result<Router> {
         .merge(routes::upload::route::create_route())
         .merge(routes::user_mfa::route::create_route())
         .merge(routes::documents::create_route())
-        .merge(routes::requests::create_route());
+        .merge(routes::requests::create_route())
+        .merge(routes::notifications::create_route())
+        .merge(routes::dead_letters::create_route());
 
     // Add Swagger UI
     if APP_CONFIG.swagger_enabled {
@@ -56,6 +59,7 @@ pub async fn create_app() -> anyhow::Result<Router> {
         header::AUTHORIZATION,
         header::ACCEPT,
         header::ACCEPT_LANGUAGE,
+        header::HeaderName::from_static(STEP_UP_TOKEN_HEADER),
     ];
     
     let allowed_methods = [
@@ -1,13 +1,20 @@
 use std::fs;
 use std::net::SocketAddr;
+use std::time::Duration;
 
 use auth_service::bootstrap::initialize_admin_user;
+use auth_service::email_service::get_mail_sender;
 use auth_service::grpc::start_grpc_server;
-use auth_service::rabbitmq_service::consumers::get_rabbitmq_connetion;
-use auth_service::rabbitmq_service::rabbitmq_service::RabbitMQService;
-use auth_service::redis_service::redis_service::init_redis_connection;
+use auth_service::key_provider::get_key_provider;
+use auth_service::kv_store::init_kv_store;
+use auth_service::rabbitmq_service::connection::rabbitmq;
+use auth_service::rabbitmq_service::outbox::OutboxRelay;
 use auth_service::static_service::get_database_connection;
+use auth_service::utils::shutdown::shutdown_signal;
 use auth_service::{app, config::APP_CONFIG, utils::tracing::init_standard_tracing};
+use tokio_util::sync::CancellationToken;
+
+const RABBITMQ_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -16,7 +23,13 @@ async fn main() -> anyhow::Result<()> {
     init_standard_tracing(env!("CARGO_CRATE_NAME"));
 
     tracing::info!("Starting application...");
-    get_rabbitmq_connetion().await;
+    // Publishers fail fast until the broker is reachable; the supervisor keeps retrying
+    if !rabbitmq()
+        .wait_connected_for(RABBITMQ_STARTUP_TIMEOUT)
+        .await
+    {
+        tracing::warn!("RabbitMQ is not reachable yet, starting without it...");
+    }
 
     tracing::info!("Create upload folder");
     fs::create_dir_all("./uploads/temp").unwrap();
@@ -24,20 +37,18 @@ async fn main() -> anyhow::Result<()> {
     // Initialize database connection
     let db_connection = get_database_connection().await;
 
-    let rabbit_mq = RabbitMQService::new().await;
+    // Publishes the messages handlers commit to the outbox
+    tokio::spawn(OutboxRelay::run());
 
-    if let Ok(()) = RabbitMQService::create_mail_queue(&rabbit_mq).await {
-        tracing::info!("Create mail queue successfully");
-    }
+    // Initialize the KV store (Redis, or in-memory for single-node dev)
+    tracing::info!("Initializing KV store...");
+    init_kv_store().await?;
 
-    // Initialize Redis connection
-    tracing::info!("Initializing Redis connection...");
-    if let Err(e) = init_redis_connection().await {
-        tracing::error!("Failed to initialize Redis connection: {}", e);
-        tracing::warn!("Continuing without Redis (MFA features may not work properly)...");
-    } else {
-        tracing::info!("Redis connection initialized successfully");
-    }
+    // A broken mail configuration fails here rather than on the first email
+    get_mail_sender().await?;
+
+    // Same for the key provider, which wallets, MFA secrets and the admin signer need
+    get_key_provider().await?;
 
     // Initialize default admin user
     tracing::info!("Checking admin user...");
@@ -52,9 +63,19 @@ async fn main() -> anyhow::Result<()> {
 
     let http_address = format!("0.0.0.0:{}", APP_CONFIG.port);
 
+    // One shutdown signal drains both servers
+    let shutdown = CancellationToken::new();
+    let signal_token = shutdown.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        tracing::info!("Shutdown signal received, draining servers...");
+        signal_token.cancel();
+    });
+
     // Start gRPC server in background
+    let grpc_shutdown = shutdown.clone();
     let grpc_handle = tokio::spawn(async move {
-        if let Err(e) = start_grpc_server().await {
+        if let Err(e) = start_grpc_server(grpc_shutdown).await {
             tracing::error!("gRPC server error: {}", e);
         }
     });
@@ -69,10 +90,14 @@ async fn main() -> anyhow::Result<()> {
         listener,
         app.into_make_service_with_connect_info::<SocketAddr>(),
     )
+    .with_graceful_shutdown(shutdown.clone().cancelled_owned())
     .await;
 
-    // Cancel gRPC server if HTTP server stops
-    grpc_handle.abort();
+    // Stop the gRPC server too if HTTP stopped on its own, and wait for it to drain
+    shutdown.cancel();
+    if let Err(e) = grpc_handle.await {
+        tracing::error!("gRPC server task failed: {}", e);
+    }
 
     http_result.expect("Failed to start HTTP server");
 
@@ -0,0 +1,171 @@
+//! Moves the messages left in the queues of the old naming scheme (`create::new::user`,
+//! `blockchain::assign::role`, ...) into their replacements from
+//! [`LEGACY_QUEUES`], then deletes the old queues once they are empty and unused.
+//!
+//! Work and retry queues are drained into the new work queue, so pending retries run
+//! straight away; dead letters go to the new dead-letter queue. Run it after the old
+//! consumers are stopped. Set `MIGRATE_QUEUES_DRY_RUN=true` to only report the counts.
+
+use auth_service::rabbitmq_service::connection::rabbitmq;
+use auth_service::rabbitmq_service::rabbitmq_service::RabbitMQService;
+use auth_service::rabbitmq_service::retry::{RetryPolicy, dead_letter_exchange, dead_letter_queue};
+use auth_service::rabbitmq_service::topology::{EXCHANGE, LEGACY_QUEUES, PERSISTENT};
+use auth_service::utils::tracing::init_standard_tracing;
+use lapin::options::{
+    BasicAckOptions, BasicGetOptions, BasicPublishOptions, QueueDeclareOptions, QueueDeleteOptions,
+};
+use lapin::types::FieldTable;
+
+#[derive(Default)]
+struct Report {
+    moved: usize,
+    deleted: usize,
+}
+
+/// Move every message of `source` to `exchange` with `routing_key`. Returns `None` when
+/// `source` does not exist.
+async fn drain(
+    source: &str,
+    exchange: &str,
+    routing_key: &str,
+    dry_run: bool,
+) -> anyhow::Result<Option<u32>> {
+    // A passive declare of a missing queue closes the channel, so each source gets its own
+    let channel = RabbitMQService::confirm_channel().await?;
+    let declared = channel
+        .queue_declare(
+            source,
+            QueueDeclareOptions {
+                passive: true,
+                ..QueueDeclareOptions::default()
+            },
+            FieldTable::default(),
+        )
+        .await;
+    let message_count = match declared {
+        Ok(queue) => queue.message_count(),
+        Err(_) => return Ok(None),
+    };
+    if dry_run {
+        let _ = channel.close(200, "OK").await;
+        return Ok(Some(message_count));
+    }
+
+    let mut moved = 0;
+    while let Some(message) = channel
+        .basic_get(source, BasicGetOptions { no_ack: false })
+        .await?
+    {
+        let delivery = message.delivery;
+        let properties = delivery.properties.clone().with_delivery_mode(PERSISTENT);
+        let confirm = channel
+            .basic_publish(
+                exchange,
+                routing_key,
+                BasicPublishOptions {
+                    mandatory: true,
+                    ..BasicPublishOptions::default()
+                },
+                &delivery.data,
+                properties,
+            )
+            .await?;
+        // Unacked messages go back to `source` when the channel closes on error
+        RabbitMQService::wait_confirm(confirm).await?;
+        delivery.ack(BasicAckOptions::default()).await?;
+        moved += 1;
+    }
+
+    let _ = channel.close(200, "OK").await;
+    Ok(Some(moved))
+}
+
+/// Delete `queue` unless something was published to it or started consuming it meanwhile
+async fn delete_if_idle(queue: &str) -> anyhow::Result<bool> {
+    let channel = rabbitmq().create_channel().await?;
+    let deleted = channel
+        .queue_delete(
+            queue,
+            QueueDeleteOptions {
+                if_unused: true,
+                if_empty: true,
+                ..QueueDeleteOptions::default()
+            },
+        )
+        .await;
+    match deleted {
+        Ok(_) => {
+            let _ = channel.close(200, "OK").await;
+            Ok(true)
+        }
+        Err(e) => {
+            tracing::warn!("Kept {}: {}", queue, e);
+            Ok(false)
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv::dotenv().ok();
+    init_standard_tracing(env!("CARGO_CRATE_NAME"));
+
+    let dry_run = std::env::var("MIGRATE_QUEUES_DRY_RUN")
+        .map(|value| value == "true" || value == "1")
+        .unwrap_or(false);
+
+    // The supervisor declares the new topology before handing out the connection
+    rabbitmq().wait_connected().await;
+
+    let policy = RetryPolicy::from_config();
+    let mut report = Report::default();
+    for &(legacy, queue) in LEGACY_QUEUES {
+        // Retry queues dead-letter into the old work queue when their TTL elapses, so they
+        // are drained before it
+        let mut sources: Vec<(String, String, String)> = (1..=policy.max_retries)
+            .map(|retry| {
+                (
+                    policy.delay_queue(legacy, retry),
+                    EXCHANGE.to_string(),
+                    queue.to_string(),
+                )
+            })
+            .collect();
+        sources.push((legacy.to_string(), EXCHANGE.to_string(), queue.to_string()));
+        sources.push((
+            dead_letter_queue(legacy),
+            dead_letter_exchange(queue),
+            String::new(),
+        ));
+
+        for (source, exchange, routing_key) in sources {
+            let Some(moved) = drain(&source, &exchange, &routing_key, dry_run).await? else {
+                continue;
+            };
+            tracing::info!(
+                "{}: {} {} messages to {}",
+                source,
+                if dry_run { "would move" } else { "moved" },
+                moved,
+                if routing_key.is_empty() {
+                    &exchange
+                } else {
+                    &routing_key
+                }
+            );
+            report.moved += moved as usize;
+
+            if !dry_run && delete_if_idle(&source).await? {
+                report.deleted += 1;
+            }
+        }
+    }
+
+    tracing::info!(
+        "✅ Queue migration finished: {} messages moved, {} legacy queues deleted",
+        report.moved,
+        report.deleted
+    );
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! Renders every email template with sample parameters in every locale, without any
+//! configuration or running service.
+//!
+//! Usage: `cargo run --bin preview_emails [output_dir]` (defaults to `email_preview`).
+//! Writes `<template>.<locale>.html` and `<template>.<locale>.txt`, the text file starting
+//! with the subject.
+
+use auth_service::email_service::template::{EmailTemplate, Locale};
+use std::path::PathBuf;
+
+fn main() -> anyhow::Result<()> {
+    let output_dir = PathBuf::from(
+        std::env::args()
+            .nth(1)
+            .unwrap_or_else(|| "email_preview".to_string()),
+    );
+    std::fs::create_dir_all(&output_dir)?;
+
+    for template in EmailTemplate::samples() {
+        for locale in Locale::ALL {
+            let email = template.render(locale);
+            let stem = format!("{}.{}", template.name(), locale);
+
+            std::fs::write(output_dir.join(format!("{}.html", stem)), &email.html)?;
+            std::fs::write(
+                output_dir.join(format!("{}.txt", stem)),
+                format!("Subject: {}\n\n{}\n", email.subject, email.text),
+            )?;
+            println!("{}", output_dir.join(format!("{}.html", stem)).display());
+        }
+    }
+
+    Ok(())
+}
@@ -1,7 +1,13 @@
-use auth_service::rabbitmq_service::consumers::{
-    RABBITMQ_CONNECTION, RabbitMqConsumer, get_rabbitmq_connetion,
+use auth_service::email_service::get_mail_sender;
+use auth_service::key_provider::get_key_provider;
+use auth_service::kv_store::init_kv_store;
+use auth_service::rabbitmq_service::connection::{keep_consuming, rabbitmq};
+use auth_service::rabbitmq_service::consumers::RabbitMqConsumer;
+use auth_service::rabbitmq_service::handlers::{
+    ActivateStudentHandler, AssignRoleHandler, DeactivateStudentHandler, RegisterManagerHandler,
+    RegisterStudentHandler, RegisterStudentsBatchHandler, RemoveManagerHandler,
 };
-use auth_service::rabbitmq_service::rabbitmq_service::RabbitMQService;
+use auth_service::rabbitmq_service::job_runner::JobRunner;
 use auth_service::static_service::get_database_connection;
 use auth_service::utils::tracing::init_standard_tracing;
 
@@ -10,105 +16,69 @@ pub async fn main() -> anyhow::Result<()> {
     dotenv::dotenv().ok();
 
     init_standard_tracing(env!("CARGO_CRATE_NAME"));
-    tracing::info!("Initializing RabbitMQ connection...");
-
-    get_rabbitmq_connetion().await;
-    tracing::info!("RabbitMQ connection established");
 
     get_database_connection().await;
     tracing::info!("Database connection established");
 
-    let rabbitmq_connection = RABBITMQ_CONNECTION
-        .get()
-        .expect("Failed to get rabbitmq connection");
-
-    tracing::info!("Creating register new user channel...");
-    RabbitMQService::create_register_new_user_channel(rabbitmq_connection)
-        .await
-        .ok();
-
-    tracing::info!("Creating register new user db channel...");
-    RabbitMQService::create_user_db_channel(rabbitmq_connection)
-        .await
-        .ok();
+    // The supervisor declares the topology on every (re)connect
+    tracing::info!("Connecting to RabbitMQ...");
+    rabbitmq().wait_connected().await;
+    tracing::info!("RabbitMQ connection established");
 
-    tracing::info!("Creating register new manager channel...");
-    RabbitMQService::create_register_new_manager_channel(rabbitmq_connection)
-        .await
-        .ok();
+    // Students get their activation email from here
+    get_mail_sender().await?;
 
-    tracing::info!("Creating all blockchain queues...");
-    RabbitMQService::create_all_blockchain_queues(rabbitmq_connection)
-        .await
-        .ok();
+    // Import and batch progress counters live there
+    init_kv_store().await?;
 
-    tracing::info!("All queues created successfully");
+    // Imported users get encrypted wallets and jobs sign with the admin key
+    get_key_provider().await?;
 
     tracing::info!("Starting all consumers...");
 
-    // Start all consumers in parallel
+    // Start all consumers in parallel; each one resubscribes after a reconnect
     let student_consumer = tokio::spawn(async {
-        tracing::info!("[Spawn] Starting student consumer task...");
-        if let Err(e) = RabbitMqConsumer::consume_register_new_student().await {
-            tracing::error!("Student consumer error: {:?}", e);
-        }
+        let runner = JobRunner::new(RegisterStudentHandler);
+        keep_consuming("Student", || runner.run()).await;
     });
 
     let manager_consumer = tokio::spawn(async {
-        tracing::info!("[Spawn] Starting manager consumer task...");
-        if let Err(e) = RabbitMqConsumer::consume_register_new_manager().await {
-            tracing::error!("Manager consumer error: {:?}", e);
-        }
+        let runner = JobRunner::new(RegisterManagerHandler);
+        keep_consuming("Manager", || runner.run()).await;
     });
 
     let assign_role_consumer = tokio::spawn(async {
-        tracing::info!("[Spawn] Starting assign role consumer task...");
-        if let Err(e) = RabbitMqConsumer::consume_assign_role().await {
-            tracing::error!("Assign role consumer error: {:?}", e);
-        }
+        let runner = JobRunner::new(AssignRoleHandler);
+        keep_consuming("Assign role", || runner.run()).await;
     });
 
     let remove_manager_consumer = tokio::spawn(async {
-        tracing::info!("[Spawn] Starting remove manager consumer task...");
-        if let Err(e) = RabbitMqConsumer::consume_remove_manager().await {
-            tracing::error!("Remove manager consumer error: {:?}", e);
-        }
+        let runner = JobRunner::new(RemoveManagerHandler);
+        keep_consuming("Remove manager", || runner.run()).await;
     });
 
     let deactivate_student_consumer = tokio::spawn(async {
-        tracing::info!("[Spawn] Starting deactivate student consumer task...");
-        if let Err(e) = RabbitMqConsumer::consume_deactivate_student().await {
-            tracing::error!("Deactivate student consumer error: {:?}", e);
-        }
+        let runner = JobRunner::new(DeactivateStudentHandler);
+        keep_consuming("Deactivate student", || runner.run()).await;
     });
 
     let activate_student_consumer = tokio::spawn(async {
-        tracing::info!("[Spawn] Starting activate student consumer task...");
-        if let Err(e) = RabbitMqConsumer::consume_activate_student().await {
-            tracing::error!("Activate student consumer error: {:?}", e);
-        }
+        let runner = JobRunner::new(ActivateStudentHandler);
+        keep_consuming("Activate student", || runner.run()).await;
     });
 
     let register_batch_consumer = tokio::spawn(async {
-        tracing::info!("[Spawn] Starting register batch consumer task...");
-        if let Err(e) = RabbitMqConsumer::consume_register_students_batch().await {
-            tracing::error!("Register students batch consumer error: {:?}", e);
-        }
+        let runner = JobRunner::new(RegisterStudentsBatchHandler);
+        keep_consuming("Register students batch", || runner.run()).await;
     });
 
     let create_user_db = tokio::spawn(async {
-        tracing::info!("[Spawn] Starting create user db consumer task...");
-        if let Err(e) = RabbitMqConsumer::consumer_create_user_db().await {
-            tracing::error!("Create user db consumer error: {:?}", e);
-        }
+        keep_consuming("Create user db", RabbitMqConsumer::consumer_create_user_db).await;
     });
 
-    // Give consumers a moment to start
-    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-
     tracing::info!("All consumers started, waiting for messages...");
 
-    // Wait for any consumer to stop (they run indefinitely)
+    // Consumers only stop if their task panics
     tokio::select! {
         _ = student_consumer => {
             tracing::warn!("Student consumer stopped");
@@ -131,6 +101,9 @@ pub async fn main() -> anyhow::Result<()> {
         _ = register_batch_consumer => {
             tracing::warn!("Register students batch consumer stopped");
         }
+        _ = create_user_db => {
+            tracing::warn!("Create user db consumer stopped");
+        }
     }
 
     Ok(())
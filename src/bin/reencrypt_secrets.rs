@@ -0,0 +1,156 @@
+//! Re-encrypts every `wallet.private_key` and `user_mfa.secret` that is still stored with a
+//! legacy scheme, sealed with a non-active master key or written by another key provider
+//! (e.g. when moving from `KEY_PROVIDER=env` to `vault`).
+//!
+//! Set `REENCRYPT_DRY_RUN=true` to only report how many rows would be rewritten.
+
+use auth_service::entities::{user_mfa, wallet};
+use auth_service::static_service::get_database_connection;
+use auth_service::key_provider::{KeyProvider, get_key_provider};
+use auth_service::utils::envelope_encryption::{CONTEXT_MFA_SECRET, CONTEXT_WALLET_PRIVATE_KEY};
+use auth_service::utils::tracing::init_standard_tracing;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set, TransactionTrait};
+
+#[derive(Default)]
+struct Report {
+    scanned: usize,
+    rewritten: usize,
+    failed: usize,
+}
+
+async fn reencrypt_wallets(
+    provider: &dyn KeyProvider,
+    db: &DatabaseConnection,
+    dry_run: bool,
+) -> anyhow::Result<Report> {
+    let mut report = Report::default();
+    let wallets = wallet::Entity::find()
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to query wallets: {}", e))?;
+
+    let txn = db.begin().await?;
+    for wallet_info in wallets {
+        report.scanned += 1;
+        if !provider.needs_reencryption(&wallet_info.private_key) {
+            continue;
+        }
+
+        let private_key = match provider
+            .decrypt(&wallet_info.private_key, CONTEXT_WALLET_PRIVATE_KEY)
+            .await
+        {
+            Ok(private_key) => private_key,
+            Err(e) => {
+                tracing::error!("Failed to decrypt wallet {}: {}", wallet_info.wallet_id, e);
+                report.failed += 1;
+                continue;
+            }
+        };
+
+        report.rewritten += 1;
+        if dry_run {
+            continue;
+        }
+
+        let sealed = provider
+            .encrypt(&private_key, CONTEXT_WALLET_PRIVATE_KEY)
+            .await?;
+        let mut active_model: wallet::ActiveModel = wallet_info.into();
+        active_model.private_key = Set(sealed);
+        active_model.updated_at = Set(chrono::Utc::now().naive_utc());
+        active_model.update(&txn).await?;
+    }
+    txn.commit().await?;
+
+    Ok(report)
+}
+
+async fn reencrypt_mfa_secrets(
+    provider: &dyn KeyProvider,
+    db: &DatabaseConnection,
+    dry_run: bool,
+) -> anyhow::Result<Report> {
+    let mut report = Report::default();
+    let mfa_records = user_mfa::Entity::find()
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to query user_mfa: {}", e))?;
+
+    let txn = db.begin().await?;
+    for mfa_record in mfa_records {
+        report.scanned += 1;
+        if !provider.needs_reencryption(&mfa_record.secret) {
+            continue;
+        }
+
+        let secret = match provider.decrypt(&mfa_record.secret, CONTEXT_MFA_SECRET).await {
+            Ok(secret) => secret,
+            Err(e) => {
+                tracing::error!("Failed to decrypt MFA secret {}: {}", mfa_record.mfa_id, e);
+                report.failed += 1;
+                continue;
+            }
+        };
+
+        report.rewritten += 1;
+        if dry_run {
+            continue;
+        }
+
+        let sealed = provider.encrypt(&secret, CONTEXT_MFA_SECRET).await?;
+        let mut active_model: user_mfa::ActiveModel = mfa_record.into();
+        active_model.secret = Set(sealed);
+        active_model.updated_at = Set(chrono::Utc::now().naive_utc());
+        active_model.update(&txn).await?;
+    }
+    txn.commit().await?;
+
+    Ok(report)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv::dotenv().ok();
+    init_standard_tracing(env!("CARGO_CRATE_NAME"));
+
+    let dry_run = std::env::var("REENCRYPT_DRY_RUN")
+        .map(|value| value == "true" || value == "1")
+        .unwrap_or(false);
+
+    let provider = get_key_provider().await?.as_ref();
+    tracing::info!(
+        "Re-encrypting secrets with the '{}' key provider{}",
+        provider.name(),
+        if dry_run { " (dry run)" } else { "" }
+    );
+
+    let db_connection = get_database_connection().await;
+
+    let wallets = reencrypt_wallets(provider, db_connection, dry_run).await?;
+    tracing::info!(
+        "wallet.private_key: scanned {}, rewritten {}, failed {}",
+        wallets.scanned,
+        wallets.rewritten,
+        wallets.failed
+    );
+
+    let mfa = reencrypt_mfa_secrets(provider, db_connection, dry_run).await?;
+    tracing::info!(
+        "user_mfa.secret: scanned {}, rewritten {}, failed {}",
+        mfa.scanned,
+        mfa.rewritten,
+        mfa.failed
+    );
+
+    if wallets.failed + mfa.failed > 0 {
+        anyhow::bail!(
+            "{} secrets could not be decrypted, check the configured keys",
+            wallets.failed + mfa.failed
+        );
+    }
+
+    tracing::info!("✅ Re-encryption finished");
+
+    Ok(())
+}
@@ -3,9 +3,9 @@ use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 use uuid::Uuid;
 
 use super::service::BlockchainService;
-use crate::config::APP_CONFIG;
 use crate::entities::wallet;
-use crate::utils::encryption::decrypt_private_key;
+use crate::key_provider::get_key_provider;
+use crate::utils::envelope_encryption::CONTEXT_WALLET_PRIVATE_KEY;
 
 pub async fn get_user_private_key(db: &DatabaseConnection, user_id: &Uuid) -> Result<String> {
     let wallet_info = wallet::Entity::find()
@@ -15,7 +15,10 @@ pub async fn get_user_private_key(db: &DatabaseConnection, user_id: &Uuid) -> Re
         .context("Failed to query wallet")?
         .ok_or_else(|| anyhow::anyhow!("Wallet not found for user"))?;
 
-    decrypt_private_key(&wallet_info.private_key, &APP_CONFIG.encryption_key)
+    get_key_provider()
+        .await?
+        .decrypt(&wallet_info.private_key, CONTEXT_WALLET_PRIVATE_KEY)
+        .await
         .context("Failed to decrypt private key")
 }
 
@@ -1,5 +1,6 @@
 use crate::blockchain::contract::DataStorage;
 use crate::config::APP_CONFIG;
+use crate::key_provider::{AdminSigner, get_key_provider};
 use anyhow::{Context, Result};
 use ethers::prelude::*;
 use ethers::providers::{Http, Provider};
@@ -8,7 +9,7 @@ use std::sync::Arc;
 
 #[derive(Clone, Debug)]
 pub struct BlockchainService {
-    contract: DataStorage<SignerMiddleware<Provider<Http>, LocalWallet>>,
+    contract: DataStorage<SignerMiddleware<Provider<Http>, AdminSigner>>,
 }
 
 impl BlockchainService {
@@ -16,13 +17,14 @@ impl BlockchainService {
         let provider = Provider::<Http>::try_from(&APP_CONFIG.blockchain_rpc_url)
             .context("Failed to create provider")?;
 
-        let admin_private_key = APP_CONFIG.admin_private_key.clone();
-        let wallet: LocalWallet = admin_private_key.parse().context("Failed to parse private key")?;
+        let signer = AdminSigner::new(get_key_provider().await?.clone())
+            .await
+            .context("Failed to load admin signer")?;
 
         let chain_id = provider.get_chainid().await?;
-        let wallet = wallet.with_chain_id(chain_id.as_u64());
+        let signer = signer.with_chain_id(chain_id.as_u64());
 
-        let client = SignerMiddleware::new(provider, wallet);
+        let client = SignerMiddleware::new(provider, signer);
         let client = Arc::new(client);
 
         let contract_address: Address = APP_CONFIG
@@ -1,13 +1,13 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
-use ethers::signers::{LocalWallet, Signer};
 use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
 use uuid::Uuid;
 
 use crate::config::APP_CONFIG;
 use crate::entities::sea_orm_active_enums::UserStatus;
 use crate::entities::{sea_orm_active_enums::RoleEnum, user, wallet};
-use crate::utils::encryption::encrypt_private_key;
+use crate::key_provider::get_key_provider;
+use crate::utils::envelope_encryption::CONTEXT_WALLET_PRIVATE_KEY;
 
 pub async fn initialize_admin_user(db: &DatabaseConnection) -> Result<()> {
     let admin_email: &str = &APP_CONFIG.admin_email;
@@ -26,15 +26,16 @@ pub async fn initialize_admin_user(db: &DatabaseConnection) -> Result<()> {
 
     tracing::info!("Creating default admin user...");
 
-    let admin_wallet: LocalWallet = APP_CONFIG
-        .admin_private_key
-        .parse()
-        .context("Failed to parse admin private key")?;
-
-    let wallet_address = format!("{:?}", admin_wallet.address());
-    let private_key = APP_CONFIG.admin_private_key.clone();
+    let key_provider = get_key_provider().await?;
+    let admin_address = key_provider
+        .admin_address()
+        .await
+        .context("Failed to load admin address")?;
+    let wallet_address = format!("{:?}", admin_address);
 
-    let encrypted_private_key = encrypt_private_key(&private_key, &APP_CONFIG.encryption_key)
+    let encrypted_private_key = key_provider
+        .seal_admin_key(CONTEXT_WALLET_PRIVATE_KEY)
+        .await
         .context("Failed to encrypt admin private key")?;
 
     let hashed_password = bcrypt::hash(default_password, bcrypt::DEFAULT_COST)
@@ -61,6 +62,7 @@ pub async fn initialize_admin_user(db: &DatabaseConnection) -> Result<()> {
         student_code: Set(None),
         deleted_at: Set(None),
         status: Set(UserStatus::Sync),
+        mfa_grace_started_at: Set(None),
     };
 
     admin_user
@@ -7,8 +7,15 @@ pub const OTP_ISSUER: &str = "NGON";
 pub const MFA_MAX_FAIL_ATTEMPTS: u32 = 3;
 pub const MFA_CODE_REUSE_TTL_SECONDS: u64 = 120; // 2 minutes
 pub const MFA_LOCK_DURATION_SECONDS: u64 = 900; // 15 minutes
+pub const MFA_TRUSTED_DEVICE_TTL_SECONDS: u64 = 2_592_000; // 30 days
+pub const MFA_TRUSTED_DEVICE_COOKIE: &str = "mfa_trusted_device";
+pub const MFA_ENABLE_OTP_EXPIRES_IN_MINUTES: i64 = 5;
 pub const JWT_EXPRIED_TIME: i64 = 86400i64;
 
+// Step-up (elevated) token issued after a fresh MFA verification
+pub const MFA_STEP_UP_TOKEN_EXPIRED_TIME: i64 = 300; // 5 minutes
+pub const MFA_STEP_UP_MAX_AGE_SECONDS: i64 = 300; // 5 minutes
+
 pub const FILE_TRACKER_EXPRIED_TIME: i64 = 86400i64;
 
 pub static APP_CONFIG: Lazy<Config> = Lazy::new(Config::parse);
@@ -39,12 +46,109 @@ pub struct Config {
     #[clap(long, env)]
     pub encryption_key: String,
 
+    /// Master keys for envelope encryption as `id:secret,id:secret`.
+    /// Defaults to `ENCRYPTION_KEY` under the key id `default`.
+    #[clap(long, env)]
+    pub encryption_keys: Option<String>,
+
+    /// Key id used to seal new secrets (defaults to the first entry of `ENCRYPTION_KEYS`)
+    #[clap(long, env)]
+    pub encryption_active_key_id: Option<String>,
+
+    /// Backend holding master keys and the admin signing key: `env`, `file` or `vault`
+    #[clap(long, env, default_value = "env")]
+    pub key_provider: String,
+
+    /// JSON key file read when `KEY_PROVIDER=file`
+    #[clap(long, env)]
+    pub key_provider_file: Option<String>,
+
+    #[clap(long, env)]
+    pub vault_addr: Option<String>,
+
+    #[clap(long, env)]
+    pub vault_token: Option<String>,
+
+    #[clap(long, env, default_value = "transit")]
+    pub vault_transit_mount: String,
+
+    #[clap(long, env, default_value = "auth_service")]
+    pub vault_transit_key: String,
+
     #[clap(long, env, default_value_t = 50051)]
     pub grpc_port: u16,
 
+    /// PEM certificate and key enabling TLS on the gRPC port
+    #[clap(long, env)]
+    pub grpc_tls_cert_path: Option<String>,
+
+    #[clap(long, env)]
+    pub grpc_tls_key_path: Option<String>,
+
+    /// PEM CA used to verify client certificates (mTLS)
+    #[clap(long, env)]
+    pub grpc_tls_client_ca_path: Option<String>,
+
+    /// Reject gRPC clients without a certificate instead of falling back to bearer tokens
+    #[clap(long, env, default_value_t = false)]
+    pub grpc_mtls_required: bool,
+
+    #[clap(long, env, default_value_t = true)]
+    pub grpc_reflection_enabled: bool,
+
+    /// Bearer token accepted from internal services on the gRPC port.
+    /// Callers presenting a client certificate (mTLS) don't need it.
+    #[clap(long, env)]
+    pub grpc_service_token: Option<String>,
+
     #[clap(long, env)]
     pub rabbitmq_uri: String,
 
+    /// Attempts after the first before a blockchain job is dead-lettered
+    #[clap(long, env, default_value_t = 5)]
+    pub rabbitmq_max_retries: u32,
+
+    /// Delay before the first retry; doubles on each following one
+    #[clap(long, env, default_value_t = 5000)]
+    pub rabbitmq_retry_base_delay_ms: u64,
+
+    /// How emails leave the service: `queue` hands them to the external mail service over
+    /// RabbitMQ, `smtp` sends them directly with the `SMTP_*` settings
+    #[clap(long, env, default_value = "queue")]
+    pub mail_backend: String,
+
+    #[clap(long, env)]
+    pub smtp_host: Option<String>,
+
+    #[clap(long, env, default_value_t = 587)]
+    pub smtp_port: u16,
+
+    #[clap(long, env)]
+    pub smtp_username: Option<String>,
+
+    #[clap(long, env)]
+    pub smtp_password: Option<String>,
+
+    /// Upgrade the connection with STARTTLS; turn off only for local sinks such as MailHog
+    #[clap(long, env, default_value_t = true)]
+    pub smtp_starttls: bool,
+
+    /// Sender address, e.g. `NGON <no-reply@example.edu.vn>`
+    #[clap(long, env)]
+    pub smtp_from: Option<String>,
+
+    /// Attempts after the first when the SMTP server fails with a transient error
+    #[clap(long, env, default_value_t = 3)]
+    pub smtp_max_retries: u32,
+
+    /// Most emails started per minute, spaced evenly; 0 sends without a limit
+    #[clap(long, env, default_value_t = 60)]
+    pub smtp_rate_limit_per_minute: u32,
+
+    /// Language of emails to users whose request carries no usable `Accept-Language`: vi or en
+    #[clap(long, env, default_value = "vi")]
+    pub mail_default_locale: String,
+
     #[clap(long, env)]
     pub admin_email: String,
 
@@ -57,12 +161,46 @@ pub struct Config {
     #[clap(long, env)]
     pub chain_id: String,
 
+    /// Redis URL; with sentinel or cluster mode, comma-separated node URLs
     #[clap(long, env, default_value = "redis://127.0.0.1:6379")]
     pub redis_url: String,
 
+    /// Redis topology: standalone, sentinel or cluster
+    #[clap(long, env, default_value = "standalone")]
+    pub redis_mode: String,
+
+    /// Master name monitored by the Sentinel nodes
+    #[clap(long, env, default_value = "mymaster")]
+    pub redis_sentinel_master: String,
+
+    /// Prefix of every Redis key and channel, such as "staging:" for environments sharing
+    /// a Redis. Empty by default, so existing keys keep their names.
+    #[clap(long, env, default_value = "")]
+    pub redis_key_prefix: String,
+
+    #[clap(long, env, default_value_t = 2000)]
+    pub redis_connect_timeout_ms: u64,
+
+    #[clap(long, env, default_value_t = 500)]
+    pub redis_response_timeout_ms: u64,
+
+    /// Backend for MFA attempts, the JWT blacklist and job progress: "redis" or "memory"
+    /// (single node only, state is lost on restart)
+    #[clap(long, env, default_value = "redis")]
+    pub kv_store: String,
+
     #[clap(long, env, default_value = "local")]
     pub app_env: String,
 
     #[clap(long, env, default_value = "*")]
     pub cors_allowed_origins: String,
+
+    /// Comma-separated roles that must enroll in MFA (admin, manager, teacher, student)
+    #[clap(long, env, default_value = "admin,manager")]
+    pub mfa_required_roles: String,
+
+    /// Days a user of a required role may log in without MFA, counted from the first time
+    /// they were seen without it, before enrollment is forced
+    #[clap(long, env, default_value_t = 7)]
+    pub mfa_enrollment_grace_days: i64,
 }
@@ -0,0 +1,148 @@
+//! Transactional emails: typed, localized templates rendered here and delivered by the
+//! [`MailSender`] selected with `MAIL_BACKEND`.
+
+pub mod queue_sender;
+pub mod smtp_sender;
+pub mod template;
+
+use crate::config::APP_CONFIG;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use http::HeaderMap;
+use http::header::ACCEPT_LANGUAGE;
+use std::sync::Arc;
+use template::{EmailTemplate, Locale, RenderedEmail};
+use tokio::sync::OnceCell;
+
+pub use queue_sender::QueueMailSender;
+pub use smtp_sender::SmtpMailSender;
+
+/// Delivers rendered emails
+#[async_trait]
+pub trait MailSender: Send + Sync {
+    /// Backend name, for logs
+    fn name(&self) -> &'static str;
+
+    /// Returns once the email was accepted for delivery
+    async fn send(&self, to: &str, email: &RenderedEmail) -> Result<()>;
+}
+
+pub static MAIL_SENDER: OnceCell<Arc<dyn MailSender>> = OnceCell::const_new();
+
+/// Build the backend selected by `MAIL_BACKEND` (`queue` or `smtp`)
+pub fn create_mail_sender() -> Result<Arc<dyn MailSender>> {
+    let sender: Arc<dyn MailSender> = match APP_CONFIG.mail_backend.as_str() {
+        "queue" => Arc::new(QueueMailSender),
+        "smtp" => Arc::new(SmtpMailSender::from_config()?),
+        other => anyhow::bail!("Unknown mail backend '{}'", other),
+    };
+
+    tracing::info!("Using '{}' mail backend", sender.name());
+    Ok(sender)
+}
+
+/// The sender built from `MAIL_BACKEND` on first use. A bad SMTP setting is an error here
+/// rather than when the first email goes out, so binaries call this before serving.
+pub async fn get_mail_sender() -> Result<&'static Arc<dyn MailSender>> {
+    MAIL_SENDER
+        .get_or_try_init(|| async { create_mail_sender().context("Failed to create mail sender") })
+        .await
+}
+
+/// Use `sender` instead of the configured backend. Must run before the first
+/// `get_mail_sender`.
+pub fn install_mail_sender(sender: Arc<dyn MailSender>) -> Result<()> {
+    MAIL_SENDER
+        .set(sender)
+        .map_err(|_| anyhow::anyhow!("Mail sender is already initialized"))
+}
+
+pub struct EmailService;
+
+impl EmailService {
+    /// The locale of users who did not tell us theirs, from `MAIL_DEFAULT_LOCALE`
+    pub fn default_locale() -> Locale {
+        APP_CONFIG.mail_default_locale.parse().unwrap_or_else(|e| {
+            tracing::warn!("{}, falling back to {}", e, Locale::default());
+            Locale::default()
+        })
+    }
+
+    /// The locale asked for by the request, for emails sent to the caller
+    pub fn locale_from_headers(headers: &HeaderMap) -> Locale {
+        headers
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Locale::from_accept_language)
+            .unwrap_or_else(Self::default_locale)
+    }
+
+    pub async fn send(to: &str, template: &EmailTemplate, locale: Locale) -> Result<()> {
+        let email = template.render(locale);
+        get_mail_sender()
+            .await?
+            .send(to, &email)
+            .await
+            .with_context(|| format!("Failed to send {} email", template.name()))
+    }
+
+    /// Send without waiting for it: SMTP retries and rate limiting can take seconds, too long
+    /// for an HTTP response or a job slot. A failure is only logged.
+    pub fn send_in_background(to: String, template: EmailTemplate, locale: Locale) {
+        tokio::spawn(async move {
+            if let Err(e) = Self::send(&to, &template, locale).await {
+                tracing::error!("Failed to email {}: {:#}", to, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingMailSender {
+        sent: Mutex<Vec<(String, RenderedEmail)>>,
+    }
+
+    #[async_trait]
+    impl MailSender for RecordingMailSender {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn send(&self, to: &str, email: &RenderedEmail) -> Result<()> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((to.to_string(), email.clone()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_renders_and_delivers_through_installed_sender() {
+        let recorder = Arc::new(RecordingMailSender::default());
+        install_mail_sender(recorder.clone()).unwrap();
+        let template = EmailTemplate::EnableMfa {
+            otp_code: "X7K2M9QA".to_string(),
+            expires_in_minutes: 5,
+        };
+
+        EmailService::send("student@example.com", &template, Locale::default())
+            .await
+            .unwrap();
+
+        let sent = recorder.sent.lock().unwrap();
+        assert_eq!(
+            *sent,
+            vec![(
+                "student@example.com".to_string(),
+                template.render(Locale::default())
+            )]
+        );
+        assert!(sent[0].1.text.contains("X7K2M9QA"));
+    }
+}
@@ -0,0 +1,19 @@
+use super::MailSender;
+use super::template::RenderedEmail;
+use crate::rabbitmq_service::rabbitmq_service::RabbitMQService;
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Hands emails to the external mail service through the `mail_service` queue
+pub struct QueueMailSender;
+
+#[async_trait]
+impl MailSender for QueueMailSender {
+    fn name(&self) -> &'static str {
+        "queue"
+    }
+
+    async fn send(&self, to: &str, email: &RenderedEmail) -> Result<()> {
+        RabbitMQService::publish_to_mail_queue(to, email).await
+    }
+}
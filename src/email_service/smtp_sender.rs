@@ -0,0 +1,207 @@
+use super::MailSender;
+use super::template::RenderedEmail;
+use crate::config::APP_CONFIG;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::response::{Code, Severity};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(10);
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Spaces sends evenly so that at most `per_minute` start in any minute
+struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// `None` for 0, which means no limit
+    fn new(per_minute: u32) -> Option<Self> {
+        (per_minute > 0).then(|| Self {
+            interval: Duration::from_secs(60) / per_minute,
+            next_slot: Mutex::new(Instant::now()),
+        })
+    }
+
+    async fn acquire(&self) {
+        let slot = self.reserve(Instant::now()).await;
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// Book the first free slot at or after `now`
+    async fn reserve(&self, now: Instant) -> Instant {
+        let mut next_slot = self.next_slot.lock().await;
+        let slot = (*next_slot).max(now);
+        *next_slot = slot + self.interval;
+        slot
+    }
+}
+
+/// Wait before retry number `retry`, doubling from [`INITIAL_RETRY_DELAY`]
+fn retry_delay(retry: u32) -> Duration {
+    INITIAL_RETRY_DELAY * 2u32.saturating_pow(retry.saturating_sub(1))
+}
+
+/// A 5xx reply (unknown mailbox, rejected content) fails the same way every time
+fn is_permanent_reply(code: Code) -> bool {
+    code.severity == Severity::PermanentNegativeCompletion
+}
+
+/// Whether retrying `error` cannot help: a permanent reply, or a message the client itself
+/// refused to send
+fn is_permanent(error: &lettre::transport::smtp::Error) -> bool {
+    error.is_client() || error.status().is_some_and(is_permanent_reply)
+}
+
+/// Sends emails itself over SMTP, for deployments without the external mail service
+pub struct SmtpMailSender {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    max_retries: u32,
+    rate_limiter: Option<RateLimiter>,
+}
+
+impl SmtpMailSender {
+    /// Configure from the `SMTP_*` variables. With `SMTP_STARTTLS=false` the connection
+    /// stays in plain text, which is only meant for local sinks such as MailHog.
+    pub fn from_config() -> Result<Self> {
+        let host = APP_CONFIG
+            .smtp_host
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("SMTP_HOST is required for the smtp mail backend"))?;
+        let from = APP_CONFIG
+            .smtp_from
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("SMTP_FROM is required for the smtp mail backend"))?
+            .parse::<Mailbox>()
+            .context("Invalid SMTP_FROM")?;
+
+        let mut builder = if APP_CONFIG.smtp_starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .context("Failed to configure SMTP STARTTLS")?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+        };
+        builder = builder
+            .port(APP_CONFIG.smtp_port)
+            .timeout(Some(SMTP_TIMEOUT));
+        if let (Some(username), Some(password)) = (
+            APP_CONFIG.smtp_username.as_ref(),
+            APP_CONFIG.smtp_password.as_ref(),
+        ) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            max_retries: APP_CONFIG.smtp_max_retries,
+            rate_limiter: RateLimiter::new(APP_CONFIG.smtp_rate_limit_per_minute),
+        })
+    }
+
+    fn message(&self, to: &str, email: &RenderedEmail) -> Result<Message> {
+        let to = to
+            .parse::<Mailbox>()
+            .with_context(|| format!("Invalid recipient '{}'", to))?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject.clone())
+            .multipart(MultiPart::alternative_plain_html(
+                email.text.clone(),
+                email.html.clone(),
+            ))?;
+        Ok(message)
+    }
+}
+
+#[async_trait]
+impl MailSender for SmtpMailSender {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    async fn send(&self, to: &str, email: &RenderedEmail) -> Result<()> {
+        let message = self.message(to, email)?;
+
+        let mut attempt = 0;
+        loop {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+            let error = match self.transport.send(message.clone()).await {
+                Ok(_) => return Ok(()),
+                Err(e) => e,
+            };
+
+            if is_permanent(&error) || attempt >= self.max_retries {
+                return Err(error).context("SMTP server did not accept the email");
+            }
+
+            attempt += 1;
+            let delay = retry_delay(attempt);
+            tracing::warn!(
+                "Failed to send email over SMTP, retry {}/{} in {:?}: {}",
+                attempt,
+                self.max_retries,
+                delay,
+                error
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lettre::transport::smtp::response::{Category, Detail};
+
+    #[tokio::test]
+    async fn test_rate_limiter_spaces_sends() {
+        let limiter = RateLimiter::new(60).unwrap();
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve(now).await, now);
+        assert_eq!(limiter.reserve(now).await, now + Duration::from_secs(1));
+        assert_eq!(limiter.reserve(now).await, now + Duration::from_secs(2));
+
+        // Slots left unused while idle are not saved up for a burst
+        let later = now + Duration::from_secs(10);
+        assert_eq!(limiter.reserve(later).await, later);
+        assert_eq!(limiter.reserve(later).await, later + Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_zero_rate_limit_disables_limiter() {
+        assert!(RateLimiter::new(0).is_none());
+        assert!(RateLimiter::new(1).is_some());
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        assert_eq!(retry_delay(1), INITIAL_RETRY_DELAY);
+        assert_eq!(retry_delay(2), INITIAL_RETRY_DELAY * 2);
+        assert_eq!(retry_delay(4), INITIAL_RETRY_DELAY * 8);
+    }
+
+    #[test]
+    fn test_only_5xx_replies_are_permanent() {
+        let reply = |severity| Code::new(severity, Category::MailSystem, Detail::Zero);
+
+        assert!(is_permanent_reply(reply(
+            Severity::PermanentNegativeCompletion
+        )));
+        assert!(!is_permanent_reply(reply(
+            Severity::TransientNegativeCompletion
+        )));
+        assert!(!is_permanent_reply(reply(Severity::PositiveCompletion)));
+    }
+}
@@ -0,0 +1,427 @@
+//! Transactional email templates.
+//!
+//! Each [`EmailTemplate`] variant carries the parameters its text needs and renders to a
+//! subject, a plain text body and an HTML body in every [`Locale`]. Rendering is pure, so
+//! templates can be previewed and tested without a broker or configuration, see
+//! [`EmailTemplate::samples`] and the `preview_emails` binary.
+
+use chrono::NaiveDateTime;
+use std::fmt;
+use std::str::FromStr;
+
+const PRODUCT_NAME: &str = "NGON";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    Vi,
+    En,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::Vi, Locale::En];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::Vi => "vi",
+            Locale::En => "en",
+        }
+    }
+
+    /// The first supported language of an `Accept-Language` header, in the order the
+    /// client listed them
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        header
+            .split(',')
+            .filter_map(|entry| entry.split(';').next())
+            .find_map(|tag| tag.trim().split('-').next()?.parse().ok())
+    }
+
+    fn pick(self, vi: &'static str, en: &'static str) -> &'static str {
+        match self {
+            Locale::Vi => vi,
+            Locale::En => en,
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "vi" => Ok(Locale::Vi),
+            "en" => Ok(Locale::En),
+            other => anyhow::bail!("Unsupported locale '{}'", other),
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum EmailTemplate {
+    ResetPassword {
+        otp_code: String,
+        expires_in_minutes: i64,
+    },
+    EnableMfa {
+        otp_code: String,
+        expires_in_minutes: i64,
+    },
+    RequestScheduled {
+        first_name: String,
+        scheduled_at: NaiveDateTime,
+        request_content: String,
+        /// Free text from the manager who scheduled the request
+        note: Option<String>,
+    },
+    /// The student was registered on chain and can sign in
+    AccountActivation {
+        full_name: String,
+        email: String,
+        student_code: String,
+    },
+}
+
+/// A template rendered in one locale, before the layout is applied. `text` and `html`
+/// paragraphs are in the same order.
+struct Content {
+    subject: String,
+    greeting: String,
+    paragraphs: Vec<Paragraph>,
+}
+
+enum Paragraph {
+    Text(String),
+    /// A code or value the reader has to copy, shown prominently
+    Code(String),
+    /// Label and value pairs
+    Details(Vec<(&'static str, String)>),
+}
+
+impl EmailTemplate {
+    /// Stable name, used for logs and previews
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmailTemplate::ResetPassword { .. } => "reset_password",
+            EmailTemplate::EnableMfa { .. } => "enable_mfa",
+            EmailTemplate::RequestScheduled { .. } => "request_scheduled",
+            EmailTemplate::AccountActivation { .. } => "account_activation",
+        }
+    }
+
+    /// One instance of every template with made-up parameters, for previews and tests
+    pub fn samples() -> Vec<EmailTemplate> {
+        vec![
+            EmailTemplate::ResetPassword {
+                otp_code: "X7K2M9QA".to_string(),
+                expires_in_minutes: 10,
+            },
+            EmailTemplate::EnableMfa {
+                otp_code: "Q4ZP8L1W".to_string(),
+                expires_in_minutes: 5,
+            },
+            EmailTemplate::RequestScheduled {
+                first_name: "An".to_string(),
+                scheduled_at: NaiveDateTime::parse_from_str(
+                    "2026-10-20 09:30:00",
+                    "%Y-%m-%d %H:%M:%S",
+                )
+                .expect("valid sample date"),
+                request_content: "Cấp lại bảng điểm <học kỳ 1>".to_string(),
+                note: Some("Vui lòng mang theo thẻ sinh viên.".to_string()),
+            },
+            EmailTemplate::AccountActivation {
+                full_name: "Nguyễn Văn An".to_string(),
+                email: "an.nguyen@example.edu.vn".to_string(),
+                student_code: "SV2026001".to_string(),
+            },
+        ]
+    }
+
+    pub fn render(&self, locale: Locale) -> RenderedEmail {
+        let content = self.content(locale);
+        RenderedEmail {
+            subject: content.subject.clone(),
+            text: render_text(&content, locale),
+            html: render_html(&content, locale),
+        }
+    }
+
+    fn content(&self, locale: Locale) -> Content {
+        match self {
+            EmailTemplate::ResetPassword {
+                otp_code,
+                expires_in_minutes,
+            } => Content {
+                subject: locale
+                    .pick("Mã OTP đặt lại mật khẩu", "Reset Password OTP")
+                    .to_string(),
+                greeting: locale.pick("Xin chào,", "Hello,").to_string(),
+                paragraphs: vec![
+                    Paragraph::Text(
+                        locale
+                            .pick(
+                                "Mã OTP để đặt lại mật khẩu của bạn là:",
+                                "Your OTP code for password reset is:",
+                            )
+                            .to_string(),
+                    ),
+                    Paragraph::Code(otp_code.clone()),
+                    Paragraph::Text(expiry(locale, *expires_in_minutes)),
+                    Paragraph::Text(
+                        locale
+                            .pick(
+                                "Nếu bạn không yêu cầu đặt lại mật khẩu, hãy bỏ qua email này.",
+                                "If you did not ask to reset your password, ignore this email.",
+                            )
+                            .to_string(),
+                    ),
+                ],
+            },
+            EmailTemplate::EnableMfa {
+                otp_code,
+                expires_in_minutes,
+            } => Content {
+                subject: locale
+                    .pick(
+                        "Bật xác thực hai lớp - Mã xác minh",
+                        "Enable MFA - Verification Code",
+                    )
+                    .to_string(),
+                greeting: locale.pick("Xin chào,", "Hello,").to_string(),
+                paragraphs: vec![
+                    Paragraph::Text(
+                        locale
+                            .pick(
+                                "Mã OTP để bật xác thực hai lớp của bạn là:",
+                                "Your OTP code to enable MFA is:",
+                            )
+                            .to_string(),
+                    ),
+                    Paragraph::Code(otp_code.clone()),
+                    Paragraph::Text(expiry(locale, *expires_in_minutes)),
+                ],
+            },
+            EmailTemplate::RequestScheduled {
+                first_name,
+                scheduled_at,
+                request_content,
+                note,
+            } => {
+                let mut paragraphs = vec![
+                    Paragraph::Text(
+                        locale
+                            .pick(
+                                "Yêu cầu của bạn đã được lên lịch xử lý.",
+                                "Your request has been scheduled.",
+                            )
+                            .to_string(),
+                    ),
+                    Paragraph::Details(vec![
+                        (
+                            locale.pick("Thời gian", "Scheduled at"),
+                            scheduled_at.format("%d/%m/%Y %H:%M").to_string(),
+                        ),
+                        (
+                            locale.pick("Nội dung yêu cầu", "Request"),
+                            request_content.clone(),
+                        ),
+                    ]),
+                ];
+                if let Some(note) = note.as_ref().filter(|note| !note.trim().is_empty()) {
+                    paragraphs.push(Paragraph::Text(note.clone()));
+                }
+
+                Content {
+                    subject: locale
+                        .pick("Lịch hẹn xử lý yêu cầu", "Your request has been scheduled")
+                        .to_string(),
+                    greeting: match locale {
+                        Locale::Vi => format!("Xin chào {},", first_name),
+                        Locale::En => format!("Hello {},", first_name),
+                    },
+                    paragraphs,
+                }
+            }
+            EmailTemplate::AccountActivation {
+                full_name,
+                email,
+                student_code,
+            } => Content {
+                subject: locale
+                    .pick("Tài khoản của bạn đã được kích hoạt", "Your account is active")
+                    .to_string(),
+                greeting: match locale {
+                    Locale::Vi => format!("Xin chào {},", full_name),
+                    Locale::En => format!("Hello {},", full_name),
+                },
+                paragraphs: vec![
+                    Paragraph::Text(
+                        locale
+                            .pick(
+                                "Tài khoản sinh viên của bạn đã được kích hoạt. Bạn có thể đăng nhập bằng địa chỉ email này.",
+                                "Your student account is now active. You can sign in with this email address.",
+                            )
+                            .to_string(),
+                    ),
+                    Paragraph::Details(vec![
+                        (locale.pick("Mã sinh viên", "Student code"), student_code.clone()),
+                        ("Email", email.clone()),
+                    ]),
+                ],
+            },
+        }
+    }
+}
+
+fn expiry(locale: Locale, minutes: i64) -> String {
+    match locale {
+        Locale::Vi => format!("Mã có hiệu lực trong {} phút.", minutes),
+        Locale::En => format!("This code will expire in {} minutes.", minutes),
+    }
+}
+
+fn sign_off(locale: Locale) -> String {
+    match locale {
+        Locale::Vi => format!("Trân trọng,\nHệ thống quản lý {}", PRODUCT_NAME),
+        Locale::En => format!("Best regards,\nThe {} team", PRODUCT_NAME),
+    }
+}
+
+fn render_text(content: &Content, locale: Locale) -> String {
+    let mut blocks = vec![content.greeting.clone()];
+    for paragraph in &content.paragraphs {
+        blocks.push(match paragraph {
+            Paragraph::Text(text) | Paragraph::Code(text) => text.clone(),
+            Paragraph::Details(details) => details
+                .iter()
+                .map(|(label, value)| format!("{}: {}", label, value))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        });
+    }
+    blocks.push(sign_off(locale));
+    blocks.join("\n\n")
+}
+
+fn render_html(content: &Content, locale: Locale) -> String {
+    let mut body = format!("<p>{}</p>\n", escape_html(&content.greeting));
+    for paragraph in &content.paragraphs {
+        match paragraph {
+            Paragraph::Text(text) => body.push_str(&format!("<p>{}</p>\n", escape_html(text))),
+            Paragraph::Code(code) => body.push_str(&format!(
+                "<p style=\"font-size:24px;font-weight:bold;letter-spacing:4px;font-family:monospace\">{}</p>\n",
+                escape_html(code)
+            )),
+            Paragraph::Details(details) => {
+                body.push_str("<table cellpadding=\"4\">\n");
+                for (label, value) in details {
+                    body.push_str(&format!(
+                        "<tr><td><strong>{}</strong></td><td>{}</td></tr>\n",
+                        escape_html(label),
+                        escape_html(value)
+                    ));
+                }
+                body.push_str("</table>\n");
+            }
+        }
+    }
+    body.push_str(&format!(
+        "<p>{}</p>\n",
+        escape_html(&sign_off(locale)).replace('\n', "<br>")
+    ));
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body style=\"font-family:Arial,sans-serif;color:#222;max-width:600px;margin:0 auto;padding:16px\">\n{}</body>\n</html>\n",
+        locale.code(),
+        escape_html(&content.subject),
+        body
+    )
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_template_renders_in_every_locale() {
+        for template in EmailTemplate::samples() {
+            for locale in Locale::ALL {
+                let email = template.render(locale);
+                assert!(!email.subject.is_empty(), "{} {}", template.name(), locale);
+                assert!(email.html.contains(&format!("lang=\"{}\"", locale)));
+                assert!(
+                    !email.text.contains("<p>"),
+                    "{} {}",
+                    template.name(),
+                    locale
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parameters_are_escaped_in_html_only() {
+        let template = EmailTemplate::samples()
+            .into_iter()
+            .find(|template| template.name() == "request_scheduled")
+            .unwrap();
+        let email = template.render(Locale::Vi);
+
+        assert!(email.html.contains("&lt;học kỳ 1&gt;"));
+        assert!(!email.html.contains("<học kỳ 1>"));
+        assert!(email.text.contains("Cấp lại bảng điểm <học kỳ 1>"));
+    }
+
+    #[test]
+    fn test_expiry_matches_parameter() {
+        let email = EmailTemplate::EnableMfa {
+            otp_code: "ABC".to_string(),
+            expires_in_minutes: 5,
+        }
+        .render(Locale::En);
+
+        assert!(email.text.contains("expire in 5 minutes"));
+    }
+
+    #[test]
+    fn test_locale_from_accept_language() {
+        assert_eq!(
+            Locale::from_accept_language("en-US,en;q=0.9,vi;q=0.8"),
+            Some(Locale::En)
+        );
+        assert_eq!(
+            Locale::from_accept_language("fr-FR, vi;q=0.5"),
+            Some(Locale::Vi)
+        );
+        assert_eq!(Locale::from_accept_language("fr"), None);
+    }
+}
@@ -8,7 +8,10 @@ pub mod document_type;
 pub mod documents;
 pub mod file_upload_history;
 pub mod major;
+pub mod notification;
 pub mod otp_verify;
+pub mod outbox_event;
+pub mod processed_message;
 pub mod request;
 pub mod score_board;
 pub mod sea_orm_active_enums;
@@ -19,3 +22,4 @@ pub mod user_mfa;
 pub mod votes;
 pub mod voting_events;
 pub mod wallet;
+pub mod wallet_export_audit;
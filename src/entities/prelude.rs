@@ -6,7 +6,10 @@ pub use super::document_type::Entity as DocumentType;
 pub use super::documents::Entity as Documents;
 pub use super::file_upload_history::Entity as FileUploadHistory;
 pub use super::major::Entity as Major;
+pub use super::notification::Entity as Notification;
 pub use super::otp_verify::Entity as OtpVerify;
+pub use super::outbox_event::Entity as OutboxEvent;
+pub use super::processed_message::Entity as ProcessedMessage;
 pub use super::request::Entity as Request;
 pub use super::score_board::Entity as ScoreBoard;
 pub use super::semester_summary::Entity as SemesterSummary;
@@ -16,3 +19,4 @@ pub use super::user_mfa::Entity as UserMfa;
 pub use super::votes::Entity as Votes;
 pub use super::voting_events::Entity as VotingEvents;
 pub use super::wallet::Entity as Wallet;
+pub use super::wallet_export_audit::Entity as WalletExportAudit;
@@ -4,6 +4,45 @@ use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "notification_kind")]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    #[sea_orm(string_value = "student_registered")]
+    StudentRegistered,
+    #[sea_orm(string_value = "student_registration_failed")]
+    StudentRegistrationFailed,
+    #[sea_orm(string_value = "students_batch_registered")]
+    StudentsBatchRegistered,
+    #[sea_orm(string_value = "students_batch_registration_failed")]
+    StudentsBatchRegistrationFailed,
+    #[sea_orm(string_value = "student_activated")]
+    StudentActivated,
+    #[sea_orm(string_value = "student_activation_failed")]
+    StudentActivationFailed,
+    #[sea_orm(string_value = "student_deactivated")]
+    StudentDeactivated,
+    #[sea_orm(string_value = "student_deactivation_failed")]
+    StudentDeactivationFailed,
+    #[sea_orm(string_value = "manager_registered")]
+    ManagerRegistered,
+    #[sea_orm(string_value = "manager_registration_failed")]
+    ManagerRegistrationFailed,
+    #[sea_orm(string_value = "manager_removed")]
+    ManagerRemoved,
+    #[sea_orm(string_value = "manager_removal_failed")]
+    ManagerRemovalFailed,
+    #[sea_orm(string_value = "role_assigned")]
+    RoleAssigned,
+    #[sea_orm(string_value = "role_assignment_failed")]
+    RoleAssignmentFailed,
+    #[sea_orm(string_value = "user_import_row_failed")]
+    UserImportRowFailed,
+    #[sea_orm(string_value = "users_import_completed")]
+    UsersImportCompleted,
+}
 #[derive(
     Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
 )]
@@ -33,6 +33,7 @@ pub struct Model {
     pub role: RoleEnum,
     pub student_code: Option<String>,
     pub status: UserStatus,
+    pub mfa_grace_started_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -53,6 +54,7 @@ pub enum Column {
     Role,
     StudentCode,
     Status,
+    MfaGraceStartedAt,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -72,6 +74,7 @@ pub enum Relation {
     Certificate,
     DocumentType,
     FileUploadHistory,
+    Notification,
     OtpVerify,
     Request,
     ScoreBoard,
@@ -101,6 +104,7 @@ impl ColumnTrait for Column {
             Self::Role => RoleEnum::db_type().get_column_type().to_owned().def(),
             Self::StudentCode => ColumnType::String(StringLen::None).def().null(),
             Self::Status => UserStatus::db_type().get_column_type().to_owned().def(),
+            Self::MfaGraceStartedAt => ColumnType::DateTime.def().null(),
         }
     }
 }
@@ -111,6 +115,7 @@ impl RelationTrait for Relation {
             Self::Certificate => Entity::has_many(super::certificate::Entity).into(),
             Self::DocumentType => Entity::has_many(super::document_type::Entity).into(),
             Self::FileUploadHistory => Entity::has_many(super::file_upload_history::Entity).into(),
+            Self::Notification => Entity::has_many(super::notification::Entity).into(),
             Self::OtpVerify => Entity::has_many(super::otp_verify::Entity).into(),
             Self::Request => Entity::has_many(super::request::Entity).into(),
             Self::ScoreBoard => Entity::has_many(super::score_board::Entity).into(),
@@ -140,6 +145,12 @@ impl Related<super::file_upload_history::Entity> for Entity {
     }
 }
 
+impl Related<super::notification::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Notification.def()
+    }
+}
+
 impl Related<super::otp_verify::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::OtpVerify.def()
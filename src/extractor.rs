@@ -1,17 +1,23 @@
-use crate::entities::sea_orm_active_enums::RoleEnum;
-use crate::entities::user;
-use crate::entities::user::Entity as UserModel;
-use crate::static_service::DATABASE_CONNECTION;
+use crate::config::MFA_STEP_UP_MAX_AGE_SECONDS;
+use crate::middleware::mfa_policy::{
+    AuthorizeError, MFA_ENROLLMENT_REQUIRED_MESSAGE, authorize_user, is_enrollment_allowed_path,
+};
+use crate::repositories::UserMfaRepository;
+use crate::utils::step_up_token::{STEP_UP_TOKEN_HEADER, decode_step_up_token};
+use axum::Json;
 use axum::extract::FromRequestParts;
+use axum::response::{IntoResponse, Response};
 use axum_extra::{
     TypedHeader,
     headers::{Authorization, authorization::Bearer},
 };
 use do_an_lib::errors::common_errors::Error as AppErrors;
 use do_an_lib::jwt::JwtManager;
-use do_an_lib::structs::token_claims::{TokenClaims, UserRole};
+use do_an_lib::structs::token_claims::TokenClaims;
+use http::StatusCode;
 use http::request::Parts;
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::Serialize;
+use utoipa::ToSchema;
 
 pub struct AuthClaims(pub TokenClaims);
 
@@ -34,7 +40,7 @@ impl<S> FromRequestParts<S> for AuthClaims
 where
     S: Send + Sync,
 {
-    type Rejection = AppErrors;
+    type Rejection = MfaRejection;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         let TypedHeader(Authorization(bearer)) =
@@ -54,48 +60,191 @@ where
                 bearer.token(),
             )
             .await
-            .expect("check jwt blacklist");
+            .map_err(|e| {
+                // Fail closed: a token that cannot be checked may have been revoked
+                tracing::error!("Failed to check JWT blacklist: {:#}", e);
+                AppErrors::unauthorized("Failed to check jwt token")
+            })?;
 
         if check_jwt_blacklist {
-            return Err(AppErrors::unauthorized("Invalid jwt token"));
+            return Err(AppErrors::unauthorized("Invalid jwt token").into());
         }
 
-        let db = DATABASE_CONNECTION
-            .get()
-            .expect("DATABASE_CONNECTION not set");
+        // Routes needed to enroll stay reachable while enrollment is forced
+        let enforce_mfa = !is_enrollment_allowed_path(parts.uri.path());
+        let claims = authorize_user(token_data, enforce_mfa).await?;
 
-        // Parse user_id from string to UUID
-        let user_uuid = uuid::Uuid::parse_str(&token_data.user_id)
-            .map_err(|_| AppErrors::unauthorized("Invalid user_id in token"))?;
+        Ok(AuthClaims(claims))
+    }
+}
 
-        let user_info = UserModel::find()
-            .filter(<user::Entity as sea_orm::EntityTrait>::Column::UserId.eq(user_uuid))
-            .one(db)
-            .await?;
+/// Error body returned whenever an MFA requirement is not satisfied
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MfaErrorResponse {
+    pub error: String,
+    pub message: String,
+}
 
-        if user_info.is_none() {
-            return Err(AppErrors::unauthorized("User not found"));
+/// Rejection of [`AuthClaims`] and [`RequireRecentMfa`]: an authentication error, or an MFA
+/// requirement the caller does not meet
+pub enum MfaRejection {
+    Auth(AppErrors),
+    Mfa {
+        status: StatusCode,
+        error: &'static str,
+        message: String,
+    },
+}
+
+impl MfaRejection {
+    fn mfa(status: StatusCode, error: &'static str, message: impl Into<String>) -> Self {
+        MfaRejection::Mfa {
+            status,
+            error,
+            message: message.into(),
         }
+    }
+}
 
-        let _path = parts.uri.path().to_string();
-        let _method = &parts.method;
-
-        let user_role = match user_info.unwrap().role {
-            RoleEnum::Admin => UserRole::ADMIN,
-            RoleEnum::Manager => UserRole::MANAGER,
-            RoleEnum::Student => UserRole::STUDENT,
-            RoleEnum::Teacher => UserRole::TEACHER,
-        };
-
-        let claims = TokenClaims {
-            user_id: token_data.user_id,
-            user_name: token_data.user_name,
-            iap: token_data.iap,
-            iat: token_data.iat,
-            exp: token_data.exp,
-            role: user_role,
-        };
+impl From<AppErrors> for MfaRejection {
+    fn from(err: AppErrors) -> Self {
+        MfaRejection::Auth(err)
+    }
+}
 
-        Ok(AuthClaims(claims))
+impl From<AuthorizeError> for MfaRejection {
+    fn from(err: AuthorizeError) -> Self {
+        match err {
+            AuthorizeError::InvalidUserId => {
+                AppErrors::unauthorized("Invalid user_id in token").into()
+            }
+            AuthorizeError::UserNotFound => AppErrors::unauthorized("User not found").into(),
+            // Forced enrollment is a 403 with its own code, not a 401: the token is valid and
+            // the frontend should send the user to enrollment rather than log them out
+            AuthorizeError::EnrollmentRequired => MfaRejection::mfa(
+                StatusCode::FORBIDDEN,
+                "mfa_enrollment_required",
+                MFA_ENROLLMENT_REQUIRED_MESSAGE,
+            ),
+            AuthorizeError::Internal(e) => MfaRejection::mfa(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "auth_check_failed",
+                format!("Failed to authorize user: {}", e),
+            ),
+        }
+    }
+}
+
+impl IntoResponse for MfaRejection {
+    fn into_response(self) -> Response {
+        match self {
+            MfaRejection::Auth(err) => err.into_response(),
+            MfaRejection::Mfa {
+                status,
+                error,
+                message,
+            } => (
+                status,
+                Json(MfaErrorResponse {
+                    error: error.to_string(),
+                    message,
+                }),
+            )
+                .into_response(),
+        }
+    }
+}
+
+/// Requires the caller to present a step-up token (see `POST /api/v1/auth/step-up`)
+/// in the `X-Step-Up-Token` header, minted no longer than `MAX_AGE` seconds ago.
+/// Users without MFA enabled pass through unchanged.
+pub struct RequireRecentMfa<const MAX_AGE: i64 = { MFA_STEP_UP_MAX_AGE_SECONDS }>(
+    pub TokenClaims,
+);
+
+impl<S, const MAX_AGE: i64> FromRequestParts<S> for RequireRecentMfa<MAX_AGE>
+where
+    S: Send + Sync,
+{
+    type Rejection = MfaRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let AuthClaims(claims) = AuthClaims::from_request_parts(parts, state).await?;
+
+        let user_id = uuid::Uuid::parse_str(&claims.user_id)
+            .map_err(|_| MfaRejection::Auth(AppErrors::unauthorized("Invalid user_id in token")))?;
+
+        let mfa_enabled = UserMfaRepository::new()
+            .find_enabled_by_user_id(user_id)
+            .await
+            .map_err(|e| {
+                MfaRejection::mfa(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "mfa_check_failed",
+                    format!("Failed to check MFA status: {}", e),
+                )
+            })?;
+
+        if mfa_enabled.is_none() {
+            return Ok(RequireRecentMfa(claims));
+        }
+
+        let token = parts
+            .headers
+            .get(STEP_UP_TOKEN_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| {
+                MfaRejection::mfa(
+                    StatusCode::UNAUTHORIZED,
+                    "mfa_required",
+                    "MFA is enabled. Please verify MFA at /api/v1/auth/step-up and send the X-Step-Up-Token header",
+                )
+            })?;
+
+        let step_up = decode_step_up_token(token).map_err(|_| {
+            MfaRejection::mfa(
+                StatusCode::UNAUTHORIZED,
+                "invalid_step_up_token",
+                "Step-up token is invalid or expired",
+            )
+        })?;
+
+        if step_up.sub != claims.user_id {
+            return Err(MfaRejection::mfa(
+                StatusCode::UNAUTHORIZED,
+                "invalid_step_up_token",
+                "Step-up token does not belong to this user",
+            ));
+        }
+
+        if step_up.age() > MAX_AGE {
+            return Err(MfaRejection::mfa(
+                StatusCode::UNAUTHORIZED,
+                "mfa_too_old",
+                format!(
+                    "MFA verification is older than {} seconds. Please verify MFA again",
+                    MAX_AGE
+                ),
+            ));
+        }
+
+        Ok(RequireRecentMfa(claims))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_enrollment_required_is_forbidden_not_unauthorized() {
+        let response = MfaRejection::from(AuthorizeError::EnrollmentRequired).into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "mfa_enrollment_required");
     }
 }
@@ -0,0 +1,155 @@
+use do_an_lib::jwt::JwtManager;
+use do_an_lib::structs::token_claims::TokenClaims;
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Status};
+
+use crate::config::APP_CONFIG;
+use crate::middleware::mfa_policy::{
+    AuthorizeError, MFA_ENROLLMENT_REQUIRED_MESSAGE, authorize_user,
+};
+use crate::redis_service::redis_service::JwtBlacklist;
+use crate::utils::step_up_token::jwt_secret;
+
+/// Interceptor type of every service mounted on the gRPC server
+pub type AuthInterceptor = fn(Request<()>) -> Result<Request<()>, Status>;
+
+/// Identity attached to every gRPC request by [`authenticate`]
+#[derive(Debug, Clone)]
+pub enum GrpcCaller {
+    /// Internal service authenticated by a client certificate or `GRPC_SERVICE_TOKEN`
+    Service,
+    /// End user authenticated by an access token
+    User { user_id: String, token: String },
+}
+
+impl GrpcCaller {
+    pub fn from_request<T>(request: &Request<T>) -> Result<Self, Status> {
+        request
+            .extensions()
+            .get::<GrpcCaller>()
+            .cloned()
+            .ok_or_else(|| Status::unauthenticated("Missing caller identity"))
+    }
+
+    /// Reject callers that are not internal services
+    pub fn require_service(&self) -> Result<(), Status> {
+        match self {
+            GrpcCaller::Service => Ok(()),
+            GrpcCaller::User { .. } => Err(Status::permission_denied(
+                "This RPC is only available to internal services",
+            )),
+        }
+    }
+
+    pub fn user_id(&self) -> Option<&str> {
+        match self {
+            GrpcCaller::Service => None,
+            GrpcCaller::User { user_id, .. } => Some(user_id),
+        }
+    }
+
+    /// Claims of a user caller, validated like `AuthClaims`: the token must not be
+    /// blacklisted, the user must still exist and be enrolled in MFA when their role
+    /// requires it, and the role is read from the database
+    pub async fn user_claims(&self) -> Result<TokenClaims, Status> {
+        let GrpcCaller::User { token, .. } = self else {
+            return Err(Status::permission_denied(
+                "This RPC acts on behalf of a user and requires their access token",
+            ));
+        };
+
+        let token_data = decode_access_token(token)
+            .map_err(|_| Status::unauthenticated("Invalid bearer token"))?;
+
+        let is_blacklisted = JwtBlacklist::check_jwt_in_blacklist(&token_data.user_id, token)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to check token: {}", e)))?;
+        if is_blacklisted {
+            return Err(Status::unauthenticated("Invalid bearer token"));
+        }
+
+        authorize_user(token_data, true).await.map_err(|e| match e {
+            AuthorizeError::InvalidUserId => Status::unauthenticated("Invalid user_id in token"),
+            AuthorizeError::UserNotFound => Status::unauthenticated("User not found"),
+            AuthorizeError::EnrollmentRequired => {
+                Status::permission_denied(MFA_ENROLLMENT_REQUIRED_MESSAGE)
+            }
+            AuthorizeError::Internal(e) => {
+                Status::internal(format!("Failed to authorize user: {:#}", e))
+            }
+        })
+    }
+}
+
+/// Map the `(StatusCode, String)` errors of the shared REST/gRPC service layer to a status
+pub fn http_error_to_status((status, message): (http::StatusCode, String)) -> Status {
+    match status {
+        http::StatusCode::BAD_REQUEST | http::StatusCode::UNPROCESSABLE_ENTITY => {
+            Status::invalid_argument(message)
+        }
+        http::StatusCode::UNAUTHORIZED => Status::unauthenticated(message),
+        http::StatusCode::FORBIDDEN => Status::permission_denied(message),
+        http::StatusCode::NOT_FOUND => Status::not_found(message),
+        http::StatusCode::CONFLICT => Status::already_exists(message),
+        http::StatusCode::TOO_MANY_REQUESTS => Status::resource_exhausted(message),
+        status if status.is_server_error() => Status::internal(message),
+        _ => Status::unknown(message),
+    }
+}
+
+/// Tonic interceptor requiring mTLS or a bearer token on every call.
+///
+/// The access token blacklist lives in Redis and can't be checked from a synchronous
+/// interceptor, so handlers acting on behalf of a user must still check it themselves.
+pub fn authenticate(mut request: Request<()>) -> Result<Request<()>, Status> {
+    let has_client_cert = request
+        .peer_certs()
+        .is_some_and(|certs| !certs.is_empty());
+
+    let caller = if has_client_cert {
+        GrpcCaller::Service
+    } else {
+        let token = bearer_token(request.metadata())?;
+
+        match APP_CONFIG.grpc_service_token.as_deref() {
+            Some(service_token) if constant_time_eq(token, service_token) => GrpcCaller::Service,
+            _ => {
+                let claims = decode_access_token(token)
+                    .map_err(|_| Status::unauthenticated("Invalid bearer token"))?;
+
+                GrpcCaller::User {
+                    user_id: claims.user_id,
+                    token: token.to_string(),
+                }
+            }
+        }
+    };
+
+    request.extensions_mut().insert(caller);
+    Ok(request)
+}
+
+/// Validate the signature and expiry of an access token issued by `/api/v1/auth/login`
+pub fn decode_access_token(token: &str) -> anyhow::Result<TokenClaims> {
+    JwtManager::new(jwt_secret())
+        .decode_jwt(token)
+        .map_err(|_| anyhow::anyhow!("Invalid access token"))
+}
+
+fn bearer_token(metadata: &MetadataMap) -> Result<&str, Status> {
+    metadata
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| Status::unauthenticated("Bearer token or client certificate required"))
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a
+            .bytes()
+            .zip(b.bytes())
+            .fold(0u8, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+use tonic::server::NamedService;
+use tonic_health::ServingStatus;
+use tonic_health::server::HealthReporter;
+
+use super::job_service::JobServiceImpl;
+use super::job_service::job::job_service_server::JobServiceServer;
+use super::mfa_service::MfaServiceImpl;
+use super::mfa_service::mfa::mfa_service_server::MfaServiceServer;
+use super::token_service::TokenServiceImpl;
+use super::token_service::auth::token_service_server::TokenServiceServer;
+use super::user_admin_service::UserAdminServiceImpl;
+use super::user_admin_service::user_admin::user_admin_service_server::UserAdminServiceServer;
+use super::wallet_service::WalletServiceImpl;
+use super::wallet_service::wallet::wallet_service_server::WalletServiceServer;
+use crate::kv_store::get_kv_store;
+use crate::rabbitmq_service::connection::rabbitmq;
+use crate::static_service::DATABASE_CONNECTION;
+
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const DEPENDENCY_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// gRPC services whose status follows the database and Redis
+const SERVICE_NAMES: &[&str] = &[
+    WalletServiceServer::<WalletServiceImpl>::NAME,
+    MfaServiceServer::<MfaServiceImpl>::NAME,
+    TokenServiceServer::<TokenServiceImpl>::NAME,
+    JobServiceServer::<JobServiceImpl>::NAME,
+    UserAdminServiceServer::<UserAdminServiceImpl>::NAME,
+];
+
+#[derive(Debug, Clone, Copy)]
+pub struct DependencyHealth {
+    pub database: bool,
+    pub redis: bool,
+    pub rabbitmq: bool,
+}
+
+impl DependencyHealth {
+    pub async fn check() -> Self {
+        let database = async {
+            match DATABASE_CONNECTION.get() {
+                Some(db) => db.ping().await.is_ok(),
+                None => false,
+            }
+        };
+
+        let redis = async {
+            match get_kv_store().await {
+                Ok(store) => store.ping().await.is_ok(),
+                Err(_) => false,
+            }
+        };
+
+        let (database, redis) = tokio::join!(
+            tokio::time::timeout(DEPENDENCY_CHECK_TIMEOUT, database),
+            tokio::time::timeout(DEPENDENCY_CHECK_TIMEOUT, redis),
+        );
+
+        Self {
+            database: database.unwrap_or(false),
+            redis: redis.unwrap_or(false),
+            rabbitmq: rabbitmq().is_connected(),
+        }
+    }
+
+    /// What the gRPC services themselves need
+    pub fn services_healthy(&self) -> bool {
+        self.database && self.redis
+    }
+
+    pub fn all_healthy(&self) -> bool {
+        self.services_healthy() && self.rabbitmq
+    }
+}
+
+fn serving_status(healthy: bool) -> ServingStatus {
+    if healthy {
+        ServingStatus::Serving
+    } else {
+        ServingStatus::NotServing
+    }
+}
+
+/// Refresh `grpc.health.v1` statuses until shutdown. The overall status (`""`) also
+/// requires RabbitMQ; each service only needs the database and Redis.
+pub async fn report_health(mut reporter: HealthReporter, shutdown: CancellationToken) {
+    let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {},
+            _ = shutdown.cancelled() => break,
+        }
+
+        let health = DependencyHealth::check().await;
+        if !health.all_healthy() {
+            tracing::warn!("gRPC health degraded: {:?}", health);
+        }
+
+        reporter
+            .set_service_status("", serving_status(health.all_healthy()))
+            .await;
+        for service_name in SERVICE_NAMES {
+            reporter
+                .set_service_status(service_name, serving_status(health.services_healthy()))
+                .await;
+        }
+    }
+
+    // Let load balancers drain this instance while in-flight calls finish
+    reporter
+        .set_service_status("", ServingStatus::NotServing)
+        .await;
+    for service_name in SERVICE_NAMES {
+        reporter
+            .set_service_status(service_name, ServingStatus::NotServing)
+            .await;
+    }
+}
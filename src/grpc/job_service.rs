@@ -0,0 +1,252 @@
+use std::pin::Pin;
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::CancellationToken;
+use tonic::service::interceptor::InterceptedService;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use super::auth_interceptor::{AuthInterceptor, GrpcCaller, authenticate};
+use crate::entities::file_upload_history;
+use crate::entities::sea_orm_active_enums::RoleEnum;
+use crate::redis_service::job_events::{self, JobEvents};
+use crate::redis_service::redis_service::{
+    ChunkUploadProgress, FileProgress, JwtBlacklist, helper_get_blockchain_registration_progress,
+    helper_get_current_file_progress,
+};
+use crate::repositories::UserRepository;
+use crate::repositories::file_upload_repository::{FileUploadRepository, FileUploadStatus};
+
+pub mod job {
+    tonic::include_proto!("job");
+}
+
+use job::{
+    JobPhase, JobProgress, RowFailure, WatchJobRequest, job_event,
+    job_service_server::{JobService, JobServiceServer},
+};
+
+/// Snapshots are also re-read on this interval, in case a pub/sub message was missed
+const WATCH_JOB_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Events buffered per watcher before a slow client applies backpressure
+const WATCH_JOB_BUFFER: usize = 32;
+
+type WatchJobStream = Pin<Box<dyn Stream<Item = Result<job::JobEvent, Status>> + Send>>;
+
+pub struct JobServiceImpl {
+    /// Ends open streams on server shutdown, otherwise they would hold it forever
+    shutdown: CancellationToken,
+}
+
+fn to_proto_progress(progress: FileProgress) -> job::FileProgress {
+    job::FileProgress {
+        total: progress.total,
+        current: progress.current,
+        percent: progress.percent,
+        success: progress.success,
+        failed: progress.failed,
+    }
+}
+
+fn to_proto_phase(phase: job_events::JobPhase) -> JobPhase {
+    match phase {
+        job_events::JobPhase::Upload => JobPhase::Upload,
+        job_events::JobPhase::CreateUser => JobPhase::CreateUser,
+        job_events::JobPhase::BlockchainRegistration => JobPhase::BlockchainRegistration,
+    }
+}
+
+fn is_terminal_status(status: &str) -> bool {
+    status == FileUploadStatus::Failed.as_str()
+        || status == FileUploadStatus::SyncBlockchain.as_str()
+}
+
+/// Read the upload status and the three progress counters of a job
+async fn job_snapshot(file_upload_history_id: &str) -> Result<JobProgress, Status> {
+    let file_upload = FileUploadRepository::new()
+        .find_by_id(file_upload_history_id)
+        .await
+        .map_err(|e| Status::internal(format!("Failed to find file upload: {}", e)))?;
+
+    let upload = ChunkUploadProgress::get_progress(file_upload_history_id)
+        .await
+        .map_err(|e| Status::internal(format!("Failed to get upload progress: {}", e)))?;
+    let create_user = helper_get_current_file_progress(&file_upload.file_name)
+        .await
+        .map_err(|e| Status::internal(format!("Failed to get create user progress: {}", e)))?;
+    let blockchain_registration =
+        helper_get_blockchain_registration_progress(file_upload_history_id)
+            .await
+            .map_err(|e| {
+                Status::internal(format!("Failed to get blockchain registration progress: {}", e))
+            })?;
+
+    Ok(JobProgress {
+        file_upload_history_id: file_upload_history_id.to_string(),
+        status: file_upload.status,
+        upload: Some(to_proto_progress(upload)),
+        create_user: Some(to_proto_progress(create_user)),
+        blockchain_registration: Some(to_proto_progress(blockchain_registration)),
+    })
+}
+
+impl JobServiceImpl {
+    /// Services, admins and managers may watch any job; other users only their own uploads
+    async fn authorize_watch(
+        caller: &GrpcCaller,
+        file_upload: &file_upload_history::Model,
+    ) -> Result<(), Status> {
+        let GrpcCaller::User { user_id, token } = caller else {
+            return Ok(());
+        };
+
+        let is_blacklisted = JwtBlacklist::check_jwt_in_blacklist(user_id, token)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to check token: {}", e)))?;
+        if is_blacklisted {
+            return Err(Status::unauthenticated("Invalid bearer token"));
+        }
+
+        if user_id == &file_upload.user_id.to_string() {
+            return Ok(());
+        }
+
+        let caller_id = Uuid::parse_str(user_id)
+            .map_err(|_| Status::unauthenticated("Invalid bearer token"))?;
+        let caller_info = UserRepository::new()
+            .find_by_id(caller_id)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to find user: {}", e)))?
+            .ok_or_else(|| Status::unauthenticated("User not found"))?;
+
+        match caller_info.role {
+            RoleEnum::Admin | RoleEnum::Manager => Ok(()),
+            _ => Err(Status::permission_denied(
+                "Only the uploader, admins and managers can watch this job",
+            )),
+        }
+    }
+
+    /// Push snapshots and row failures into `tx` until the job ends, the client goes away
+    /// or the server shuts down
+    async fn forward_job_events(
+        file_upload_history_id: String,
+        events: impl Stream<Item = job_events::JobEvent> + Send,
+        tx: mpsc::Sender<Result<job::JobEvent, Status>>,
+        shutdown: CancellationToken,
+    ) {
+        let mut events = std::pin::pin!(events);
+        let mut poll = tokio::time::interval(WATCH_JOB_POLL_INTERVAL);
+        let mut last_snapshot: Option<JobProgress> = None;
+
+        loop {
+            let row_failure = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tx.closed() => break,
+                _ = poll.tick() => None,
+                event = events.next() => match event {
+                    Some(job_events::JobEvent::RowFailed {
+                        phase,
+                        row_number,
+                        email,
+                        reason,
+                    }) => Some(RowFailure {
+                        phase: to_proto_phase(phase) as i32,
+                        row_number,
+                        email,
+                        reason,
+                    }),
+                    Some(job_events::JobEvent::Progress { .. }) => None,
+                    None => {
+                        let _ = tx
+                            .send(Err(Status::unavailable("Job event subscription closed")))
+                            .await;
+                        break;
+                    }
+                },
+            };
+
+            if let Some(row_failure) = row_failure {
+                let event = job::JobEvent {
+                    event: Some(job_event::Event::RowFailure(row_failure)),
+                };
+                if tx.send(Ok(event)).await.is_err() {
+                    break;
+                }
+            }
+
+            let snapshot = match job_snapshot(&file_upload_history_id).await {
+                Ok(snapshot) => snapshot,
+                Err(status) => {
+                    let _ = tx.send(Err(status)).await;
+                    break;
+                }
+            };
+
+            if last_snapshot.as_ref() == Some(&snapshot) {
+                continue;
+            }
+
+            let is_done = is_terminal_status(&snapshot.status);
+            let event = job::JobEvent {
+                event: Some(job_event::Event::Progress(snapshot.clone())),
+            };
+            if tx.send(Ok(event)).await.is_err() || is_done {
+                break;
+            }
+            last_snapshot = Some(snapshot);
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl JobService for JobServiceImpl {
+    type WatchJobStream = WatchJobStream;
+
+    async fn watch_job(
+        &self,
+        request: Request<WatchJobRequest>,
+    ) -> Result<Response<Self::WatchJobStream>, Status> {
+        let caller = GrpcCaller::from_request(&request)?;
+        let file_upload_history_id = request.into_inner().file_upload_history_id;
+
+        Uuid::parse_str(&file_upload_history_id).map_err(|e| {
+            Status::invalid_argument(format!(
+                "Invalid file_upload_history_id {}: {}",
+                file_upload_history_id, e
+            ))
+        })?;
+
+        let file_upload = FileUploadRepository::new()
+            .find_by_id(&file_upload_history_id)
+            .await
+            .map_err(|_| Status::not_found("File upload not found"))?;
+
+        Self::authorize_watch(&caller, &file_upload).await?;
+
+        // Upload and blockchain counters are keyed by id, user creation by file name
+        let events = JobEvents::subscribe(&[&file_upload_history_id, &file_upload.file_name])
+            .await
+            .map_err(|e| Status::internal(format!("Failed to subscribe to job events: {}", e)))?;
+
+        let (tx, rx) = mpsc::channel(WATCH_JOB_BUFFER);
+        tokio::spawn(Self::forward_job_events(
+            file_upload_history_id,
+            events,
+            tx,
+            self.shutdown.clone(),
+        ));
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+}
+
+pub fn create_job_service(
+    shutdown: CancellationToken,
+) -> InterceptedService<JobServiceServer<JobServiceImpl>, AuthInterceptor> {
+    JobServiceServer::with_interceptor(JobServiceImpl { shutdown }, authenticate as AuthInterceptor)
+}
@@ -1,6 +1,7 @@
+use tonic::service::interceptor::InterceptedService;
 use tonic::{Request, Response, Status};
 
-use crate::repositories::UserMfaRepository;
+use super::auth_interceptor::{AuthInterceptor, GrpcCaller, authenticate};
 
 pub mod mfa {
     tonic::include_proto!("mfa");
@@ -19,8 +20,18 @@ impl MfaService for MfaServiceImpl {
         &self,
         request: Request<VerifyMfaCodeRequest>,
     ) -> Result<Response<VerifyMfaCodeResponse>, Status> {
+        let caller = GrpcCaller::from_request(&request)?;
         let req = request.into_inner();
 
+        // Users may only verify their own codes; services may verify anyone's
+        if let Some(caller_id) = caller.user_id() {
+            if caller_id != req.user_id {
+                return Err(Status::permission_denied(
+                    "Cannot verify MFA codes of another user",
+                ));
+            }
+        }
+
         if req.user_id.is_empty() {
             return Err(Status::invalid_argument("user_id is required"));
         }
@@ -29,71 +40,34 @@ impl MfaService for MfaServiceImpl {
             return Err(Status::invalid_argument("authenticator_code is required"));
         }
 
-        let mfa_repo = UserMfaRepository::new();
-
-        use crate::repositories::mfa_verify_result::MfaVerifyResult;
+        let result = crate::mfa_service::MfaService::new()
+            .verify(&req.user_id, &req.authenticator_code)
+            .await;
 
-        match mfa_repo
-            .verify_mfa_code(&req.user_id, &req.authenticator_code)
-            .await
-        {
-            Ok(result) => {
-                let (is_valid, reason, message, locked_until) = match result {
-                    MfaVerifyResult::Success => (
-                        true,
-                        "success".to_string(),
-                        "MFA code verified successfully".to_string(),
-                        0i64,
-                    ),
-                    MfaVerifyResult::Locked { locked_until } => (
-                        false,
-                        "locked".to_string(),
-                        format!(
-                            "MFA is locked{}",
-                            locked_until
-                                .map(|u| format!(" until {}", u))
-                                .unwrap_or_default()
-                        ),
-                        locked_until.unwrap_or(0),
-                    ),
-                    MfaVerifyResult::CodeAlreadyUsed => (
-                        false,
-                        "code_already_used".to_string(),
-                        "MFA code has already been used".to_string(),
-                        0,
-                    ),
-                    MfaVerifyResult::InvalidCode => (
-                        false,
-                        "invalid_code".to_string(),
-                        "Invalid MFA code".to_string(),
-                        0,
-                    ),
-                    MfaVerifyResult::MfaNotEnabled => (
-                        false,
-                        "mfa_not_enabled".to_string(),
-                        "MFA is not enabled for this user".to_string(),
-                        0,
-                    ),
-                };
-
-                Ok(Response::new(VerifyMfaCodeResponse {
-                    is_valid,
-                    reason,
-                    message,
-                    locked_until: if locked_until > 0 { locked_until } else { 0 },
-                }))
-            }
+        let response = match result {
+            Ok(()) => VerifyMfaCodeResponse {
+                is_valid: true,
+                reason: "success".to_string(),
+                message: "MFA code verified successfully".to_string(),
+                locked_until: 0,
+            },
+            Err(e) if e.is_rejection() => VerifyMfaCodeResponse {
+                is_valid: false,
+                reason: e.reason().to_string(),
+                message: e.to_string(),
+                locked_until: e.locked_until().unwrap_or(0),
+            },
             Err(e) => {
                 tracing::error!("Failed to verify MFA code for user {}: {}", req.user_id, e);
-                Err(Status::internal(format!(
-                    "Failed to verify MFA code: {}",
-                    e
-                )))
+                return Err(e.into());
             }
-        }
+        };
+
+        Ok(Response::new(response))
     }
 }
 
-pub fn create_mfa_service() -> MfaServiceServer<MfaServiceImpl> {
-    MfaServiceServer::new(MfaServiceImpl)
+pub fn create_mfa_service()
+-> InterceptedService<MfaServiceServer<MfaServiceImpl>, AuthInterceptor> {
+    MfaServiceServer::with_interceptor(MfaServiceImpl, authenticate as AuthInterceptor)
 }
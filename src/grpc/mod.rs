@@ -1,5 +1,14 @@
+pub mod auth_interceptor;
+pub mod health;
+pub mod job_service;
 pub mod mfa_service;
 pub mod server;
+pub mod token_service;
+pub mod user_admin_service;
 pub mod wallet_service;
 
 pub use server::start_grpc_server;
+
+/// Encoded descriptors of the protos served here, for server reflection
+pub const FILE_DESCRIPTOR_SET: &[u8] =
+    tonic::include_file_descriptor_set!("auth_service_descriptor");
@@ -1,28 +1,95 @@
 use anyhow::Context;
 use std::net::SocketAddr;
-use tonic::transport::Server;
+use tokio_util::sync::CancellationToken;
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
 use tracing::info;
 
+use super::FILE_DESCRIPTOR_SET;
+use super::health::report_health;
+use super::job_service::create_job_service;
 use super::mfa_service::create_mfa_service;
+use super::token_service::create_token_service;
+use super::user_admin_service::create_user_admin_service;
 use super::wallet_service::create_wallet_service;
 use crate::config::APP_CONFIG;
 
-pub async fn start_grpc_server() -> anyhow::Result<()> {
+/// TLS from `GRPC_TLS_CERT_PATH`/`GRPC_TLS_KEY_PATH`, with client certificate
+/// verification when `GRPC_TLS_CLIENT_CA_PATH` is set
+fn tls_config() -> anyhow::Result<Option<ServerTlsConfig>> {
+    let (Some(cert_path), Some(key_path)) = (
+        APP_CONFIG.grpc_tls_cert_path.as_deref(),
+        APP_CONFIG.grpc_tls_key_path.as_deref(),
+    ) else {
+        return Ok(None);
+    };
+
+    let cert = std::fs::read_to_string(cert_path)
+        .with_context(|| format!("Failed to read gRPC TLS certificate {}", cert_path))?;
+    let key = std::fs::read_to_string(key_path)
+        .with_context(|| format!("Failed to read gRPC TLS key {}", key_path))?;
+
+    let mut tls = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+
+    if let Some(ca_path) = APP_CONFIG.grpc_tls_client_ca_path.as_deref() {
+        let ca = std::fs::read_to_string(ca_path)
+            .with_context(|| format!("Failed to read gRPC client CA {}", ca_path))?;
+        tls = tls
+            .client_ca_root(Certificate::from_pem(ca))
+            .client_auth_optional(!APP_CONFIG.grpc_mtls_required);
+    }
+
+    Ok(Some(tls))
+}
+
+pub async fn start_grpc_server(shutdown: CancellationToken) -> anyhow::Result<()> {
     let addr: SocketAddr = format!("0.0.0.0:{}", APP_CONFIG.grpc_port)
         .parse()
         .context("Invalid gRPC server address")?;
 
     let wallet_service = create_wallet_service();
     let mfa_service = create_mfa_service();
+    let token_service = create_token_service();
+    let job_service = create_job_service(shutdown.clone());
+    let user_admin_service = create_user_admin_service();
 
-    info!("Starting gRPC server on {}", addr);
+    // Health and reflection stay unauthenticated so probes and grpcurl work
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    tokio::spawn(report_health(health_reporter, shutdown.clone()));
 
-    Server::builder()
+    let mut builder = Server::builder();
+    let tls = tls_config()?;
+    let scheme = if tls.is_some() { "TLS" } else { "plaintext" };
+    if let Some(tls) = tls {
+        builder = builder
+            .tls_config(tls)
+            .context("Invalid gRPC TLS configuration")?;
+    }
+
+    let mut router = builder
+        .add_service(health_service)
         .add_service(wallet_service)
         .add_service(mfa_service)
-        .serve(addr)
+        .add_service(token_service)
+        .add_service(job_service)
+        .add_service(user_admin_service);
+
+    if APP_CONFIG.grpc_reflection_enabled {
+        let reflection_service = tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+            .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+            .build_v1()
+            .context("Failed to build gRPC reflection service")?;
+        router = router.add_service(reflection_service);
+    }
+
+    info!("Starting gRPC server on {} ({})", addr, scheme);
+
+    router
+        .serve_with_shutdown(addr, shutdown.cancelled_owned())
         .await
         .context("gRPC server error")?;
 
+    info!("gRPC server stopped");
+
     Ok(())
 }
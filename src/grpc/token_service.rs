@@ -0,0 +1,285 @@
+use do_an_lib::structs::token_claims::{TokenClaims, UserRole};
+use sea_orm::ActiveEnum;
+use tonic::service::interceptor::InterceptedService;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use super::auth_interceptor::{AuthInterceptor, GrpcCaller, authenticate, decode_access_token};
+use crate::entities::sea_orm_active_enums::RoleEnum;
+use crate::entities::user;
+use crate::middleware::permission;
+use crate::redis_service::redis_service::JwtBlacklist;
+use crate::repositories::UserRepository;
+
+pub mod auth {
+    tonic::include_proto!("auth");
+}
+
+use auth::{
+    CheckPermissionRequest, CheckPermissionResponse, GetUserRequest, GetUserResponse,
+    GetUsersByIdsRequest, GetUsersByIdsResponse, IntrospectTokenRequest, IntrospectTokenResponse,
+    Permission,
+    token_service_server::{TokenService, TokenServiceServer},
+};
+
+/// Upper bound on ids accepted by a single `GetUsersByIds` call
+const MAX_USERS_PER_LOOKUP: usize = 500;
+
+pub struct TokenServiceImpl;
+
+/// Why a token is not active, reported in `IntrospectTokenResponse.reason`
+enum InactiveReason {
+    InvalidToken,
+    Blacklisted,
+    UserNotFound,
+}
+
+impl InactiveReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            InactiveReason::InvalidToken => "invalid_token",
+            InactiveReason::Blacklisted => "blacklisted",
+            InactiveReason::UserNotFound => "user_not_found",
+        }
+    }
+}
+
+fn to_user_role(role: &RoleEnum) -> UserRole {
+    match role {
+        RoleEnum::Admin => UserRole::ADMIN,
+        RoleEnum::Manager => UserRole::MANAGER,
+        RoleEnum::Student => UserRole::STUDENT,
+        RoleEnum::Teacher => UserRole::TEACHER,
+    }
+}
+
+fn to_proto_user(user: user::Model) -> auth::User {
+    auth::User {
+        user_id: user.user_id.to_string(),
+        first_name: user.first_name,
+        last_name: user.last_name,
+        email: user.email,
+        role: user.role.to_value(),
+        student_code: user.student_code.unwrap_or_default(),
+        status: user.status.to_value(),
+        created_at: user.create_at.and_utc().timestamp(),
+    }
+}
+
+fn parse_user_id(value: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(value)
+        .map_err(|e| Status::invalid_argument(format!("Invalid user_id {}: {}", value, e)))
+}
+
+impl TokenServiceImpl {
+    /// Validate a token the same way `AuthClaims` does: signature and expiry, blacklist,
+    /// and the user must still exist. The role is refreshed from the database.
+    async fn resolve_token(
+        token: &str,
+    ) -> Result<Result<(TokenClaims, user::Model), (InactiveReason, Option<TokenClaims>)>, Status>
+    {
+        let Ok(token_data) = decode_access_token(token) else {
+            return Ok(Err((InactiveReason::InvalidToken, None)));
+        };
+
+        let is_blacklisted = JwtBlacklist::check_jwt_in_blacklist(&token_data.user_id, token)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to check token blacklist: {}", e)))?;
+
+        let user_info = match Uuid::parse_str(&token_data.user_id) {
+            Ok(user_id) => UserRepository::new()
+                .find_by_id(user_id)
+                .await
+                .map_err(|e| Status::internal(format!("Failed to find user: {}", e)))?,
+            Err(_) => None,
+        };
+
+        let role = match &user_info {
+            Some(user_info) => to_user_role(&user_info.role),
+            None => token_data.role,
+        };
+
+        let claims = TokenClaims {
+            user_id: token_data.user_id,
+            user_name: token_data.user_name,
+            iap: token_data.iap,
+            iat: token_data.iat,
+            exp: token_data.exp,
+            role,
+        };
+
+        if is_blacklisted {
+            return Ok(Err((InactiveReason::Blacklisted, Some(claims))));
+        }
+
+        match user_info {
+            Some(user_info) => Ok(Ok((claims, user_info))),
+            None => Ok(Err((InactiveReason::UserNotFound, Some(claims)))),
+        }
+    }
+
+    fn to_proto_claims(claims: &TokenClaims) -> auth::TokenClaims {
+        auth::TokenClaims {
+            user_id: claims.user_id.clone(),
+            user_name: claims.user_name.clone(),
+            role: user_role_name(&claims.role).to_string(),
+            iat: claims.iat as i64,
+            exp: claims.exp as i64,
+        }
+    }
+}
+
+fn user_role_name(role: &UserRole) -> &'static str {
+    match role {
+        UserRole::ADMIN => "admin",
+        UserRole::MANAGER => "manager",
+        UserRole::TEACHER => "teacher",
+        UserRole::STUDENT => "student",
+    }
+}
+
+#[tonic::async_trait]
+impl TokenService for TokenServiceImpl {
+    async fn introspect_token(
+        &self,
+        request: Request<IntrospectTokenRequest>,
+    ) -> Result<Response<IntrospectTokenResponse>, Status> {
+        GrpcCaller::from_request(&request)?.require_service()?;
+        let req = request.into_inner();
+
+        if req.token.is_empty() {
+            return Err(Status::invalid_argument("token is required"));
+        }
+
+        let response = match Self::resolve_token(&req.token).await? {
+            Ok((claims, _)) => IntrospectTokenResponse {
+                active: true,
+                blacklisted: false,
+                reason: String::new(),
+                claims: Some(Self::to_proto_claims(&claims)),
+            },
+            Err((reason, claims)) => IntrospectTokenResponse {
+                active: false,
+                blacklisted: matches!(reason, InactiveReason::Blacklisted),
+                reason: reason.as_str().to_string(),
+                claims: claims.as_ref().map(Self::to_proto_claims),
+            },
+        };
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_user(
+        &self,
+        request: Request<GetUserRequest>,
+    ) -> Result<Response<GetUserResponse>, Status> {
+        GrpcCaller::from_request(&request)?.require_service()?;
+        let user_id = parse_user_id(&request.into_inner().user_id)?;
+
+        let user_info = UserRepository::new()
+            .find_by_id(user_id)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to find user: {}", e)))?
+            .ok_or_else(|| Status::not_found("User not found"))?;
+
+        Ok(Response::new(GetUserResponse {
+            user: Some(to_proto_user(user_info)),
+        }))
+    }
+
+    async fn get_users_by_ids(
+        &self,
+        request: Request<GetUsersByIdsRequest>,
+    ) -> Result<Response<GetUsersByIdsResponse>, Status> {
+        GrpcCaller::from_request(&request)?.require_service()?;
+        let req = request.into_inner();
+
+        if req.user_ids.len() > MAX_USERS_PER_LOOKUP {
+            return Err(Status::invalid_argument(format!(
+                "At most {} user_ids per call",
+                MAX_USERS_PER_LOOKUP
+            )));
+        }
+
+        let user_ids = req
+            .user_ids
+            .iter()
+            .map(|user_id| parse_user_id(user_id))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let users = UserRepository::new()
+            .find_by_ids(user_ids.clone())
+            .await
+            .map_err(|e| Status::internal(format!("Failed to find users: {}", e)))?;
+
+        let missing_user_ids = user_ids
+            .iter()
+            .filter(|user_id| !users.iter().any(|user| &user.user_id == *user_id))
+            .map(|user_id| user_id.to_string())
+            .collect();
+
+        Ok(Response::new(GetUsersByIdsResponse {
+            users: users.into_iter().map(to_proto_user).collect(),
+            missing_user_ids,
+        }))
+    }
+
+    async fn check_permission(
+        &self,
+        request: Request<CheckPermissionRequest>,
+    ) -> Result<Response<CheckPermissionResponse>, Status> {
+        GrpcCaller::from_request(&request)?.require_service()?;
+        let req = request.into_inner();
+
+        let claims = match Self::resolve_token(&req.token).await? {
+            Ok((claims, _)) => claims,
+            Err((reason, _)) => {
+                return Ok(Response::new(CheckPermissionResponse {
+                    allowed: false,
+                    reason: reason.as_str().to_string(),
+                }));
+            }
+        };
+
+        let result = match req.permission() {
+            Permission::Unspecified => {
+                return Err(Status::invalid_argument("permission is required"));
+            }
+            Permission::Admin => permission::is_admin(&claims),
+            Permission::AdminOrManager => permission::is_admin_or_manager(&claims),
+            Permission::Staff => permission::is_staff(&claims),
+            Permission::AccessUserResource => {
+                let target_user_id = parse_user_id(&req.target_user_id)?;
+                permission::can_access_user_resource(&claims, &target_user_id.to_string())
+            }
+            Permission::ModifyUser => {
+                let target_user_id = parse_user_id(&req.target_user_id)?;
+                let target_user = UserRepository::new()
+                    .find_by_id(target_user_id)
+                    .await
+                    .map_err(|e| Status::internal(format!("Failed to find user: {}", e)))?
+                    .ok_or_else(|| Status::not_found("Target user not found"))?;
+
+                permission::can_modify_user(&claims, &to_user_role(&target_user.role))
+            }
+        };
+
+        let response = match result {
+            Ok(()) => CheckPermissionResponse {
+                allowed: true,
+                reason: String::new(),
+            },
+            Err((_, reason)) => CheckPermissionResponse {
+                allowed: false,
+                reason,
+            },
+        };
+
+        Ok(Response::new(response))
+    }
+}
+
+pub fn create_token_service()
+-> InterceptedService<TokenServiceServer<TokenServiceImpl>, AuthInterceptor> {
+    TokenServiceServer::with_interceptor(TokenServiceImpl, authenticate as AuthInterceptor)
+}
@@ -0,0 +1,238 @@
+use tonic::service::interceptor::InterceptedService;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use super::auth_interceptor::{AuthInterceptor, GrpcCaller, authenticate, http_error_to_status};
+use crate::entities::sea_orm_active_enums::RoleEnum;
+use crate::routes::users::dto;
+use crate::routes::users::service::UserService;
+
+pub mod user_admin {
+    tonic::include_proto!("user_admin");
+}
+
+use user_admin::{
+    CreateUserRequest, CreateUserResponse, DeleteUserRequest, DeleteUserResponse, GetUserRequest,
+    ImportUsersRequest, ImportUsersResponse, ListUsersRequest, ListUsersResponse, Role,
+    UpdateUserRequest, UserDetail,
+    user_admin_service_server::{UserAdminService, UserAdminServiceServer},
+};
+
+const DEFAULT_PAGE: usize = 1;
+const DEFAULT_PAGE_SIZE: usize = 20;
+
+pub struct UserAdminServiceImpl;
+
+fn to_role_enum(role: Role) -> Option<RoleEnum> {
+    match role {
+        Role::Unspecified => None,
+        Role::Admin => Some(RoleEnum::Admin),
+        Role::Manager => Some(RoleEnum::Manager),
+        Role::Student => Some(RoleEnum::Student),
+        Role::Teacher => Some(RoleEnum::Teacher),
+    }
+}
+
+fn to_proto_role(role: &RoleEnum) -> Role {
+    match role {
+        RoleEnum::Admin => Role::Admin,
+        RoleEnum::Manager => Role::Manager,
+        RoleEnum::Student => Role::Student,
+        RoleEnum::Teacher => Role::Teacher,
+    }
+}
+
+fn parse_uuid(field: &str, value: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(value)
+        .map_err(|e| Status::invalid_argument(format!("Invalid {} {}: {}", field, value, e)))
+}
+
+fn parse_major_ids(major_ids: &[String]) -> Result<Vec<Uuid>, Status> {
+    major_ids
+        .iter()
+        .map(|major_id| parse_uuid("major_id", major_id))
+        .collect()
+}
+
+fn to_proto_user_detail(user: dto::UserDetailResponse) -> UserDetail {
+    UserDetail {
+        user_id: user.user_id.to_string(),
+        first_name: user.first_name,
+        last_name: user.last_name,
+        address: user.address,
+        email: user.email,
+        cccd: user.cccd,
+        phone_number: user.phone_number,
+        role: to_proto_role(&user.role) as i32,
+        is_priority: user.is_priority,
+        is_first_login: user.is_first_login,
+        wallet_address: user.wallet_address,
+        major_ids: user.major_ids.iter().map(Uuid::to_string).collect(),
+        major_names: user.major_names,
+        created_at: user.created_at.and_utc().timestamp(),
+        updated_at: user.updated_at.and_utc().timestamp(),
+        student_code: user.student_code,
+    }
+}
+
+#[tonic::async_trait]
+impl UserAdminService for UserAdminServiceImpl {
+    async fn create_user(
+        &self,
+        request: Request<CreateUserRequest>,
+    ) -> Result<Response<CreateUserResponse>, Status> {
+        let claims = GrpcCaller::from_request(&request)?.user_claims().await?;
+        let req = request.into_inner();
+
+        let role =
+            to_role_enum(req.role()).ok_or_else(|| Status::invalid_argument("role is required"))?;
+        let major_ids = parse_major_ids(&req.major_ids)?;
+
+        let payload = dto::CreateUserRequest {
+            first_name: req.first_name,
+            last_name: req.last_name,
+            address: req.address,
+            email: req.email,
+            password: req.password,
+            cccd: req.cccd,
+            phone_number: req.phone_number,
+            role,
+            major_ids: (!major_ids.is_empty()).then_some(major_ids),
+        };
+
+        let user = UserService::create_user(&claims, payload)
+            .await
+            .map_err(http_error_to_status)?;
+
+        Ok(Response::new(CreateUserResponse {
+            user_id: user.user_id.to_string(),
+            first_name: user.first_name,
+            last_name: user.last_name,
+            email: user.email,
+            role: to_proto_role(&user.role) as i32,
+            wallet_address: user.wallet_address,
+            is_first_login: user.is_first_login,
+            created_at: user.created_at.and_utc().timestamp(),
+        }))
+    }
+
+    async fn get_user(
+        &self,
+        request: Request<GetUserRequest>,
+    ) -> Result<Response<UserDetail>, Status> {
+        let claims = GrpcCaller::from_request(&request)?.user_claims().await?;
+        let user_id = parse_uuid("user_id", &request.into_inner().user_id)?;
+
+        let user = UserService::get_user(&claims, user_id)
+            .await
+            .map_err(http_error_to_status)?;
+
+        Ok(Response::new(to_proto_user_detail(user)))
+    }
+
+    async fn list_users(
+        &self,
+        request: Request<ListUsersRequest>,
+    ) -> Result<Response<ListUsersResponse>, Status> {
+        let claims = GrpcCaller::from_request(&request)?.user_claims().await?;
+        let req = request.into_inner();
+
+        let params = dto::UserQueryParams {
+            page: match req.page {
+                0 => DEFAULT_PAGE,
+                page => page as usize,
+            },
+            page_size: match req.page_size {
+                0 => DEFAULT_PAGE_SIZE,
+                page_size => page_size as usize,
+            },
+            role: to_role_enum(req.role()),
+            search: req.search,
+        };
+
+        let list = UserService::list_users(&claims, params)
+            .await
+            .map_err(http_error_to_status)?;
+
+        Ok(Response::new(ListUsersResponse {
+            users: list.users.into_iter().map(to_proto_user_detail).collect(),
+            total: list.total as u64,
+            page: list.page as u32,
+            page_size: list.page_size as u32,
+        }))
+    }
+
+    async fn update_user(
+        &self,
+        request: Request<UpdateUserRequest>,
+    ) -> Result<Response<UserDetail>, Status> {
+        let claims = GrpcCaller::from_request(&request)?.user_claims().await?;
+        let req = request.into_inner();
+        let user_id = parse_uuid("user_id", &req.user_id)?;
+
+        let major_ids = req
+            .major_ids
+            .as_ref()
+            .map(|major_ids| parse_major_ids(&major_ids.major_ids))
+            .transpose()?;
+
+        let payload = dto::UpdateUserRequest {
+            role: to_role_enum(req.role()),
+            first_name: req.first_name,
+            last_name: req.last_name,
+            address: req.address,
+            email: req.email,
+            password: req.password,
+            cccd: req.cccd,
+            phone_number: req.phone_number,
+            major_ids,
+        };
+
+        let user = UserService::update_user(&claims, user_id, payload)
+            .await
+            .map_err(http_error_to_status)?;
+
+        Ok(Response::new(to_proto_user_detail(user)))
+    }
+
+    async fn delete_user(
+        &self,
+        request: Request<DeleteUserRequest>,
+    ) -> Result<Response<DeleteUserResponse>, Status> {
+        let claims = GrpcCaller::from_request(&request)?.user_claims().await?;
+        let user_id = parse_uuid("user_id", &request.into_inner().user_id)?;
+
+        UserService::delete_user(&claims, user_id)
+            .await
+            .map_err(http_error_to_status)?;
+
+        Ok(Response::new(DeleteUserResponse {
+            user_id: user_id.to_string(),
+        }))
+    }
+
+    async fn import_users(
+        &self,
+        request: Request<ImportUsersRequest>,
+    ) -> Result<Response<ImportUsersResponse>, Status> {
+        let claims = GrpcCaller::from_request(&request)?.user_claims().await?;
+        let file_upload_history_id = request.into_inner().file_upload_history_id;
+        parse_uuid("file_upload_history_id", &file_upload_history_id)?;
+
+        let message = UserService::import_users(
+            &claims,
+            dto::CreateUserRequestBulk {
+                history_file_upload_id: file_upload_history_id,
+            },
+        )
+        .await
+        .map_err(http_error_to_status)?;
+
+        Ok(Response::new(ImportUsersResponse { message }))
+    }
+}
+
+pub fn create_user_admin_service()
+-> InterceptedService<UserAdminServiceServer<UserAdminServiceImpl>, AuthInterceptor> {
+    UserAdminServiceServer::with_interceptor(UserAdminServiceImpl, authenticate as AuthInterceptor)
+}
@@ -1,8 +1,18 @@
+use tonic::service::interceptor::InterceptedService;
 use tonic::{Request, Response, Status};
 use uuid::Uuid;
 
+use super::auth_interceptor::{AuthInterceptor, GrpcCaller, authenticate};
 use crate::blockchain::helpers::get_user_private_key;
+use crate::config::MFA_STEP_UP_MAX_AGE_SECONDS;
+use crate::redis_service::redis_service::JwtBlacklist;
+use crate::repositories::wallet_export_audit_repository::{
+    EXPORT_OUTCOME_DENIED, EXPORT_OUTCOME_FAILED, EXPORT_OUTCOME_SUCCESS, WalletExportAuditEntry,
+};
+use crate::repositories::{UserMfaRepository, WalletExportAuditRepository};
 use crate::static_service::get_database_connection;
+use crate::utils::key_wrapping::{WRAPPING_SCHEME, parse_recipient_public_key, wrap_private_key};
+use crate::utils::step_up_token::{STEP_UP_TOKEN_HEADER, decode_step_up_token};
 
 pub mod wallet {
     tonic::include_proto!("wallet");
@@ -15,36 +25,196 @@ use wallet::{
 
 pub struct WalletServiceImpl;
 
+impl WalletServiceImpl {
+    /// Only the wallet owner, with a fresh MFA verification, may export the key
+    async fn authorize_export(
+        caller: &GrpcCaller,
+        step_up_token: Option<&str>,
+        user_id: &Uuid,
+    ) -> Result<(), Status> {
+        let GrpcCaller::User {
+            user_id: caller_id,
+            token,
+        } = caller
+        else {
+            return Err(Status::permission_denied(
+                "Private key export requires the wallet owner's access token",
+            ));
+        };
+
+        let is_blacklisted = JwtBlacklist::check_jwt_in_blacklist(caller_id, token)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to check token: {}", e)))?;
+        if is_blacklisted {
+            return Err(Status::unauthenticated("Invalid bearer token"));
+        }
+
+        if caller_id != &user_id.to_string() {
+            return Err(Status::permission_denied(
+                "Only the wallet owner can export its private key",
+            ));
+        }
+
+        let mfa_enabled = UserMfaRepository::new()
+            .find_enabled_by_user_id(*user_id)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to check MFA status: {}", e)))?
+            .is_some();
+        if !mfa_enabled {
+            return Err(Status::failed_precondition(
+                "MFA must be enabled to export a private key",
+            ));
+        }
+
+        let step_up_token = step_up_token.ok_or_else(|| {
+            Status::unauthenticated(format!("{} metadata is required", STEP_UP_TOKEN_HEADER))
+        })?;
+        let step_up = decode_step_up_token(step_up_token)
+            .map_err(|_| Status::unauthenticated("Invalid step-up token"))?;
+        if &step_up.sub != caller_id {
+            return Err(Status::unauthenticated(
+                "Step-up token does not belong to the caller",
+            ));
+        }
+        if step_up.age() > MFA_STEP_UP_MAX_AGE_SECONDS {
+            return Err(Status::unauthenticated(
+                "MFA verification is too old, please verify again",
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn export(
+        caller: &GrpcCaller,
+        step_up_token: Option<&str>,
+        user_id: &Uuid,
+        recipient_public_key: &str,
+    ) -> Result<ExportPrivateKeyResponse, (&'static str, Status)> {
+        Self::authorize_export(caller, step_up_token, user_id)
+            .await
+            .map_err(|status| (EXPORT_OUTCOME_DENIED, status))?;
+
+        let recipient = if recipient_public_key.is_empty() {
+            None
+        } else {
+            let public_key = parse_recipient_public_key(recipient_public_key).map_err(|e| {
+                (
+                    EXPORT_OUTCOME_FAILED,
+                    Status::invalid_argument(format!("Invalid recipient_public_key: {}", e)),
+                )
+            })?;
+            Some(public_key)
+        };
+
+        let db = get_database_connection().await;
+        let private_key = get_user_private_key(db, user_id).await.map_err(|e| {
+            tracing::error!("Failed to export private key for user {}: {}", user_id, e);
+            (
+                EXPORT_OUTCOME_FAILED,
+                Status::not_found(format!("Failed to export private key: {}", e)),
+            )
+        })?;
+
+        let response = match recipient {
+            Some(recipient) => {
+                let wrapped = wrap_private_key(&private_key, &recipient, &user_id.to_string())
+                    .map_err(|e| {
+                        (
+                            EXPORT_OUTCOME_FAILED,
+                            Status::internal(format!("Failed to wrap private key: {}", e)),
+                        )
+                    })?;
+
+                ExportPrivateKeyResponse {
+                    success: true,
+                    private_key: String::new(),
+                    message: "Private key exported successfully".to_string(),
+                    wrapped_private_key: wrapped,
+                    wrapping_scheme: WRAPPING_SCHEME.to_string(),
+                }
+            }
+            None => ExportPrivateKeyResponse {
+                success: true,
+                private_key,
+                message: "Private key exported successfully".to_string(),
+                wrapped_private_key: String::new(),
+                wrapping_scheme: String::new(),
+            },
+        };
+
+        Ok(response)
+    }
+}
+
 #[tonic::async_trait]
 impl WalletService for WalletServiceImpl {
     async fn export_private_key(
         &self,
         request: Request<ExportPrivateKeyRequest>,
     ) -> Result<Response<ExportPrivateKeyResponse>, Status> {
+        let caller = GrpcCaller::from_request(&request)?;
+        let peer_addr = request.remote_addr().map(|addr| addr.to_string());
+        let step_up_token = request
+            .metadata()
+            .get(STEP_UP_TOKEN_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
         let req = request.into_inner();
 
         let user_id = Uuid::parse_str(&req.user_id)
             .map_err(|e| Status::invalid_argument(format!("Invalid user_id: {}", e)))?;
 
-        let db = get_database_connection().await;
+        let result = Self::export(
+            &caller,
+            step_up_token.as_deref(),
+            &user_id,
+            &req.recipient_public_key,
+        )
+        .await;
 
-        match get_user_private_key(&db, &user_id).await {
-            Ok(private_key) => Ok(Response::new(ExportPrivateKeyResponse {
-                success: true,
-                private_key,
-                message: "Private key exported successfully".to_string(),
-            })),
-            Err(e) => {
-                tracing::error!("Failed to export private key for user {}: {}", user_id, e);
-                Err(Status::not_found(format!(
-                    "Failed to export private key: {}",
-                    e
-                )))
+        let (outcome, reason) = match &result {
+            Ok(_) => (EXPORT_OUTCOME_SUCCESS, None),
+            Err((outcome, status)) => (*outcome, Some(status.message().to_string())),
+        };
+
+        let audit = WalletExportAuditRepository::new()
+            .record(WalletExportAuditEntry {
+                user_id,
+                actor_user_id: caller.user_id().and_then(|id| Uuid::parse_str(id).ok()),
+                peer_addr,
+                wrapped: !req.recipient_public_key.is_empty(),
+                outcome,
+                reason,
+            })
+            .await;
+
+        match (result, audit) {
+            (Ok(response), Ok(_)) => {
+                tracing::info!("Private key of user {} exported", user_id);
+                Ok(Response::new(response))
+            }
+            // Never hand out a key whose export could not be audited
+            (Ok(_), Err(e)) => {
+                tracing::error!("Failed to audit private key export for {}: {}", user_id, e);
+                Err(Status::internal("Failed to record export audit"))
+            }
+            (Err((_, status)), audit) => {
+                if let Err(e) = audit {
+                    tracing::error!("Failed to audit private key export for {}: {}", user_id, e);
+                }
+                tracing::warn!(
+                    "Private key export for user {} rejected: {}",
+                    user_id,
+                    status.message()
+                );
+                Err(status)
             }
         }
     }
 }
 
-pub fn create_wallet_service() -> WalletServiceServer<WalletServiceImpl> {
-    WalletServiceServer::new(WalletServiceImpl)
+pub fn create_wallet_service()
+-> InterceptedService<WalletServiceServer<WalletServiceImpl>, AuthInterceptor> {
+    WalletServiceServer::with_interceptor(WalletServiceImpl, authenticate as AuthInterceptor)
 }
@@ -0,0 +1,103 @@
+use super::KeyProvider;
+use async_trait::async_trait;
+use ethers::signers::Signer;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, H256, Signature};
+use ethers::utils::hash_message;
+use std::fmt;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct AdminSignerError(anyhow::Error);
+
+impl fmt::Display for AdminSignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#}", self.0)
+    }
+}
+
+impl std::error::Error for AdminSignerError {}
+
+/// ethers [`Signer`] for the admin wallet. Every signature is asked of the [`KeyProvider`],
+/// so contract clients built on it never hold the admin key.
+#[derive(Clone)]
+pub struct AdminSigner {
+    provider: Arc<dyn KeyProvider>,
+    address: Address,
+    chain_id: u64,
+}
+
+impl AdminSigner {
+    /// Signer for mainnet until [`Signer::with_chain_id`] says otherwise, like `LocalWallet`
+    pub async fn new(provider: Arc<dyn KeyProvider>) -> anyhow::Result<Self> {
+        let address = provider.admin_address().await?;
+        Ok(Self {
+            provider,
+            address,
+            chain_id: 1,
+        })
+    }
+}
+
+impl fmt::Debug for AdminSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdminSigner")
+            .field("provider", &self.provider.name())
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Signer for AdminSigner {
+    type Error = AdminSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        self.provider
+            .sign_admin_hash(hash_message(message))
+            .await
+            .map_err(AdminSignerError)
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let mut tx = tx.clone();
+        if tx.chain_id().is_none() {
+            tx.set_chain_id(self.chain_id);
+        }
+        self.provider
+            .sign_admin_transaction(&tx)
+            .await
+            .map_err(AdminSignerError)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        let digest = payload
+            .encode_eip712()
+            .map_err(|e| AdminSignerError(anyhow::anyhow!("Failed to encode typed data: {}", e)))?;
+        self.provider
+            .sign_admin_hash(H256::from(digest))
+            .await
+            .map_err(AdminSignerError)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
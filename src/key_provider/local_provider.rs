@@ -0,0 +1,106 @@
+use super::{KeyProvider, private_key_hex};
+use crate::config::APP_CONFIG;
+use crate::utils::envelope_encryption::{KeyRing, decrypt_legacy, is_envelope};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, H256, Signature};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Key material held in process: master keys for envelope encryption and the admin key.
+/// Loaded either from the environment or from a JSON key file.
+pub struct LocalKeyProvider {
+    name: &'static str,
+    key_ring: KeyRing,
+    admin_private_key: String,
+}
+
+/// Layout of the file read by `KEY_PROVIDER=file`
+///
+/// ```json
+/// {
+///   "active_key_id": "2025-12",
+///   "master_keys": { "2025-12": "...", "2025-06": "..." },
+///   "admin_private_key": "0x..."
+/// }
+/// ```
+#[derive(Debug, Deserialize)]
+struct KeyFile {
+    active_key_id: String,
+    master_keys: BTreeMap<String, String>,
+    admin_private_key: String,
+}
+
+impl LocalKeyProvider {
+    /// `ENCRYPTION_KEYS` / `ENCRYPTION_KEY` and `ADMIN_PRIVATE_KEY`
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            name: "env",
+            key_ring: KeyRing::from_config()?,
+            admin_private_key: APP_CONFIG.admin_private_key.clone(),
+        })
+    }
+
+    pub fn from_file(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read key file {}", path))?;
+        let key_file: KeyFile =
+            serde_json::from_str(&content).context("Failed to parse key file")?;
+
+        let master_keys: Vec<(String, String)> = key_file.master_keys.into_iter().collect();
+
+        Ok(Self {
+            name: "file",
+            key_ring: KeyRing::new(&key_file.active_key_id, &master_keys)?,
+            admin_private_key: key_file.admin_private_key,
+        })
+    }
+
+    fn admin_wallet(&self) -> Result<LocalWallet> {
+        self.admin_private_key
+            .parse()
+            .context("Failed to parse admin private key")
+    }
+}
+
+#[async_trait]
+impl KeyProvider for LocalKeyProvider {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn encrypt(&self, plaintext: &str, context: &str) -> Result<String> {
+        self.key_ring.seal(plaintext, context)
+    }
+
+    async fn decrypt(&self, ciphertext: &str, context: &str) -> Result<String> {
+        if is_envelope(ciphertext) {
+            self.key_ring.open(ciphertext, context)
+        } else {
+            decrypt_legacy(ciphertext, context)
+        }
+    }
+
+    fn needs_reencryption(&self, ciphertext: &str) -> bool {
+        self.key_ring.needs_reencryption(ciphertext)
+    }
+
+    async fn admin_address(&self) -> Result<Address> {
+        Ok(self.admin_wallet()?.address())
+    }
+
+    async fn sign_admin_transaction(&self, tx: &TypedTransaction) -> Result<Signature> {
+        Ok(self.admin_wallet()?.sign_transaction(tx).await?)
+    }
+
+    async fn sign_admin_hash(&self, hash: H256) -> Result<Signature> {
+        Ok(self.admin_wallet()?.sign_hash(hash)?)
+    }
+
+    async fn seal_admin_key(&self, context: &str) -> Result<String> {
+        let private_key = private_key_hex(&self.admin_wallet()?);
+        self.key_ring.seal(&private_key, context)
+    }
+}
@@ -0,0 +1,90 @@
+pub mod admin_signer;
+pub mod local_provider;
+pub mod vault_provider;
+
+use crate::config::APP_CONFIG;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, H256, Signature};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+pub use admin_signer::AdminSigner;
+pub use local_provider::LocalKeyProvider;
+pub use vault_provider::VaultTransitKeyProvider;
+
+/// Context used when the admin private key itself is stored as a ciphertext
+pub const CONTEXT_ADMIN_PRIVATE_KEY: &str = "config.admin_private_key";
+
+/// Backend holding the key material for wallet private keys, MFA secrets and the
+/// admin signing key. Callers get ciphertexts, decrypted secrets and admin signatures,
+/// never master keys or the admin key.
+///
+/// Vault Transit cannot sign secp256k1, so the Vault backend still unwraps the admin key
+/// into this process; it stays private to the provider.
+#[async_trait]
+pub trait KeyProvider: Send + Sync {
+    /// Backend name, for logs
+    fn name(&self) -> &'static str;
+
+    /// Encrypt a secret for storage in the column identified by `context`
+    async fn encrypt(&self, plaintext: &str, context: &str) -> Result<String>;
+
+    /// Decrypt a stored secret written by this or an older backend
+    async fn decrypt(&self, ciphertext: &str, context: &str) -> Result<String>;
+
+    /// Whether a stored value should be rewritten under the current key
+    fn needs_reencryption(&self, ciphertext: &str) -> bool;
+
+    /// Address of the wallet that signs admin blockchain transactions
+    async fn admin_address(&self) -> Result<Address>;
+
+    /// Sign an admin transaction, whose chain id must be set
+    async fn sign_admin_transaction(&self, tx: &TypedTransaction) -> Result<Signature>;
+
+    /// Sign a message hash or EIP-712 digest with the admin key
+    async fn sign_admin_hash(&self, hash: H256) -> Result<Signature>;
+
+    /// The admin key encrypted for the column identified by `context`, for the admin's own
+    /// wallet row
+    async fn seal_admin_key(&self, context: &str) -> Result<String>;
+}
+
+/// `0x`-prefixed hex private key, the format wallets are stored in
+fn private_key_hex(wallet: &LocalWallet) -> String {
+    format!("0x{}", hex::encode(wallet.signer().to_bytes()))
+}
+
+pub static KEY_PROVIDER: OnceCell<Arc<dyn KeyProvider>> = OnceCell::const_new();
+
+/// Build the backend selected by `KEY_PROVIDER` (`env`, `file` or `vault`)
+pub fn create_key_provider() -> Result<Arc<dyn KeyProvider>> {
+    let provider: Arc<dyn KeyProvider> = match APP_CONFIG.key_provider.as_str() {
+        "env" => Arc::new(LocalKeyProvider::from_env()?),
+        "file" => {
+            let path = APP_CONFIG
+                .key_provider_file
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("KEY_PROVIDER_FILE is required for file key provider"))?;
+            Arc::new(LocalKeyProvider::from_file(path)?)
+        }
+        "vault" => Arc::new(VaultTransitKeyProvider::from_config()?),
+        other => anyhow::bail!("Unknown key provider '{}'", other),
+    };
+
+    tracing::info!("Using '{}' key provider", provider.name());
+    Ok(provider)
+}
+
+/// The configured backend. A bad `KEY_PROVIDER`, key file or Vault setting is returned
+/// rather than panicking the request or job that first needs a key, and binaries load the
+/// provider in `main` so they refuse to start with one.
+pub async fn get_key_provider() -> Result<&'static Arc<dyn KeyProvider>> {
+    KEY_PROVIDER
+        .get_or_try_init(|| async {
+            create_key_provider().context("Failed to create key provider")
+        })
+        .await
+}
@@ -0,0 +1,296 @@
+use super::{CONTEXT_ADMIN_PRIVATE_KEY, KeyProvider, LocalKeyProvider, private_key_hex};
+use crate::config::APP_CONFIG;
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, H256, Signature};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::OnceCell;
+
+const VAULT_CIPHERTEXT_PREFIX: &str = "vault:";
+
+/// Key provider backed by a HashiCorp Vault Transit compatible HTTP API.
+///
+/// Secrets are encrypted and decrypted by Vault, so master keys never enter this process.
+/// Transit cannot sign secp256k1 transactions, so `ADMIN_PRIVATE_KEY` must hold a Transit
+/// ciphertext of the admin key (context `config.admin_private_key`), unwrapped on first use
+/// and only ever used inside this provider.
+pub struct VaultTransitKeyProvider {
+    client: reqwest::Client,
+    addr: String,
+    token: String,
+    mount: String,
+    key_name: String,
+    admin_private_key_ciphertext: String,
+    admin_wallet: OnceCell<LocalWallet>,
+    /// Reads values written before the switch to Vault (envelope or legacy schemes)
+    fallback: Option<LocalKeyProvider>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransitResponse<T> {
+    data: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct EncryptData {
+    ciphertext: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DecryptData {
+    plaintext: String,
+}
+
+impl VaultTransitKeyProvider {
+    pub fn from_config() -> Result<Self> {
+        let addr = APP_CONFIG
+            .vault_addr
+            .clone()
+            .ok_or_else(|| anyhow!("VAULT_ADDR is required for vault key provider"))?;
+        let token = APP_CONFIG
+            .vault_token
+            .clone()
+            .ok_or_else(|| anyhow!("VAULT_TOKEN is required for vault key provider"))?;
+
+        let fallback = LocalKeyProvider::from_env()
+            .map_err(|e| tracing::warn!("No local fallback for pre-Vault secrets: {}", e))
+            .ok();
+
+        Ok(Self::new(
+            &addr,
+            &token,
+            &APP_CONFIG.vault_transit_mount,
+            &APP_CONFIG.vault_transit_key,
+            &APP_CONFIG.admin_private_key,
+            fallback,
+        ))
+    }
+
+    pub fn new(
+        addr: &str,
+        token: &str,
+        mount: &str,
+        key_name: &str,
+        admin_private_key_ciphertext: &str,
+        fallback: Option<LocalKeyProvider>,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            addr: addr.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            mount: mount.trim_matches('/').to_string(),
+            key_name: key_name.to_string(),
+            admin_private_key_ciphertext: admin_private_key_ciphertext.to_string(),
+            admin_wallet: OnceCell::new(),
+            fallback,
+        }
+    }
+
+    async fn transit<T: serde::de::DeserializeOwned>(
+        &self,
+        operation: &str,
+        body: serde_json::Value,
+    ) -> Result<T> {
+        let url = format!(
+            "{}/v1/{}/{}/{}",
+            self.addr, self.mount, operation, self.key_name
+        );
+
+        let response = self
+            .client
+            .post(&url)
+            .header("X-Vault-Token", &self.token)
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("Failed to call Vault transit {}", operation))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            anyhow::bail!("Vault transit {} failed ({}): {}", operation, status, message);
+        }
+
+        let body: TransitResponse<T> = response
+            .json()
+            .await
+            .with_context(|| format!("Invalid Vault transit {} response", operation))?;
+        Ok(body.data)
+    }
+
+    async fn transit_decrypt(&self, ciphertext: &str, context: &str) -> Result<String> {
+        let data: DecryptData = self
+            .transit(
+                "decrypt",
+                json!({
+                    "ciphertext": ciphertext,
+                    "associated_data": general_purpose::STANDARD.encode(context),
+                }),
+            )
+            .await?;
+
+        let plaintext = general_purpose::STANDARD
+            .decode(data.plaintext)
+            .context("Failed to decode Vault plaintext")?;
+        String::from_utf8(plaintext).context("Failed to convert decrypted data to string")
+    }
+
+    async fn admin_wallet(&self) -> Result<&LocalWallet> {
+        self.admin_wallet
+            .get_or_try_init(|| async {
+                if !self
+                    .admin_private_key_ciphertext
+                    .starts_with(VAULT_CIPHERTEXT_PREFIX)
+                {
+                    anyhow::bail!("ADMIN_PRIVATE_KEY must be a Vault transit ciphertext");
+                }
+
+                let private_key = self
+                    .transit_decrypt(&self.admin_private_key_ciphertext, CONTEXT_ADMIN_PRIVATE_KEY)
+                    .await?;
+                private_key
+                    .parse::<LocalWallet>()
+                    .context("Failed to parse admin private key")
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl KeyProvider for VaultTransitKeyProvider {
+    fn name(&self) -> &'static str {
+        "vault"
+    }
+
+    async fn encrypt(&self, plaintext: &str, context: &str) -> Result<String> {
+        let data: EncryptData = self
+            .transit(
+                "encrypt",
+                json!({
+                    "plaintext": general_purpose::STANDARD.encode(plaintext),
+                    "associated_data": general_purpose::STANDARD.encode(context),
+                }),
+            )
+            .await?;
+        Ok(data.ciphertext)
+    }
+
+    async fn decrypt(&self, ciphertext: &str, context: &str) -> Result<String> {
+        if ciphertext.starts_with(VAULT_CIPHERTEXT_PREFIX) {
+            return self.transit_decrypt(ciphertext, context).await;
+        }
+
+        match &self.fallback {
+            Some(fallback) => fallback.decrypt(ciphertext, context).await,
+            None => Err(anyhow!("Value was not encrypted by Vault")),
+        }
+    }
+
+    fn needs_reencryption(&self, ciphertext: &str) -> bool {
+        !ciphertext.starts_with(VAULT_CIPHERTEXT_PREFIX)
+    }
+
+    async fn admin_address(&self) -> Result<Address> {
+        Ok(self.admin_wallet().await?.address())
+    }
+
+    async fn sign_admin_transaction(&self, tx: &TypedTransaction) -> Result<Signature> {
+        Ok(self.admin_wallet().await?.sign_transaction(tx).await?)
+    }
+
+    async fn sign_admin_hash(&self, hash: H256) -> Result<Signature> {
+        Ok(self.admin_wallet().await?.sign_hash(hash)?)
+    }
+
+    async fn seal_admin_key(&self, context: &str) -> Result<String> {
+        let private_key = private_key_hex(self.admin_wallet().await?);
+        self.encrypt(&private_key, context).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_provider::AdminSigner;
+    use axum::{Json, Router, extract::Path, routing::post};
+    use std::sync::Arc;
+    use serde_json::Value;
+
+    /// Minimal Transit mock: the "ciphertext" is the base64 plaintext and AAD behind a prefix
+    fn mock_transit() -> Router {
+        Router::new()
+            .route(
+                "/v1/transit/encrypt/{key}",
+                post(|Path(_key): Path<String>, Json(body): Json<Value>| async move {
+                    let ciphertext = format!(
+                        "vault:v1:{}.{}",
+                        body["plaintext"].as_str().unwrap(),
+                        body["associated_data"].as_str().unwrap()
+                    );
+                    Json(json!({ "data": { "ciphertext": ciphertext } }))
+                }),
+            )
+            .route(
+                "/v1/transit/decrypt/{key}",
+                post(|Path(_key): Path<String>, Json(body): Json<Value>| async move {
+                    let ciphertext = body["ciphertext"].as_str().unwrap();
+                    let (plaintext, aad) = ciphertext
+                        .trim_start_matches("vault:v1:")
+                        .split_once('.')
+                        .unwrap();
+                    if aad != body["associated_data"].as_str().unwrap() {
+                        return Err(http::StatusCode::BAD_REQUEST);
+                    }
+                    Ok(Json(json!({ "data": { "plaintext": plaintext } })))
+                }),
+            )
+    }
+
+    #[tokio::test]
+    async fn test_vault_transit_round_trip() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, mock_transit()).await });
+
+        let admin_key = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+        let admin_ciphertext = format!(
+            "vault:v1:{}.{}",
+            general_purpose::STANDARD.encode(admin_key),
+            general_purpose::STANDARD.encode(CONTEXT_ADMIN_PRIVATE_KEY)
+        );
+
+        let provider = VaultTransitKeyProvider::new(
+            &format!("http://{}", addr),
+            "dev-token",
+            "transit",
+            "auth_service",
+            &admin_ciphertext,
+            None,
+        );
+
+        let ciphertext = provider.encrypt("JBSWY3DPEHPK3PXP", "user_mfa.secret").await.unwrap();
+        assert!(ciphertext.starts_with("vault:v1:"));
+        assert!(!provider.needs_reencryption(&ciphertext));
+        assert_eq!(
+            provider.decrypt(&ciphertext, "user_mfa.secret").await.unwrap(),
+            "JBSWY3DPEHPK3PXP"
+        );
+        assert!(provider.decrypt(&ciphertext, "wallet.private_key").await.is_err());
+
+        let admin_address = admin_key.parse::<LocalWallet>().unwrap().address();
+        let sealed = provider.seal_admin_key("wallet.private_key").await.unwrap();
+        assert_eq!(
+            provider.decrypt(&sealed, "wallet.private_key").await.unwrap(),
+            admin_key
+        );
+
+        let signer = AdminSigner::new(Arc::new(provider)).await.unwrap();
+        assert_eq!(signer.address(), admin_address);
+        let signature = signer.sign_message("register student").await.unwrap();
+        assert!(signature.verify("register student", admin_address).is_ok());
+    }
+}
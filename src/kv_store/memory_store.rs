@@ -0,0 +1,339 @@
+use super::{
+    KvStore, PROGRESS_COMPLETED, PROGRESS_CURRENT, PROGRESS_FAILED, PROGRESS_SUCCESS,
+    PROGRESS_TOTAL,
+};
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+
+/// Messages buffered per subscriber before slow subscribers start missing some
+const PUBSUB_CAPACITY: usize = 1024;
+
+enum Value {
+    String(String),
+    Set(HashSet<String>),
+    Hash(HashMap<String, u64>),
+}
+
+struct Entry {
+    value: Value,
+    expires_at: Option<Instant>,
+}
+
+impl Entry {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// Process-local store for single-node dev mode and tests. State is lost on restart and
+/// not shared between instances.
+pub struct MemoryKvStore {
+    entries: Mutex<HashMap<String, Entry>>,
+    pubsub: broadcast::Sender<(String, String)>,
+}
+
+impl MemoryKvStore {
+    pub fn new() -> Self {
+        let (pubsub, _) = broadcast::channel(PUBSUB_CAPACITY);
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            pubsub,
+        }
+    }
+
+    /// Run `f` on the live entries, dropping `key` first if it has expired
+    fn with_entries<T>(&self, key: &str, f: impl FnOnce(&mut HashMap<String, Entry>) -> T) -> T {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries
+            .get(key)
+            .is_some_and(|entry| entry.is_expired(Instant::now()))
+        {
+            entries.remove(key);
+        }
+        f(&mut entries)
+    }
+
+    /// Run `f` on the hash at `key`, creating it if needed, and reset its TTL
+    fn with_hash<T>(
+        &self,
+        key: &str,
+        ttl_seconds: u64,
+        f: impl FnOnce(&mut HashMap<String, u64>) -> T,
+    ) -> Result<T> {
+        self.with_entries(key, |entries| {
+            let entry = entries.entry(key.to_string()).or_insert_with(|| Entry {
+                value: Value::Hash(HashMap::new()),
+                expires_at: None,
+            });
+            entry.expires_at = Some(Instant::now() + Duration::from_secs(ttl_seconds));
+            match &mut entry.value {
+                Value::Hash(fields) => Ok(f(fields)),
+                _ => bail!("WRONGTYPE {} does not hold a hash", key),
+            }
+        })
+    }
+}
+
+impl Default for MemoryKvStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl KvStore for MemoryKvStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        self.with_entries(key, |entries| match entries.get(key) {
+            None => Ok(None),
+            Some(Entry {
+                value: Value::String(value),
+                ..
+            }) => Ok(Some(value.clone())),
+            Some(_) => bail!("WRONGTYPE {} does not hold a string", key),
+        })
+    }
+
+    async fn set_ex(&self, key: &str, value: &str, ttl_seconds: u64) -> Result<()> {
+        self.with_entries(key, |entries| {
+            entries.insert(
+                key.to_string(),
+                Entry {
+                    value: Value::String(value.to_string()),
+                    expires_at: Some(Instant::now() + Duration::from_secs(ttl_seconds)),
+                },
+            );
+        });
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        Ok(self.with_entries(key, |entries| entries.contains_key(key)))
+    }
+
+    async fn del(&self, key: &str) -> Result<bool> {
+        Ok(self.with_entries(key, |entries| entries.remove(key).is_some()))
+    }
+
+    async fn expire(&self, key: &str, ttl_seconds: u64) -> Result<()> {
+        self.with_entries(key, |entries| {
+            if let Some(entry) = entries.get_mut(key) {
+                entry.expires_at = Some(Instant::now() + Duration::from_secs(ttl_seconds));
+            }
+        });
+        Ok(())
+    }
+
+    async fn sadd(&self, key: &str, member: &str) -> Result<()> {
+        self.with_entries(key, |entries| {
+            let entry = entries.entry(key.to_string()).or_insert_with(|| Entry {
+                value: Value::Set(HashSet::new()),
+                expires_at: None,
+            });
+            match &mut entry.value {
+                Value::Set(members) => {
+                    members.insert(member.to_string());
+                    Ok(())
+                }
+                _ => bail!("WRONGTYPE {} does not hold a set", key),
+            }
+        })
+    }
+
+    async fn srem(&self, key: &str, member: &str) -> Result<()> {
+        self.with_entries(key, |entries| {
+            if let Some(Entry {
+                value: Value::Set(members),
+                ..
+            }) = entries.get_mut(key)
+            {
+                members.remove(member);
+                if members.is_empty() {
+                    entries.remove(key);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    async fn smembers(&self, key: &str) -> Result<Vec<String>> {
+        self.with_entries(key, |entries| match entries.get(key) {
+            None => Ok(Vec::new()),
+            Some(Entry {
+                value: Value::Set(members),
+                ..
+            }) => Ok(members.iter().cloned().collect()),
+            Some(_) => bail!("WRONGTYPE {} does not hold a set", key),
+        })
+    }
+
+    async fn hset_ex(&self, key: &str, fields: &[(&str, u64)], ttl_seconds: u64) -> Result<()> {
+        self.with_hash(key, ttl_seconds, |hash| {
+            for (field, value) in fields {
+                hash.insert(field.to_string(), *value);
+            }
+        })
+    }
+
+    async fn hset_nx_ex(
+        &self,
+        key: &str,
+        field: &str,
+        value: u64,
+        ttl_seconds: u64,
+    ) -> Result<bool> {
+        self.with_hash(key, ttl_seconds, |hash| {
+            if hash.contains_key(field) {
+                return false;
+            }
+            hash.insert(field.to_string(), value);
+            true
+        })
+    }
+
+    async fn hincr_ex(&self, key: &str, field: &str, delta: u64, ttl_seconds: u64) -> Result<u64> {
+        self.with_hash(key, ttl_seconds, |hash| {
+            let value = hash.entry(field.to_string()).or_insert(0);
+            *value += delta;
+            *value
+        })
+    }
+
+    async fn hget_all(&self, key: &str) -> Result<HashMap<String, u64>> {
+        self.with_entries(key, |entries| match entries.get(key) {
+            None => Ok(HashMap::new()),
+            Some(Entry {
+                value: Value::Hash(fields),
+                ..
+            }) => Ok(fields.clone()),
+            Some(_) => bail!("WRONGTYPE {} does not hold a hash", key),
+        })
+    }
+
+    async fn hincr_progress(&self, key: &str, field: &str, ttl_seconds: u64) -> Result<bool> {
+        // Same steps as the Redis script; the entries lock makes them atomic
+        self.with_hash(key, ttl_seconds, |hash| {
+            *hash.entry(field.to_string()).or_insert(0) += 1;
+            *hash.entry(PROGRESS_CURRENT.to_string()).or_insert(0) += 1;
+
+            let count = |name: &str| hash.get(name).copied().unwrap_or(0);
+            let total = count(PROGRESS_TOTAL);
+            let processed = count(PROGRESS_SUCCESS) + count(PROGRESS_FAILED);
+            if total == 0 || processed < total || count(PROGRESS_COMPLETED) == 1 {
+                return false;
+            }
+            hash.insert(PROGRESS_COMPLETED.to_string(), 1);
+            true
+        })
+    }
+
+    async fn publish(&self, channel: &str, payload: &str) -> Result<()> {
+        // No subscribers is not an error, same as Redis
+        let _ = self.pubsub.send((channel.to_string(), payload.to_string()));
+        Ok(())
+    }
+
+    async fn subscribe(&self, channels: &[String]) -> Result<BoxStream<'static, String>> {
+        let channels: HashSet<String> = channels.iter().cloned().collect();
+        Ok(BroadcastStream::new(self.pubsub.subscribe())
+            .filter_map(move |message| {
+                let payload = match message {
+                    Ok((channel, payload)) if channels.contains(&channel) => Some(payload),
+                    _ => None,
+                };
+                async move { payload }
+            })
+            .boxed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_expired_keys_are_gone() {
+        let store = MemoryKvStore::new();
+        store.set_ex("otp", "123456", 0).await.unwrap();
+        store.set_u64_ex("counter", 7, 60).await.unwrap();
+
+        assert_eq!(store.get("otp").await.unwrap(), None);
+        assert!(!store.exists("otp").await.unwrap());
+        assert_eq!(store.get_u64("counter").await.unwrap(), Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_progress_completes_once() {
+        let store = MemoryKvStore::new();
+        store
+            .hset_ex("job", &[(PROGRESS_TOTAL, 3), (PROGRESS_COMPLETED, 0)], 60)
+            .await
+            .unwrap();
+
+        assert!(
+            !store
+                .hincr_progress("job", PROGRESS_SUCCESS, 60)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !store
+                .hincr_progress("job", PROGRESS_FAILED, 60)
+                .await
+                .unwrap()
+        );
+        assert!(
+            store
+                .hincr_progress("job", PROGRESS_SUCCESS, 60)
+                .await
+                .unwrap()
+        );
+        // A redelivered message past the total does not complete the job again
+        assert!(
+            !store
+                .hincr_progress("job", PROGRESS_SUCCESS, 60)
+                .await
+                .unwrap()
+        );
+
+        let fields = store.hget_all("job").await.unwrap();
+        assert_eq!(fields[PROGRESS_CURRENT], 4);
+        assert_eq!(fields[PROGRESS_FAILED], 1);
+        assert_eq!(fields[PROGRESS_COMPLETED], 1);
+    }
+
+    #[tokio::test]
+    async fn test_sets_and_pubsub() {
+        let store = MemoryKvStore::new();
+        store.sadd("devices", "a").await.unwrap();
+        store.sadd("devices", "b").await.unwrap();
+        store.srem("devices", "a").await.unwrap();
+        assert_eq!(
+            store.smembers("devices").await.unwrap(),
+            vec!["b".to_string()]
+        );
+        assert!(store.get("devices").await.is_err());
+
+        let mut messages = store
+            .subscribe(&["job_events:1".to_string()])
+            .await
+            .unwrap();
+        store.publish("job_events:2", "ignored").await.unwrap();
+        store.publish("job_events:1", "progress").await.unwrap();
+        assert_eq!(messages.next().await.as_deref(), Some("progress"));
+    }
+}
@@ -0,0 +1,138 @@
+pub mod memory_store;
+pub mod redis_store;
+
+use crate::config::APP_CONFIG;
+use crate::redis_service::connection::key_prefix;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+pub use memory_store::MemoryKvStore;
+pub use redis_store::RedisKvStore;
+
+/// Fields of a job progress hash, see [`KvStore::hincr_progress`]
+pub const PROGRESS_TOTAL: &str = "total";
+pub const PROGRESS_CURRENT: &str = "current";
+pub const PROGRESS_SUCCESS: &str = "success";
+pub const PROGRESS_FAILED: &str = "failed";
+pub const PROGRESS_COMPLETED: &str = "completed";
+
+/// Key-value backend for short-lived state: MFA attempts, the JWT blacklist, trusted
+/// devices and job progress counters. Values are strings; hashes hold counters.
+#[async_trait]
+pub trait KvStore: Send + Sync {
+    /// Backend name, for logs
+    fn name(&self) -> &'static str;
+
+    async fn ping(&self) -> Result<()>;
+
+    async fn get(&self, key: &str) -> Result<Option<String>>;
+
+    async fn set_ex(&self, key: &str, value: &str, ttl_seconds: u64) -> Result<()>;
+
+    async fn exists(&self, key: &str) -> Result<bool>;
+
+    /// Returns whether the key existed
+    async fn del(&self, key: &str) -> Result<bool>;
+
+    async fn expire(&self, key: &str, ttl_seconds: u64) -> Result<()>;
+
+    async fn sadd(&self, key: &str, member: &str) -> Result<()>;
+
+    async fn srem(&self, key: &str, member: &str) -> Result<()>;
+
+    async fn smembers(&self, key: &str) -> Result<Vec<String>>;
+
+    /// Set hash fields and refresh the TTL of the whole hash
+    async fn hset_ex(&self, key: &str, fields: &[(&str, u64)], ttl_seconds: u64) -> Result<()>;
+
+    /// Set a hash field unless it exists. Returns whether it was set.
+    async fn hset_nx_ex(
+        &self,
+        key: &str,
+        field: &str,
+        value: u64,
+        ttl_seconds: u64,
+    ) -> Result<bool>;
+
+    /// Atomically add `delta` to a hash field and refresh the TTL. Returns the new value.
+    async fn hincr_ex(&self, key: &str, field: &str, delta: u64, ttl_seconds: u64) -> Result<u64>;
+
+    /// Every field of a hash; empty when the key does not exist
+    async fn hget_all(&self, key: &str) -> Result<HashMap<String, u64>>;
+
+    /// Count one processed item of a job: increment `field` ([`PROGRESS_SUCCESS`] or
+    /// [`PROGRESS_FAILED`]) and [`PROGRESS_CURRENT`] of the progress hash `key`.
+    ///
+    /// Returns true for exactly one call: the one that makes `success + failed` reach
+    /// `total`. It also sets [`PROGRESS_COMPLETED`], so concurrent consumers cannot both
+    /// finish the job.
+    async fn hincr_progress(&self, key: &str, field: &str, ttl_seconds: u64) -> Result<bool>;
+
+    /// Fire-and-forget message to the current subscribers of `channel`
+    async fn publish(&self, channel: &str, payload: &str) -> Result<()>;
+
+    /// Payloads published to any of `channels` from now on
+    async fn subscribe(&self, channels: &[String]) -> Result<BoxStream<'static, String>>;
+
+    async fn get_u64(&self, key: &str) -> Result<Option<u64>> {
+        self.get(key)
+            .await?
+            .map(|value| {
+                value
+                    .parse::<u64>()
+                    .with_context(|| format!("Invalid counter value at {}", key))
+            })
+            .transpose()
+    }
+
+    async fn set_u64_ex(&self, key: &str, value: u64, ttl_seconds: u64) -> Result<()> {
+        self.set_ex(key, &value.to_string(), ttl_seconds).await
+    }
+}
+
+pub static KV_STORE: OnceCell<Arc<dyn KvStore>> = OnceCell::const_new();
+
+/// Build the backend selected by `KV_STORE` (`redis` or `memory`)
+pub fn create_kv_store() -> Result<Arc<dyn KvStore>> {
+    let store: Arc<dyn KvStore> = match APP_CONFIG.kv_store.as_str() {
+        "redis" => Arc::new(RedisKvStore::new(key_prefix())),
+        "memory" => Arc::new(MemoryKvStore::new()),
+        other => anyhow::bail!("Unknown KV store '{}'", other),
+    };
+
+    tracing::info!("Using '{}' KV store", store.name());
+    Ok(store)
+}
+
+/// The configured backend. A configuration error is returned, and tried again on the next
+/// call; binaries call [`init_kv_store`] at startup so it fails there.
+pub async fn get_kv_store() -> Result<&'static Arc<dyn KvStore>> {
+    KV_STORE
+        .get_or_try_init(|| async { create_kv_store().context("Failed to create KV store") })
+        .await
+}
+
+/// Use `store` instead of the configured backend. Must run before the first `get_kv_store`.
+pub fn install_kv_store(store: Arc<dyn KvStore>) -> Result<()> {
+    KV_STORE
+        .set(store)
+        .map_err(|_| anyhow::anyhow!("KV store is already initialized"))
+}
+
+/// Create the configured store, returning a bad `KV_STORE`, and check that it answers. An
+/// unreachable store is only logged: Redis may come up after this service.
+pub async fn init_kv_store() -> Result<()> {
+    let store = get_kv_store().await?;
+    match store.ping().await {
+        Ok(()) => tracing::info!("KV store initialized successfully"),
+        Err(e) => tracing::warn!(
+            "KV store is not reachable yet (MFA features may not work properly, set KV_STORE=memory for single-node dev): {:#}",
+            e
+        ),
+    }
+    Ok(())
+}
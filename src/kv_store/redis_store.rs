@@ -0,0 +1,225 @@
+use super::{
+    KvStore, PROGRESS_COMPLETED, PROGRESS_CURRENT, PROGRESS_FAILED, PROGRESS_SUCCESS,
+    PROGRESS_TOTAL,
+};
+use crate::redis_service::connection::{RedisConnection, get_redis_connection};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use once_cell::sync::Lazy;
+use redis::AsyncCommands;
+use std::collections::HashMap;
+
+/// KEYS[1] = progress hash, ARGV[1] = outcome field, ARGV[2] = TTL in seconds.
+/// Field names are passed as ARGV[3..] so they stay in sync with the Rust constants.
+const HINCR_PROGRESS_SCRIPT: &str = r#"
+local key, field, ttl = KEYS[1], ARGV[1], ARGV[2]
+local current, total, success, failed, completed = ARGV[3], ARGV[4], ARGV[5], ARGV[6], ARGV[7]
+
+redis.call('HINCRBY', key, field, 1)
+redis.call('HINCRBY', key, current, 1)
+redis.call('EXPIRE', key, ttl)
+
+local total_count = tonumber(redis.call('HGET', key, total)) or 0
+local processed = (tonumber(redis.call('HGET', key, success)) or 0)
+    + (tonumber(redis.call('HGET', key, failed)) or 0)
+
+if total_count > 0 and processed >= total_count and redis.call('HGET', key, completed) ~= '1' then
+    redis.call('HSET', key, completed, 1)
+    return 1
+end
+return 0
+"#;
+
+static HINCR_PROGRESS: Lazy<redis::Script> =
+    Lazy::new(|| redis::Script::new(HINCR_PROGRESS_SCRIPT));
+
+/// Stores every key and channel under `key_prefix` on the shared Redis connection
+pub struct RedisKvStore {
+    key_prefix: String,
+}
+
+impl RedisKvStore {
+    pub fn new(key_prefix: impl Into<String>) -> Self {
+        Self {
+            key_prefix: key_prefix.into(),
+        }
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{}", self.key_prefix, key)
+    }
+
+    async fn connection(&self) -> Result<RedisConnection> {
+        get_redis_connection()
+            .await
+            .context("Failed to get Redis connection")
+    }
+}
+
+#[async_trait]
+impl KvStore for RedisKvStore {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn ping(&self) -> Result<()> {
+        let mut redis = self.connection().await?;
+        let _: String = redis::cmd("PING")
+            .query_async(&mut redis)
+            .await
+            .context("Failed to ping Redis")?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        let mut redis = self.connection().await?;
+        let key = self.key(key);
+        Ok(redis.get(&key).await?)
+    }
+
+    async fn set_ex(&self, key: &str, value: &str, ttl_seconds: u64) -> Result<()> {
+        let mut redis = self.connection().await?;
+        let key = self.key(key);
+        let _: () = redis.set_ex(&key, value, ttl_seconds).await?;
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        let mut redis = self.connection().await?;
+        let key = self.key(key);
+        Ok(redis.exists(&key).await?)
+    }
+
+    async fn del(&self, key: &str) -> Result<bool> {
+        let mut redis = self.connection().await?;
+        let key = self.key(key);
+        let removed: u64 = redis.del(&key).await?;
+        Ok(removed > 0)
+    }
+
+    async fn expire(&self, key: &str, ttl_seconds: u64) -> Result<()> {
+        let mut redis = self.connection().await?;
+        let key = self.key(key);
+        let _: () = redis.expire(&key, ttl_seconds as i64).await?;
+        Ok(())
+    }
+
+    async fn sadd(&self, key: &str, member: &str) -> Result<()> {
+        let mut redis = self.connection().await?;
+        let key = self.key(key);
+        let _: () = redis.sadd(&key, member).await?;
+        Ok(())
+    }
+
+    async fn srem(&self, key: &str, member: &str) -> Result<()> {
+        let mut redis = self.connection().await?;
+        let key = self.key(key);
+        let _: () = redis.srem(&key, member).await?;
+        Ok(())
+    }
+
+    async fn smembers(&self, key: &str) -> Result<Vec<String>> {
+        let mut redis = self.connection().await?;
+        let key = self.key(key);
+        Ok(redis.smembers(&key).await?)
+    }
+
+    async fn hset_ex(&self, key: &str, fields: &[(&str, u64)], ttl_seconds: u64) -> Result<()> {
+        let mut redis = self.connection().await?;
+        let key = self.key(key);
+        let _: () = redis::pipe()
+            .hset_multiple(&key, fields)
+            .ignore()
+            .expire(&key, ttl_seconds as i64)
+            .ignore()
+            .query_async(&mut redis)
+            .await?;
+        Ok(())
+    }
+
+    async fn hset_nx_ex(
+        &self,
+        key: &str,
+        field: &str,
+        value: u64,
+        ttl_seconds: u64,
+    ) -> Result<bool> {
+        let mut redis = self.connection().await?;
+        let key = self.key(key);
+        let (inserted,): (bool,) = redis::pipe()
+            .hset_nx(&key, field, value)
+            .expire(&key, ttl_seconds as i64)
+            .ignore()
+            .query_async(&mut redis)
+            .await?;
+        Ok(inserted)
+    }
+
+    async fn hincr_ex(&self, key: &str, field: &str, delta: u64, ttl_seconds: u64) -> Result<u64> {
+        let mut redis = self.connection().await?;
+        let key = self.key(key);
+        let (value,): (u64,) = redis::pipe()
+            .hincr(&key, field, delta)
+            .expire(&key, ttl_seconds as i64)
+            .ignore()
+            .query_async(&mut redis)
+            .await?;
+        Ok(value)
+    }
+
+    async fn hget_all(&self, key: &str) -> Result<HashMap<String, u64>> {
+        let mut redis = self.connection().await?;
+        let key = self.key(key);
+        Ok(redis.hgetall(&key).await?)
+    }
+
+    async fn hincr_progress(&self, key: &str, field: &str, ttl_seconds: u64) -> Result<bool> {
+        let mut redis = self.connection().await?;
+        let key = self.key(key);
+        let completed: i64 = HINCR_PROGRESS
+            .key(&key)
+            .arg(field)
+            .arg(ttl_seconds)
+            .arg(PROGRESS_CURRENT)
+            .arg(PROGRESS_TOTAL)
+            .arg(PROGRESS_SUCCESS)
+            .arg(PROGRESS_FAILED)
+            .arg(PROGRESS_COMPLETED)
+            .invoke_async(&mut redis)
+            .await
+            .context("Failed to run progress script")?;
+        Ok(completed == 1)
+    }
+
+    async fn publish(&self, channel: &str, payload: &str) -> Result<()> {
+        let mut redis = self.connection().await?;
+        let _: () = redis.publish(self.key(channel), payload).await?;
+        Ok(())
+    }
+
+    async fn subscribe(&self, channels: &[String]) -> Result<BoxStream<'static, String>> {
+        // Pub/sub needs a dedicated connection
+        let mut pubsub = self
+            .connection()
+            .await?
+            .pubsub_client()
+            .await
+            .get_async_pubsub()
+            .await
+            .context("Failed to open Redis pub/sub connection")?;
+
+        for channel in channels {
+            pubsub
+                .subscribe(self.key(channel))
+                .await
+                .with_context(|| format!("Failed to subscribe to {}", channel))?;
+        }
+
+        Ok(pubsub
+            .into_on_message()
+            .filter_map(|message| async move { message.get_payload::<String>().ok() })
+            .boxed())
+    }
+}
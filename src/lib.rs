@@ -3,9 +3,13 @@ pub mod app;
 pub mod blockchain;
 pub mod bootstrap;
 pub mod config;
+pub mod email_service;
 pub mod entities;
 pub mod extractor;
 pub mod grpc;
+pub mod key_provider;
+pub mod kv_store;
+pub mod mfa_service;
 pub mod middleware;
 pub mod rabbitmq_service;
 pub mod redis_service;
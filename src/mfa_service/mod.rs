@@ -0,0 +1,333 @@
+//! Authenticator code verification shared by login, step-up, the MFA routes and gRPC.
+//!
+//! Handlers call [`MfaService::verify`] and convert [`MfaError`] with `?`: it maps to
+//! `(StatusCode, String)` for axum and to `tonic::Status` for gRPC.
+
+pub mod store;
+
+use crate::config::MFA_MAX_FAIL_ATTEMPTS;
+use axum::http::StatusCode;
+use google_authenticator::GoogleAuthenticator;
+use std::fmt;
+use std::sync::Arc;
+use store::{DbMfaSecretSource, MfaSecretSource, MfaStore, RedisMfaStore};
+
+/// Why an authenticator code was not accepted
+#[derive(Debug)]
+pub enum MfaError {
+    /// Too many failed attempts; `locked_until` is a Unix timestamp
+    Locked { locked_until: Option<i64> },
+    CodeAlreadyUsed,
+    InvalidCode,
+    NotEnabled,
+    /// Redis, database or decryption failure
+    Internal(anyhow::Error),
+}
+
+impl MfaError {
+    /// Stable machine-readable reason, as returned in MFA verification responses
+    pub fn reason(&self) -> &'static str {
+        match self {
+            MfaError::Locked { .. } => "locked",
+            MfaError::CodeAlreadyUsed => "code_already_used",
+            MfaError::InvalidCode => "invalid_code",
+            MfaError::NotEnabled => "mfa_not_enabled",
+            MfaError::Internal(_) => "internal_error",
+        }
+    }
+
+    pub fn locked_until(&self) -> Option<i64> {
+        match self {
+            MfaError::Locked { locked_until } => *locked_until,
+            _ => None,
+        }
+    }
+
+    /// The code was checked and refused, as opposed to verification failing to run
+    pub fn is_rejection(&self) -> bool {
+        !matches!(self, MfaError::Internal(_))
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            MfaError::Locked { .. } => StatusCode::FORBIDDEN,
+            MfaError::CodeAlreadyUsed | MfaError::InvalidCode => StatusCode::UNAUTHORIZED,
+            MfaError::NotEnabled => StatusCode::BAD_REQUEST,
+            MfaError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for MfaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MfaError::Locked {
+                locked_until: Some(until),
+            } => write!(f, "MFA is locked until {} (too many failed attempts)", until),
+            MfaError::Locked { locked_until: None } => {
+                write!(f, "MFA is locked due to too many failed attempts")
+            }
+            MfaError::CodeAlreadyUsed => write!(f, "MFA code has already been used"),
+            MfaError::InvalidCode => write!(f, "Invalid MFA code"),
+            MfaError::NotEnabled => write!(f, "MFA is not enabled for this user"),
+            MfaError::Internal(e) => write!(f, "Failed to verify MFA code: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MfaError {}
+
+impl From<anyhow::Error> for MfaError {
+    fn from(e: anyhow::Error) -> Self {
+        MfaError::Internal(e)
+    }
+}
+
+impl From<MfaError> for (StatusCode, String) {
+    fn from(e: MfaError) -> Self {
+        (e.status_code(), e.to_string())
+    }
+}
+
+impl From<MfaError> for tonic::Status {
+    fn from(e: MfaError) -> Self {
+        match e {
+            MfaError::Locked { .. } => tonic::Status::permission_denied(e.to_string()),
+            MfaError::CodeAlreadyUsed | MfaError::InvalidCode => {
+                tonic::Status::unauthenticated(e.to_string())
+            }
+            MfaError::NotEnabled => tonic::Status::failed_precondition(e.to_string()),
+            MfaError::Internal(_) => tonic::Status::internal(e.to_string()),
+        }
+    }
+}
+
+pub struct MfaService {
+    store: Arc<dyn MfaStore>,
+    secrets: Arc<dyn MfaSecretSource>,
+}
+
+impl MfaService {
+    /// Attempts and used codes in Redis, secrets from `user_mfa`
+    pub fn new() -> Self {
+        Self::with_backends(Arc::new(RedisMfaStore), Arc::new(DbMfaSecretSource))
+    }
+
+    pub fn with_backends(store: Arc<dyn MfaStore>, secrets: Arc<dyn MfaSecretSource>) -> Self {
+        Self { store, secrets }
+    }
+
+    /// Verify a TOTP code. A code is accepted once; `MFA_MAX_FAIL_ATTEMPTS` failures in a
+    /// row lock verification for `MFA_LOCK_DURATION_SECONDS`.
+    pub async fn verify(&self, user_id: &str, code: &str) -> Result<(), MfaError> {
+        let mut mfa_attempts = self.store.get_attempts(user_id).await?;
+
+        if mfa_attempts.is_locked() {
+            tracing::warn!(
+                "MFA is locked for user {} due to too many failed attempts",
+                user_id
+            );
+            return Err(MfaError::Locked {
+                locked_until: mfa_attempts.locked_until,
+            });
+        }
+
+        if self.store.is_code_used(user_id, code).await? {
+            tracing::warn!("MFA code already used for user {}", user_id);
+            return Err(MfaError::CodeAlreadyUsed);
+        }
+
+        let Some(secret) = self.secrets.enabled_secret(user_id).await? else {
+            return Err(MfaError::NotEnabled);
+        };
+
+        if GoogleAuthenticator::new().verify_code(&secret, code, 1, 0) {
+            self.store.mark_code_used(user_id, code).await?;
+            self.store.reset_attempts(user_id).await?;
+
+            tracing::info!("MFA code verified successfully for user {}", user_id);
+            return Ok(());
+        }
+
+        mfa_attempts.increment_fail();
+        self.store.set_attempts(user_id, &mfa_attempts).await?;
+
+        tracing::warn!(
+            "MFA code verification failed for user {} (attempt {})",
+            user_id,
+            mfa_attempts.invalid_mfa_count
+        );
+
+        if mfa_attempts.invalid_mfa_count >= MFA_MAX_FAIL_ATTEMPTS {
+            tracing::error!(
+                "MFA locked for user {} due to {} failed attempts",
+                user_id,
+                MFA_MAX_FAIL_ATTEMPTS
+            );
+            Err(MfaError::Locked {
+                locked_until: mfa_attempts.locked_until,
+            })
+        } else {
+            Err(MfaError::InvalidCode)
+        }
+    }
+}
+
+impl Default for MfaService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis_service::redis_service::MfaAttempts;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::Mutex;
+
+    const USER_ID: &str = "5f0c9a3e-1d2b-4c6a-9e8f-7a6b5c4d3e2f";
+    const SECRET: &str = "JBSWY3DPEHPK3PXP";
+
+    #[derive(Default)]
+    struct InMemoryMfaStore {
+        attempts: Mutex<HashMap<String, MfaAttempts>>,
+        used_codes: Mutex<HashSet<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl MfaStore for InMemoryMfaStore {
+        async fn get_attempts(&self, user_id: &str) -> Result<MfaAttempts> {
+            Ok(self
+                .attempts
+                .lock()
+                .unwrap()
+                .get(user_id)
+                .cloned()
+                .unwrap_or(MfaAttempts {
+                    user_id: user_id.to_string(),
+                    invalid_mfa_count: 0,
+                    locked_until: None,
+                }))
+        }
+
+        async fn set_attempts(&self, user_id: &str, attempts: &MfaAttempts) -> Result<()> {
+            self.attempts
+                .lock()
+                .unwrap()
+                .insert(user_id.to_string(), attempts.clone());
+            Ok(())
+        }
+
+        async fn reset_attempts(&self, user_id: &str) -> Result<()> {
+            self.attempts.lock().unwrap().remove(user_id);
+            Ok(())
+        }
+
+        async fn is_code_used(&self, user_id: &str, code: &str) -> Result<bool> {
+            Ok(self
+                .used_codes
+                .lock()
+                .unwrap()
+                .contains(&(user_id.to_string(), code.to_string())))
+        }
+
+        async fn mark_code_used(&self, user_id: &str, code: &str) -> Result<()> {
+            self.used_codes
+                .lock()
+                .unwrap()
+                .insert((user_id.to_string(), code.to_string()));
+            Ok(())
+        }
+    }
+
+    struct StaticSecrets(HashMap<String, String>);
+
+    #[async_trait]
+    impl MfaSecretSource for StaticSecrets {
+        async fn enabled_secret(&self, user_id: &str) -> Result<Option<String>> {
+            Ok(self.0.get(user_id).cloned())
+        }
+    }
+
+    fn service() -> MfaService {
+        let secrets = HashMap::from([(USER_ID.to_string(), SECRET.to_string())]);
+        MfaService::with_backends(
+            Arc::new(InMemoryMfaStore::default()),
+            Arc::new(StaticSecrets(secrets)),
+        )
+    }
+
+    fn current_code() -> String {
+        GoogleAuthenticator::new().get_code(SECRET, 0).unwrap()
+    }
+
+    fn wrong_code() -> String {
+        let code: u32 = current_code().parse().unwrap();
+        format!("{:06}", (code + 500_000) % 1_000_000)
+    }
+
+    #[tokio::test]
+    async fn test_valid_code_is_accepted_once() {
+        let mfa = service();
+        let code = current_code();
+
+        assert!(mfa.verify(USER_ID, &code).await.is_ok());
+        assert!(matches!(
+            mfa.verify(USER_ID, &code).await,
+            Err(MfaError::CodeAlreadyUsed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_locks_after_max_failed_attempts() {
+        let mfa = service();
+
+        for _ in 1..MFA_MAX_FAIL_ATTEMPTS {
+            assert!(matches!(
+                mfa.verify(USER_ID, &wrong_code()).await,
+                Err(MfaError::InvalidCode)
+            ));
+        }
+
+        let err = mfa.verify(USER_ID, &wrong_code()).await.unwrap_err();
+        assert!(matches!(err, MfaError::Locked { locked_until: Some(_) }));
+
+        // A correct code does not lift the lock
+        let err = mfa.verify(USER_ID, &current_code()).await.unwrap_err();
+        assert!(matches!(err, MfaError::Locked { .. }));
+        assert_eq!(
+            <(StatusCode, String)>::from(err).0,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn test_success_resets_failed_attempts() {
+        let mfa = service();
+
+        for _ in 1..MFA_MAX_FAIL_ATTEMPTS {
+            let _ = mfa.verify(USER_ID, &wrong_code()).await;
+        }
+        assert!(mfa.verify(USER_ID, &current_code()).await.is_ok());
+
+        // The counter starts over, so one more failure does not lock
+        assert!(matches!(
+            mfa.verify(USER_ID, &wrong_code()).await,
+            Err(MfaError::InvalidCode)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mfa_not_enabled() {
+        let err = service()
+            .verify("00000000-0000-0000-0000-000000000000", "123456")
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.reason(), "mfa_not_enabled");
+        assert_eq!(tonic::Status::from(err).code(), tonic::Code::FailedPrecondition);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::key_provider::get_key_provider;
+use crate::redis_service::redis_service::{MfaAttempts, MfaRedisService};
+use crate::repositories::UserMfaRepository;
+use crate::utils::envelope_encryption::CONTEXT_MFA_SECRET;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+
+/// Failed attempt counters and recently used codes
+#[async_trait]
+pub trait MfaStore: Send + Sync {
+    async fn get_attempts(&self, user_id: &str) -> Result<MfaAttempts>;
+    async fn set_attempts(&self, user_id: &str, attempts: &MfaAttempts) -> Result<()>;
+    async fn reset_attempts(&self, user_id: &str) -> Result<()>;
+    async fn is_code_used(&self, user_id: &str, code: &str) -> Result<bool>;
+    async fn mark_code_used(&self, user_id: &str, code: &str) -> Result<()>;
+}
+
+/// Source of the plaintext TOTP secret of users with MFA enabled
+#[async_trait]
+pub trait MfaSecretSource: Send + Sync {
+    /// `None` when the user has not enabled MFA
+    async fn enabled_secret(&self, user_id: &str) -> Result<Option<String>>;
+}
+
+pub struct RedisMfaStore;
+
+#[async_trait]
+impl MfaStore for RedisMfaStore {
+    async fn get_attempts(&self, user_id: &str) -> Result<MfaAttempts> {
+        MfaRedisService::get_mfa_attempts(user_id).await
+    }
+
+    async fn set_attempts(&self, user_id: &str, attempts: &MfaAttempts) -> Result<()> {
+        MfaRedisService::set_mfa_attempts(user_id, attempts).await
+    }
+
+    async fn reset_attempts(&self, user_id: &str) -> Result<()> {
+        MfaRedisService::reset_mfa_attempts(user_id).await
+    }
+
+    async fn is_code_used(&self, user_id: &str, code: &str) -> Result<bool> {
+        MfaRedisService::is_mfa_code_used(user_id, code).await
+    }
+
+    async fn mark_code_used(&self, user_id: &str, code: &str) -> Result<()> {
+        MfaRedisService::mark_mfa_code_as_used(user_id, code).await
+    }
+}
+
+/// Reads `user_mfa` and decrypts the secret with the configured key provider
+pub struct DbMfaSecretSource;
+
+#[async_trait]
+impl MfaSecretSource for DbMfaSecretSource {
+    async fn enabled_secret(&self, user_id: &str) -> Result<Option<String>> {
+        let user_id = user_id.parse().context("Invalid user_id")?;
+        let Some(user_mfa) = UserMfaRepository::new()
+            .find_enabled_by_user_id(user_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let secret = get_key_provider()
+            .await?
+            .decrypt(&user_mfa.secret, CONTEXT_MFA_SECRET)
+            .await
+            .context("Failed to decrypt secret")?;
+        Ok(Some(secret))
+    }
+}
@@ -0,0 +1,236 @@
+use crate::config::APP_CONFIG;
+use crate::entities::sea_orm_active_enums::RoleEnum;
+use crate::entities::user;
+use crate::repositories::{UserMfaRepository, UserRepository};
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime, Utc};
+use do_an_lib::structs::token_claims::{TokenClaims, UserRole};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Message of [`AuthorizeError::EnrollmentRequired`], whatever the transport
+pub const MFA_ENROLLMENT_REQUIRED_MESSAGE: &str =
+    "MFA enrollment required. Please enable MFA at /api/v1/user-mfa/enable";
+
+/// Routes a user may still call while MFA enrollment is being forced
+pub const MFA_ENROLLMENT_ALLOWED_PATHS: &[&str] = &[
+    "/api/v1/user-mfa/",
+    "/api/v1/auth/logout",
+    "/api/v1/profile",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case", tag = "state")]
+pub enum MfaPolicyState {
+    /// MFA is optional for this role
+    NotRequired,
+    /// MFA is required and the user has it enabled
+    Satisfied,
+    /// MFA is required but the user is still inside the enrollment grace period
+    GracePeriod { deadline: String },
+    /// MFA is required and the grace period is over: enrollment is forced
+    EnrollmentRequired,
+}
+
+impl MfaPolicyState {
+    pub fn policy_applies(&self) -> bool {
+        !matches!(self, MfaPolicyState::NotRequired)
+    }
+}
+
+/// Per-role MFA policy configured by admins through
+/// `MFA_REQUIRED_ROLES` and `MFA_ENROLLMENT_GRACE_DAYS`
+#[derive(Debug, Clone)]
+pub struct MfaPolicy {
+    pub required_roles: Vec<RoleEnum>,
+    pub grace_period: Duration,
+}
+
+impl MfaPolicy {
+    pub fn from_config() -> Self {
+        Self {
+            required_roles: parse_roles(&APP_CONFIG.mfa_required_roles),
+            grace_period: Duration::days(APP_CONFIG.mfa_enrollment_grace_days),
+        }
+    }
+
+    pub fn is_required_for(&self, role: &RoleEnum) -> bool {
+        self.required_roles.contains(role)
+    }
+
+    /// Evaluate the policy for one user whose grace period started at `grace_started_at`
+    pub fn evaluate(
+        &self,
+        role: &RoleEnum,
+        grace_started_at: NaiveDateTime,
+        mfa_enabled: bool,
+        now: NaiveDateTime,
+    ) -> MfaPolicyState {
+        if !self.is_required_for(role) {
+            return MfaPolicyState::NotRequired;
+        }
+
+        if mfa_enabled {
+            return MfaPolicyState::Satisfied;
+        }
+
+        let deadline = grace_started_at + self.grace_period;
+        if now < deadline {
+            MfaPolicyState::GracePeriod {
+                deadline: deadline.to_string(),
+            }
+        } else {
+            MfaPolicyState::EnrollmentRequired
+        }
+    }
+
+    /// Evaluate the policy for a stored user. The grace period starts the first time the user
+    /// is seen without MFA while the policy requires it, not when the account was created, so
+    /// rolling the policy out or adding a role to it does not lock anyone out at once.
+    pub async fn evaluate_user(
+        &self,
+        user: &user::Model,
+        mfa_enabled: bool,
+    ) -> Result<MfaPolicyState> {
+        let now = Utc::now().naive_utc();
+        if !self.is_required_for(&user.role) || mfa_enabled {
+            return Ok(self.evaluate(&user.role, now, mfa_enabled, now));
+        }
+
+        let grace_started_at = match user.mfa_grace_started_at {
+            Some(started_at) => started_at,
+            None => {
+                UserRepository::new()
+                    .start_mfa_grace(user.user_id, now)
+                    .await?
+            }
+        };
+        Ok(self.evaluate(&user.role, grace_started_at, mfa_enabled, now))
+    }
+}
+
+/// Why [`authorize_user`] turned a token down
+#[derive(Debug)]
+pub enum AuthorizeError {
+    InvalidUserId,
+    /// The user was deleted since the token was issued
+    UserNotFound,
+    /// MFA is required for the user's role and their grace period is over
+    EnrollmentRequired,
+    /// The user or their MFA status could not be read, or the grace period not stored
+    Internal(anyhow::Error),
+}
+
+/// Check the claims of a valid access token against the database and return the claims
+/// callers may trust: the user must still exist and, when `enforce_mfa` is set and their
+/// role requires MFA, be enrolled or inside the grace period. The role is read from the
+/// user, not from the token.
+///
+/// Shared by the HTTP extractor and the gRPC interceptor, which map the error to their
+/// transport.
+pub async fn authorize_user(
+    token: TokenClaims,
+    enforce_mfa: bool,
+) -> Result<TokenClaims, AuthorizeError> {
+    let user_id = Uuid::parse_str(&token.user_id).map_err(|_| AuthorizeError::InvalidUserId)?;
+    let user_info = UserRepository::new()
+        .find_by_id(user_id)
+        .await
+        .map_err(AuthorizeError::Internal)?
+        .ok_or(AuthorizeError::UserNotFound)?;
+
+    let mfa_policy = MfaPolicy::from_config();
+    if enforce_mfa && mfa_policy.is_required_for(&user_info.role) {
+        let mfa_enabled = UserMfaRepository::new()
+            .find_enabled_by_user_id(user_id)
+            .await
+            .map_err(AuthorizeError::Internal)?
+            .is_some();
+
+        let state = mfa_policy
+            .evaluate_user(&user_info, mfa_enabled)
+            .await
+            .map_err(AuthorizeError::Internal)?;
+        if state == MfaPolicyState::EnrollmentRequired {
+            return Err(AuthorizeError::EnrollmentRequired);
+        }
+    }
+
+    let role = match user_info.role {
+        RoleEnum::Admin => UserRole::ADMIN,
+        RoleEnum::Manager => UserRole::MANAGER,
+        RoleEnum::Student => UserRole::STUDENT,
+        RoleEnum::Teacher => UserRole::TEACHER,
+    };
+
+    Ok(TokenClaims {
+        user_id: token.user_id,
+        user_name: token.user_name,
+        iap: token.iap,
+        iat: token.iat,
+        exp: token.exp,
+        role,
+    })
+}
+
+fn parse_roles(value: &str) -> Vec<RoleEnum> {
+    value
+        .split(',')
+        .filter_map(|role| match role.trim().to_lowercase().as_str() {
+            "admin" => Some(RoleEnum::Admin),
+            "manager" => Some(RoleEnum::Manager),
+            "teacher" => Some(RoleEnum::Teacher),
+            "student" => Some(RoleEnum::Student),
+            "" => None,
+            other => {
+                tracing::warn!("Ignoring unknown role '{}' in MFA_REQUIRED_ROLES", other);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Whether a path stays reachable while the caller is forced to enroll in MFA
+pub fn is_enrollment_allowed_path(path: &str) -> bool {
+    MFA_ENROLLMENT_ALLOWED_PATHS
+        .iter()
+        .any(|allowed| path.starts_with(allowed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> MfaPolicy {
+        MfaPolicy {
+            required_roles: parse_roles("admin, manager"),
+            grace_period: Duration::days(7),
+        }
+    }
+
+    #[test]
+    fn test_mfa_policy_evaluate() {
+        let grace_started_at = chrono::Utc::now().naive_utc() - Duration::days(3);
+        let inside_grace = grace_started_at + Duration::days(1);
+        let after_grace = grace_started_at + Duration::days(8);
+
+        let policy = policy();
+        assert_eq!(
+            policy.evaluate(&RoleEnum::Student, grace_started_at, false, after_grace),
+            MfaPolicyState::NotRequired
+        );
+        assert_eq!(
+            policy.evaluate(&RoleEnum::Admin, grace_started_at, true, after_grace),
+            MfaPolicyState::Satisfied
+        );
+        assert!(matches!(
+            policy.evaluate(&RoleEnum::Manager, grace_started_at, false, inside_grace),
+            MfaPolicyState::GracePeriod { .. }
+        ));
+        assert_eq!(
+            policy.evaluate(&RoleEnum::Manager, grace_started_at, false, after_grace),
+            MfaPolicyState::EnrollmentRequired
+        );
+    }
+}
@@ -1,2 +1,3 @@
 pub mod http_logger;
+pub mod mfa_policy;
 pub mod permission;
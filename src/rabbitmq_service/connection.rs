@@ -0,0 +1,173 @@
+//! The RabbitMQ connection of the process, kept alive by a supervisor task.
+//!
+//! The supervisor connects with exponential backoff, re-declares the topology on every new
+//! connection and reconnects when the broker goes away. Publishers get an error straight
+//! away while it is down instead of waiting on a dead connection; consumers wrapped in
+//! [`keep_consuming`] resubscribe once it is back.
+
+use crate::config::APP_CONFIG;
+use crate::rabbitmq_service::topology;
+use anyhow::{Context, Result, anyhow};
+use lapin::{Channel, Connection, ConnectionProperties};
+use once_cell::sync::Lazy;
+use std::future::Future;
+use std::sync::{Arc, Once, RwLock};
+use std::time::Duration;
+use tokio::sync::{Notify, watch};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+/// Fallback for connection losses that do not trigger the error callback
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Pause before resubscribing a consumer that stopped while the connection looked fine
+const CONSUMER_RESTART_DELAY: Duration = Duration::from_secs(1);
+
+pub struct RabbitMqSupervisor {
+    connection: RwLock<Option<Arc<Connection>>>,
+    /// Bumped on every new connection
+    generation: watch::Sender<u64>,
+    started: Once,
+}
+
+static SUPERVISOR: Lazy<RabbitMqSupervisor> = Lazy::new(|| RabbitMqSupervisor {
+    connection: RwLock::new(None),
+    generation: watch::Sender::new(0),
+    started: Once::new(),
+});
+
+/// The process-wide supervisor, started on first use
+pub fn rabbitmq() -> &'static RabbitMqSupervisor {
+    let supervisor = &*SUPERVISOR;
+    supervisor
+        .started
+        .call_once(|| drop(tokio::spawn(supervisor.supervise())));
+    supervisor
+}
+
+/// The live connection, or an error right away while the broker is unreachable
+pub async fn get_rabbitmq_connection() -> Result<Arc<Connection>> {
+    rabbitmq()
+        .current()
+        .ok_or_else(|| anyhow!("RabbitMQ is not connected"))
+}
+
+impl RabbitMqSupervisor {
+    pub fn is_connected(&self) -> bool {
+        self.current()
+            .is_some_and(|connection| connection.status().connected())
+    }
+
+    fn current(&self) -> Option<Arc<Connection>> {
+        self.connection
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn replace(&self, connection: Option<Arc<Connection>>) {
+        *self.connection.write().unwrap_or_else(|e| e.into_inner()) = connection;
+    }
+
+    /// Wait until connected, however long the broker is down
+    pub async fn wait_connected(&self) -> Arc<Connection> {
+        let mut generation = self.generation.subscribe();
+        loop {
+            if let Some(connection) = self.current() {
+                return connection;
+            }
+            // The sender lives in a static, so this never fails
+            let _ = generation.changed().await;
+        }
+    }
+
+    /// Wait up to `timeout` for the first connection; false if it is still down
+    pub async fn wait_connected_for(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, self.wait_connected())
+            .await
+            .is_ok()
+    }
+
+    pub async fn create_channel(&self) -> Result<Channel> {
+        get_rabbitmq_connection()
+            .await?
+            .create_channel()
+            .await
+            .context("Failed to create RabbitMQ channel")
+    }
+
+    async fn connect() -> Result<Connection> {
+        let connection = tokio::time::timeout(
+            CONNECT_TIMEOUT,
+            Connection::connect(&APP_CONFIG.rabbitmq_uri, ConnectionProperties::default()),
+        )
+        .await
+        .context("Timed out")?
+        .context("Failed to connect to RabbitMQ")?;
+
+        topology::declare(&connection)
+            .await
+            .context("Failed to declare RabbitMQ topology")?;
+        Ok(connection)
+    }
+
+    async fn supervise(&'static self) {
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        loop {
+            let connection = match Self::connect().await {
+                Ok(connection) => Arc::new(connection),
+                Err(e) => {
+                    tracing::error!("RabbitMQ unavailable, retrying in {:?}: {:#}", delay, e);
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+                    continue;
+                }
+            };
+            delay = INITIAL_RECONNECT_DELAY;
+
+            let lost = Arc::new(Notify::new());
+            {
+                let lost = lost.clone();
+                connection.on_error(move |e| {
+                    tracing::error!("RabbitMQ connection error: {}", e);
+                    lost.notify_one();
+                });
+            }
+
+            self.replace(Some(connection.clone()));
+            self.generation.send_modify(|generation| *generation += 1);
+            tracing::info!("Connected to RabbitMQ");
+
+            loop {
+                tokio::select! {
+                    _ = lost.notified() => break,
+                    _ = tokio::time::sleep(STATUS_POLL_INTERVAL) => {
+                        if !connection.status().connected() {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            self.replace(None);
+            tracing::warn!("Lost RabbitMQ connection, reconnecting");
+        }
+    }
+}
+
+/// Run `consume` again whenever it returns, once the connection is back. For consumer
+/// loops that end when their channel dies.
+pub async fn keep_consuming<F, Fut>(name: &str, mut consume: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    loop {
+        rabbitmq().wait_connected().await;
+        match consume().await {
+            Ok(()) => tracing::warn!("{} consumer stopped, resubscribing", name),
+            Err(e) => tracing::error!("{} consumer failed, resubscribing: {:#}", name, e),
+        }
+        tokio::time::sleep(CONSUMER_RESTART_DELAY).await;
+    }
+}
@@ -1,1122 +1,40 @@
 use crate::blockchain::BlockchainService;
 use crate::config::APP_CONFIG;
-use crate::entities::sea_orm_active_enums::{RoleEnum, UserStatus};
-use crate::rabbitmq_service::structs::{
-    ActivateStudentMessage, AssignRoleMessage, DeactivateStudentMessage, RegisterNewManagerMessage,
-    RegisterNewUserMessage, RegisterStudentsBatchMessage, RemoveManagerMessage,
-};
+use crate::rabbitmq_service::connection::rabbitmq;
+use crate::rabbitmq_service::job_runner::notify_user;
+use crate::rabbitmq_service::topology::CREATE_USER_DB;
+use crate::entities::sea_orm_active_enums::{NotificationKind, RoleEnum};
+use crate::redis_service::job_events::{JobEvent, JobEvents, JobPhase};
 use crate::redis_service::redis_emitter::RedisEmitter;
 use crate::redis_service::redis_service::{
-    BlockchainRegistrationProgress, FileHandleTrackProgress,
+    FileHandleTrackProgress, helper_get_current_file_progress,
 };
+use crate::redis_service::socket_events::{BatchProgressPayload, SocketEvent, user_room};
 use crate::repositories::{file_upload_repository::FileUploadRepository, UserRepository, WalletRepository};
 use crate::routes::users::dto::UserCsvColumn;
-use crate::utils::encryption::encrypt_private_key;
+use crate::key_provider::get_key_provider;
+use crate::utils::envelope_encryption::CONTEXT_WALLET_PRIVATE_KEY;
 use anyhow::{Context, anyhow};
 use chrono::Utc;
 use futures::StreamExt;
 use http::StatusCode;
 use lapin::options::{BasicAckOptions, BasicConsumeOptions};
 use lapin::types::FieldTable;
-use lapin::{Connection, ConnectionProperties};
 use sea_orm::{ActiveModelTrait, Set};
-use serde_json::json;
-use tokio::sync::OnceCell;
+use serde_json::{Map, Value, json};
 use uuid::Uuid;
 use crate::entities::user_major;
 
-pub const REGISTER_NEW_USER_CHANNEL: &str = "create::new::user";
-pub const CREATE_USER_DB: &str = "create::user::db";
-pub const REGISTER_NEW_MANAGER_CHANNEL: &str = "create:new:manager";
-pub const ASSIGN_ROLE_CHANNEL: &str = "blockchain::assign::role";
-pub const REMOVE_MANAGER_CHANNEL: &str = "blockchain::remove::manager";
-pub const DEACTIVATE_STUDENT_CHANNEL: &str = "blockchain::deactivate::student";
-pub const ACTIVATE_STUDENT_CHANNEL: &str = "blockchain::activate::student";
-pub const REGISTER_STUDENTS_BATCH_CHANNEL: &str = "blockchain::register::students::batch";
-
-pub static RABBITMQ_CONNECTION: OnceCell<Connection> = OnceCell::const_new();
-
-pub async fn get_rabbitmq_connetion() -> &'static Connection {
-    RABBITMQ_CONNECTION
-        .get_or_init(|| async {
-            Connection::connect(&APP_CONFIG.rabbitmq_uri, ConnectionProperties::default())
-                .await
-                .expect("Failed to connect to RabbitMQ")
-        })
-        .await
-}
 pub struct RabbitMqConsumer;
 
 impl RabbitMqConsumer {
-    pub async fn new() -> Connection {
-        let connection =
-            Connection::connect(&APP_CONFIG.rabbitmq_uri, ConnectionProperties::default())
-                .await
-                .expect("Failed to connect to RabbitMQ");
-        connection
-    }
-
-    pub async fn consume_register_new_student() -> Result<(), anyhow::Error> {
-        tracing::info!(
-            "Starting consumer for register new student queue: {}",
-            REGISTER_NEW_USER_CHANNEL
-        );
-
-        let rabbit_conn = RABBITMQ_CONNECTION
-            .get()
-            .expect("Failed to connect to rabbitMQ");
-        let channel = rabbit_conn.create_channel().await.expect("created channel");
-
-        tracing::info!("Created RabbitMQ channel, starting to consume messages...");
-
-        let mut consumer = channel
-            .basic_consume(
-                REGISTER_NEW_USER_CHANNEL,
-                "register_student",
-                BasicConsumeOptions::default(),
-                FieldTable::default(),
-            )
-            .await?;
-
-        tracing::info!("Consumer started successfully, waiting for messages...");
-
-        while let Some(delivery) = consumer.next().await {
-            tracing::debug!("Received message from queue");
-            let delivery = match delivery {
-                Ok(d) => d,
-                Err(e) => {
-                    tracing::error!("Failed to receive message rabbitMQ: {:?}", e);
-                    continue;
-                }
-            };
-
-            match std::str::from_utf8(&delivery.data) {
-                Ok(_payload) => {
-                    let deserialize_payload: RegisterNewUserMessage =
-                        serde_json::from_slice::<RegisterNewUserMessage>(&delivery.data)?;
-
-                    tracing::info!(
-                        "Processing register student message for student_code: {}, email: {}",
-                        deserialize_payload.student_code,
-                        deserialize_payload.email
-                    );
-
-                    let ack_options = BasicAckOptions::default();
-                    if let Err(e) = delivery.ack(ack_options).await {
-                        tracing::error!("Failed to acknowledge register new user message: {}", e);
-                    } else {
-                        tracing::debug!(
-                            "Message acknowledged, starting blockchain registration..."
-                        );
-
-                        let blockchain = BlockchainService::new().await?;
-                        let result = blockchain
-                            .register_student(
-                                &deserialize_payload.wallet_address,
-                                &deserialize_payload.student_code,
-                                &deserialize_payload.full_name,
-                                &deserialize_payload.email,
-                            )
-                            .await;
-
-                        match result {
-                            Ok(_) => {
-                                let user_repo = UserRepository::new();
-                                if let Err(status_err) = user_repo
-                                    .update_status_by_email(
-                                        &deserialize_payload.email,
-                                        UserStatus::Sync,
-                                    )
-                                    .await
-                                {
-                                    tracing::error!(
-                                        "Failed to update user status to Sync for {}: {}",
-                                        deserialize_payload.email,
-                                        status_err
-                                    );
-                                }
-
-                                // Update progress if file_upload_history_id is present
-                                if let Some(file_upload_history_id) =
-                                    deserialize_payload.file_upload_history_id.as_deref()
-                                {
-                                    if let Err(progress_err) =
-                                        BlockchainRegistrationProgress::increment_success(
-                                            file_upload_history_id,
-                                        )
-                                        .await
-                                    {
-                                        tracing::error!(
-                                            "Failed to update blockchain registration progress for {}: {}",
-                                            file_upload_history_id,
-                                            progress_err
-                                        );
-                                    } else {
-                                        // Check if process is complete and update status
-                                        let file_repo = FileUploadRepository::new();
-                                        if let Err(status_err) = file_repo
-                                            .check_and_update_blockchain_status_on_completion(
-                                                file_upload_history_id,
-                                                crate::repositories::file_upload_repository::FileUploadStatus::SyncBlockchain,
-                                            )
-                                            .await
-                                        {
-                                            tracing::error!(
-                                                "Failed to check and update blockchain status for {}: {}",
-                                                file_upload_history_id,
-                                                status_err
-                                            );
-                                        }
-                                    }
-                                }
-
-                                let notification = json!({
-                                    "status": "success",
-                                    "student_code": deserialize_payload.student_code,
-                                    "email": deserialize_payload.email,
-                                    "message": "Register student on blockchain successfully."
-                                })
-                                .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", deserialize_payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-
-                                tracing::info!(
-                                    "Successfully registered student {} on blockchain and sent notification",
-                                    deserialize_payload.student_code
-                                );
-                            }
-                            Err(e) => {
-                                // Update progress even on failure (to track total processed)
-                                if let Some(file_upload_history_id) =
-                                    deserialize_payload.file_upload_history_id.as_deref()
-                                {
-                                    if let Err(progress_err) =
-                                        BlockchainRegistrationProgress::increment_failed(
-                                            file_upload_history_id,
-                                        )
-                                        .await
-                                    {
-                                        tracing::error!(
-                                            "Failed to update blockchain registration progress for {}: {}",
-                                            file_upload_history_id,
-                                            progress_err
-                                        );
-                                    } else {
-                                        // Check if process is complete and update status
-                                        let file_repo = FileUploadRepository::new();
-                                        if let Err(status_err) = file_repo
-                                            .check_and_update_blockchain_status_on_completion(
-                                                file_upload_history_id,
-                                                crate::repositories::file_upload_repository::FileUploadStatus::SyncBlockchain,
-                                            )
-                                            .await
-                                        {
-                                            tracing::error!(
-                                                "Failed to check and update blockchain status for {}: {}",
-                                                file_upload_history_id,
-                                                status_err
-                                            );
-                                        }
-                                    }
-                                }
-
-                                tracing::error!("Failed to register new student: {}", e);
-                                let user_repo = UserRepository::new();
-                                if let Err(status_err) = user_repo
-                                    .update_status_by_email(
-                                        &deserialize_payload.email,
-                                        UserStatus::Failed,
-                                    )
-                                    .await
-                                {
-                                    tracing::error!(
-                                        "Failed to update user status to Failed for {}: {}",
-                                        deserialize_payload.email,
-                                        status_err
-                                    );
-                                } else {
-                                    tracing::info!(
-                                        "Set user status to Failed for {} after blockchain error",
-                                        deserialize_payload.email
-                                    );
-                                }
-
-                                let notification = json!({
-                                    "status": "failed",
-                                    "student_code": deserialize_payload.student_code,
-                                    "email": deserialize_payload.email,
-                                    "reason": e.to_string(),
-                                    "message": "Failed to register student on blockchain. Please try again or contact with admin"
-                                })
-                                .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", deserialize_payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-
-                                tracing::info!(
-                                    "Sent failure notification for student {} after blockchain registration failed",
-                                    deserialize_payload.student_code
-                                );
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Failed to consumer message rabbitmq: {}", e);
-                    delivery.ack(BasicAckOptions::default()).await?;
-                }
-            }
-        }
-
-        Ok(())
-    }
-
-    pub async fn consume_register_new_manager() -> Result<(), anyhow::Error> {
-        tracing::info!(
-            "Starting consumer for register new manager queue: {}",
-            REGISTER_NEW_MANAGER_CHANNEL
-        );
-
-        let rabbit_conn = RABBITMQ_CONNECTION
-            .get()
-            .expect("Failed to connect to rabbitMQ");
-        let channel = rabbit_conn.create_channel().await.expect("created channel");
-
-        tracing::info!("Created RabbitMQ channel, starting to consume messages...");
-
-        let mut consumer = channel
-            .basic_consume(
-                REGISTER_NEW_MANAGER_CHANNEL,
-                "register_manager",
-                BasicConsumeOptions::default(),
-                FieldTable::default(),
-            )
-            .await?;
-
-        tracing::info!("Consumer started successfully, waiting for messages...");
-
-        while let Some(delivery) = consumer.next().await {
-            tracing::debug!("Received message from queue");
-            let delivery = match delivery {
-                Ok(d) => d,
-                Err(e) => {
-                    tracing::error!("Failed to receive message rabbitMQ: {:?}", e);
-                    continue;
-                }
-            };
-
-            match std::str::from_utf8(&delivery.data) {
-                Ok(_payload) => {
-                    let deserialize_payload: RegisterNewManagerMessage =
-                        serde_json::from_slice::<RegisterNewManagerMessage>(&delivery.data)?;
-
-                    tracing::info!(
-                        "Processing register manager message for address: {}",
-                        deserialize_payload.wallet_address
-                    );
-
-                    let ack_options = BasicAckOptions::default();
-                    if let Err(e) = delivery.ack(ack_options).await {
-                        tracing::error!(
-                            "Failed to acknowledge register new manager message: {}",
-                            e
-                        );
-                    } else {
-                        tracing::debug!(
-                            "Message acknowledged, starting blockchain registration..."
-                        );
-
-                        let blockchain = BlockchainService::new().await?;
-                        let result = blockchain
-                            .add_manager(&deserialize_payload.wallet_address)
-                            .await;
-
-                        match result {
-                            Ok(_) => {
-                                // Update user status to Sync after successful blockchain registration
-                                let user_repo = UserRepository::new();
-                                if let Err(status_err) = user_repo
-                                    .update_status_by_email(
-                                        &deserialize_payload.email,
-                                        UserStatus::Sync,
-                                    )
-                                    .await
-                                {
-                                    tracing::error!(
-                                        "Failed to update user status to Sync for {}: {}",
-                                        deserialize_payload.email,
-                                        status_err
-                                    );
-                                }
-
-                                let notification = json!({
-                                    "status": "success",
-                                    "email": deserialize_payload.email,
-                                    "message": "Register manager on blockchain successfully."
-                                })
-                                .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", deserialize_payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-
-                                tracing::info!(
-                                    "Successfully registered new manager {} on blockchain and sent notification",
-                                    deserialize_payload.email
-                                );
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to register new manager: {}", e);
-                                
-                                // Update user status to Failed after blockchain error
-                                let user_repo = UserRepository::new();
-                                if let Err(status_err) = user_repo
-                                    .update_status_by_email(
-                                        &deserialize_payload.email,
-                                        UserStatus::Failed,
-                                    )
-                                    .await
-                                {
-                                    tracing::error!(
-                                        "Failed to update user status to Failed for {}: {}",
-                                        deserialize_payload.email,
-                                        status_err
-                                    );
-                                } else {
-                                    tracing::info!(
-                                        "Set user status to Failed for {} after blockchain error",
-                                        deserialize_payload.email
-                                    );
-                                }
-
-                                let notification = json!({
-                                    "status": "failed",
-                                    "email": deserialize_payload.email,
-                                    "reason": e.to_string(),
-                                    "message": "Failed to register manager on blockchain. Please try again or contact with admin"
-                                })
-                                    .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", deserialize_payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-
-                                tracing::info!(
-                                    "Sent failure notification for new manager {} after blockchain registration failed",
-                                    deserialize_payload.email
-                                );
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Failed to consumer message rabbitmq: {}", e);
-                    delivery.ack(BasicAckOptions::default()).await?;
-                }
-            }
-        }
-
-        Ok(())
-    }
-
-    pub async fn consume_assign_role() -> Result<(), anyhow::Error> {
-        tracing::info!(
-            "Starting consumer for assign role queue: {}",
-            ASSIGN_ROLE_CHANNEL
-        );
-
-        let rabbit_conn = RABBITMQ_CONNECTION
-            .get()
-            .ok_or_else(|| anyhow::anyhow!("RabbitMQ connection not initialized"))?;
-
-        let channel = rabbit_conn
-            .create_channel()
-            .await
-            .context("Failed to create RabbitMQ channel")?;
-
-        let mut consumer = channel
-            .basic_consume(
-                ASSIGN_ROLE_CHANNEL,
-                "assign_role",
-                BasicConsumeOptions::default(),
-                FieldTable::default(),
-            )
-            .await
-            .context("Failed to start consuming from queue")?;
-
-        tracing::info!("Consumer started successfully, waiting for messages...");
-
-        while let Some(delivery) = consumer.next().await {
-            let delivery = match delivery {
-                Ok(d) => d,
-                Err(e) => {
-                    tracing::error!("Failed to receive message rabbitMQ: {:?}", e);
-                    continue;
-                }
-            };
-
-            match serde_json::from_slice::<AssignRoleMessage>(&delivery.data) {
-                Ok(payload) => {
-                    tracing::info!(
-                        "Processing assign role message for address: {}, role: {}",
-                        payload.user_address,
-                        payload.role
-                    );
-
-                    let ack_options = BasicAckOptions::default();
-                    if let Err(e) = delivery.ack(ack_options).await {
-                        tracing::error!("Failed to acknowledge assign role message: {}", e);
-                    } else {
-                        let blockchain = BlockchainService::new().await?;
-                        let result = blockchain
-                            .assign_role(&payload.user_address, payload.role)
-                            .await;
-
-                        match result {
-                            Ok(_) => {
-                                // Update user status to Sync after successful blockchain role assignment
-                                let user_repo = UserRepository::new();
-                                if let Err(status_err) = user_repo
-                                    .update_status_by_email(
-                                        &payload.email,
-                                        UserStatus::Sync,
-                                    )
-                                    .await
-                                {
-                                    tracing::error!(
-                                        "Failed to update user status to Sync for {}: {}",
-                                        payload.email,
-                                        status_err
-                                    );
-                                }
-
-                                let notification = json!({
-                                    "status": "success",
-                                    "email": payload.email,
-                                    "message": "Assign role on blockchain successfully."
-                                })
-                                .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-
-                                tracing::info!(
-                                    "Successfully assigned role {} to {} and sent notification",
-                                    payload.role,
-                                    payload.user_address
-                                );
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to assign role: {}", e);
-                                
-                                // Update user status to Failed after blockchain error
-                                let user_repo = UserRepository::new();
-                                if let Err(status_err) = user_repo
-                                    .update_status_by_email(
-                                        &payload.email,
-                                        UserStatus::Failed,
-                                    )
-                                    .await
-                                {
-                                    tracing::error!(
-                                        "Failed to update user status to Failed for {}: {}",
-                                        payload.email,
-                                        status_err
-                                    );
-                                }
-
-                                let notification = json!({
-                                    "status": "failed",
-                                    "email": payload.email,
-                                    "reason": e.to_string(),
-                                    "message": "Failed to assign role on blockchain. Please try again or contact with admin"
-                                })
-                                .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Failed to deserialize assign role message: {}", e);
-                    delivery.ack(BasicAckOptions::default()).await?;
-                }
-            }
-        }
-
-        Ok(())
-    }
-
-    pub async fn consume_remove_manager() -> Result<(), anyhow::Error> {
-        tracing::info!(
-            "Starting consumer for remove manager queue: {}",
-            REMOVE_MANAGER_CHANNEL
-        );
-
-        let rabbit_conn = RABBITMQ_CONNECTION
-            .get()
-            .ok_or_else(|| anyhow::anyhow!("RabbitMQ connection not initialized"))?;
-
-        let channel = rabbit_conn
-            .create_channel()
-            .await
-            .context("Failed to create RabbitMQ channel")?;
-
-        let mut consumer = channel
-            .basic_consume(
-                REMOVE_MANAGER_CHANNEL,
-                "remove_manager",
-                BasicConsumeOptions::default(),
-                FieldTable::default(),
-            )
-            .await
-            .context("Failed to start consuming from queue")?;
-
-        tracing::info!("Consumer started successfully, waiting for messages...");
-
-        while let Some(delivery) = consumer.next().await {
-            let delivery = match delivery {
-                Ok(d) => d,
-                Err(e) => {
-                    tracing::error!("Failed to receive message rabbitMQ: {:?}", e);
-                    continue;
-                }
-            };
-
-            match serde_json::from_slice::<RemoveManagerMessage>(&delivery.data) {
-                Ok(payload) => {
-                    tracing::info!(
-                        "Processing remove manager message for address: {}",
-                        payload.manager_address
-                    );
-
-                    let ack_options = BasicAckOptions::default();
-                    if let Err(e) = delivery.ack(ack_options).await {
-                        tracing::error!("Failed to acknowledge remove manager message: {}", e);
-                    } else {
-                        let blockchain = BlockchainService::new().await?;
-                        let result = blockchain.remove_manager(&payload.manager_address).await;
-
-                        match result {
-                            Ok(_) => {
-                                // Update user status to Sync after successful blockchain manager removal
-                                let user_repo = UserRepository::new();
-                                if let Err(status_err) = user_repo
-                                    .update_status_by_email(
-                                        &payload.email,
-                                        UserStatus::Sync,
-                                    )
-                                    .await
-                                {
-                                    tracing::error!(
-                                        "Failed to update user status to Sync for {}: {}",
-                                        payload.email,
-                                        status_err
-                                    );
-                                }
-
-                                let notification = json!({
-                                    "status": "success",
-                                    "email": payload.email,
-                                    "message": "Remove manager from blockchain successfully."
-                                })
-                                .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-
-                                tracing::info!(
-                                    "Successfully removed manager {} and sent notification",
-                                    payload.manager_address
-                                );
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to remove manager: {}", e);
-                                
-                                // Update user status to Failed after blockchain error
-                                let user_repo = UserRepository::new();
-                                if let Err(status_err) = user_repo
-                                    .update_status_by_email(
-                                        &payload.email,
-                                        UserStatus::Failed,
-                                    )
-                                    .await
-                                {
-                                    tracing::error!(
-                                        "Failed to update user status to Failed for {}: {}",
-                                        payload.email,
-                                        status_err
-                                    );
-                                }
-
-                                let notification = json!({
-                                    "status": "failed",
-                                    "email": payload.email,
-                                    "reason": e.to_string(),
-                                    "message": "Failed to remove manager from blockchain. Please try again or contact with admin"
-                                })
-                                .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Failed to deserialize remove manager message: {}", e);
-                    delivery.ack(BasicAckOptions::default()).await?;
-                }
-            }
-        }
-
-        Ok(())
-    }
-
-    pub async fn consume_deactivate_student() -> Result<(), anyhow::Error> {
-        tracing::info!(
-            "Starting consumer for deactivate student queue: {}",
-            DEACTIVATE_STUDENT_CHANNEL
-        );
-
-        let rabbit_conn = RABBITMQ_CONNECTION
-            .get()
-            .ok_or_else(|| anyhow::anyhow!("RabbitMQ connection not initialized"))?;
-
-        let channel = rabbit_conn
-            .create_channel()
-            .await
-            .context("Failed to create RabbitMQ channel")?;
-
-        let mut consumer = channel
-            .basic_consume(
-                DEACTIVATE_STUDENT_CHANNEL,
-                "deactivate_student",
-                BasicConsumeOptions::default(),
-                FieldTable::default(),
-            )
-            .await
-            .context("Failed to start consuming from queue")?;
-
-        tracing::info!("Consumer started successfully, waiting for messages...");
-
-        while let Some(delivery) = consumer.next().await {
-            let delivery = match delivery {
-                Ok(d) => d,
-                Err(e) => {
-                    tracing::error!("Failed to receive message rabbitMQ: {:?}", e);
-                    continue;
-                }
-            };
-
-            match serde_json::from_slice::<DeactivateStudentMessage>(&delivery.data) {
-                Ok(payload) => {
-                    tracing::info!(
-                        "Processing deactivate student message for student_id: {}",
-                        payload.student_id
-                    );
-
-                    let ack_options = BasicAckOptions::default();
-                    if let Err(e) = delivery.ack(ack_options).await {
-                        tracing::error!("Failed to acknowledge deactivate student message: {}", e);
-                    } else {
-                        let blockchain = BlockchainService::new().await?;
-                        let result = blockchain.deactivate_student(payload.student_id).await;
-
-                        match result {
-                            Ok(_) => {
-                                // Update user status to Sync after successful blockchain deactivation
-                                let user_repo = UserRepository::new();
-                                if let Err(status_err) = user_repo
-                                    .update_status_by_email(
-                                        &payload.email,
-                                        UserStatus::Sync,
-                                    )
-                                    .await
-                                {
-                                    tracing::error!(
-                                        "Failed to update user status to Sync for {}: {}",
-                                        payload.email,
-                                        status_err
-                                    );
-                                }
-
-                                let notification = json!({
-                                    "status": "success",
-                                    "email": payload.email,
-                                    "message": "Deactivate student on blockchain successfully."
-                                })
-                                .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-
-                                tracing::info!(
-                                    "Successfully deactivated student {} and sent notification",
-                                    payload.student_id
-                                );
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to deactivate student: {}", e);
-                                
-                                // Update user status to Failed after blockchain error
-                                let user_repo = UserRepository::new();
-                                if let Err(status_err) = user_repo
-                                    .update_status_by_email(
-                                        &payload.email,
-                                        UserStatus::Failed,
-                                    )
-                                    .await
-                                {
-                                    tracing::error!(
-                                        "Failed to update user status to Failed for {}: {}",
-                                        payload.email,
-                                        status_err
-                                    );
-                                }
-
-                                let notification = json!({
-                                    "status": "failed",
-                                    "email": payload.email,
-                                    "reason": e.to_string(),
-                                    "message": "Failed to deactivate student on blockchain. Please try again or contact with admin"
-                                })
-                                .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Failed to deserialize deactivate student message: {}", e);
-                    delivery.ack(BasicAckOptions::default()).await?;
-                }
-            }
-        }
-
-        Ok(())
-    }
-
-    pub async fn consume_activate_student() -> Result<(), anyhow::Error> {
-        tracing::info!(
-            "Starting consumer for activate student queue: {}",
-            ACTIVATE_STUDENT_CHANNEL
-        );
-
-        let rabbit_conn = RABBITMQ_CONNECTION
-            .get()
-            .ok_or_else(|| anyhow::anyhow!("RabbitMQ connection not initialized"))?;
-
-        let channel = rabbit_conn
-            .create_channel()
-            .await
-            .context("Failed to create RabbitMQ channel")?;
-
-        let mut consumer = channel
-            .basic_consume(
-                ACTIVATE_STUDENT_CHANNEL,
-                "activate_student",
-                BasicConsumeOptions::default(),
-                FieldTable::default(),
-            )
-            .await
-            .context("Failed to start consuming from queue")?;
-
-        tracing::info!("Consumer started successfully, waiting for messages...");
-
-        while let Some(delivery) = consumer.next().await {
-            let delivery = match delivery {
-                Ok(d) => d,
-                Err(e) => {
-                    tracing::error!("Failed to receive message rabbitMQ: {:?}", e);
-                    continue;
-                }
-            };
-
-            match serde_json::from_slice::<ActivateStudentMessage>(&delivery.data) {
-                Ok(payload) => {
-                    tracing::info!(
-                        "Processing activate student message for student_id: {}",
-                        payload.student_id
-                    );
-
-                    let ack_options = BasicAckOptions::default();
-                    if let Err(e) = delivery.ack(ack_options).await {
-                        tracing::error!("Failed to acknowledge activate student message: {}", e);
-                    } else {
-                        let blockchain = BlockchainService::new().await?;
-                        let result = blockchain.activate_student(payload.student_id).await;
-
-                        match result {
-                            Ok(_) => {
-                                // Update user status to Sync after successful blockchain activation
-                                let user_repo = UserRepository::new();
-                                if let Err(status_err) = user_repo
-                                    .update_status_by_email(
-                                        &payload.email,
-                                        UserStatus::Sync,
-                                    )
-                                    .await
-                                {
-                                    tracing::error!(
-                                        "Failed to update user status to Sync for {}: {}",
-                                        payload.email,
-                                        status_err
-                                    );
-                                }
-
-                                let notification = json!({
-                                    "status": "success",
-                                    "email": payload.email,
-                                    "message": "Activate student on blockchain successfully."
-                                })
-                                .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-
-                                tracing::info!(
-                                    "Successfully activated student {} and sent notification",
-                                    payload.student_id
-                                );
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to activate student: {}", e);
-                                
-                                // Update user status to Failed after blockchain error
-                                let user_repo = UserRepository::new();
-                                if let Err(status_err) = user_repo
-                                    .update_status_by_email(
-                                        &payload.email,
-                                        UserStatus::Failed,
-                                    )
-                                    .await
-                                {
-                                    tracing::error!(
-                                        "Failed to update user status to Failed for {}: {}",
-                                        payload.email,
-                                        status_err
-                                    );
-                                }
-
-                                let notification = json!({
-                                    "status": "failed",
-                                    "email": payload.email,
-                                    "reason": e.to_string(),
-                                    "message": "Failed to activate student on blockchain. Please try again or contact with admin"
-                                })
-                                .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Failed to deserialize activate student message: {}", e);
-                    delivery.ack(BasicAckOptions::default()).await?;
-                }
-            }
-        }
-
-        Ok(())
-    }
-
-    pub async fn consume_register_students_batch() -> Result<(), anyhow::Error> {
-        tracing::info!(
-            "Starting consumer for register students batch queue: {}",
-            REGISTER_STUDENTS_BATCH_CHANNEL
-        );
-
-        let rabbit_conn = RABBITMQ_CONNECTION
-            .get()
-            .ok_or_else(|| anyhow::anyhow!("RabbitMQ connection not initialized"))?;
-
-        let channel = rabbit_conn
-            .create_channel()
-            .await
-            .context("Failed to create RabbitMQ channel")?;
-
-        let mut consumer = channel
-            .basic_consume(
-                REGISTER_STUDENTS_BATCH_CHANNEL,
-                "register_students_batch",
-                BasicConsumeOptions::default(),
-                FieldTable::default(),
-            )
-            .await
-            .context("Failed to start consuming from queue")?;
-
-        tracing::info!("Consumer started successfully, waiting for messages...");
-
-        while let Some(delivery) = consumer.next().await {
-            let delivery = match delivery {
-                Ok(d) => d,
-                Err(e) => {
-                    tracing::error!("Failed to receive message rabbitMQ: {:?}", e);
-                    continue;
-                }
-            };
-
-            match serde_json::from_slice::<RegisterStudentsBatchMessage>(&delivery.data) {
-                Ok(payload) => {
-                    tracing::info!(
-                        "Processing register students batch message for {} students",
-                        payload.wallet_addresses.len()
-                    );
-
-                    let ack_options = BasicAckOptions::default();
-                    if let Err(e) = delivery.ack(ack_options).await {
-                        tracing::error!(
-                            "Failed to acknowledge register students batch message: {}",
-                            e
-                        );
-                    } else {
-                        let blockchain = BlockchainService::new().await?;
-                        let result = blockchain
-                            .register_students_batch(
-                                payload.wallet_addresses.clone(),
-                                payload.student_codes.clone(),
-                                payload.full_names.clone(),
-                                payload.emails.clone(),
-                            )
-                            .await;
-
-                        match result {
-                            Ok(_) => {
-                                // Update status to Sync for all students after successful batch registration
-                                let user_repo = UserRepository::new();
-                                for email in &payload.emails {
-                                    if let Err(status_err) = user_repo
-                                        .update_status_by_email(email, UserStatus::Sync)
-                                        .await
-                                    {
-                                        tracing::error!(
-                                            "Failed to update user status to Sync for {}: {}",
-                                            email,
-                                            status_err
-                                        );
-                                    }
-                                }
-
-                                // Send notification to creator
-                                let notification = json!({
-                                    "status": "success",
-                                    "total_students": payload.emails.len(),
-                                    "message": "Batch register students on blockchain successfully."
-                                })
-                                .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-
-                                tracing::info!(
-                                    "Successfully registered {} students in batch and sent notifications",
-                                    payload.wallet_addresses.len()
-                                );
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to register students batch: {}", e);
-                                
-                                // Update status to Failed for all students after batch registration error
-                                let user_repo = UserRepository::new();
-                                for email in &payload.emails {
-                                    if let Err(status_err) = user_repo
-                                        .update_status_by_email(email, UserStatus::Failed)
-                                        .await
-                                    {
-                                        tracing::error!(
-                                            "Failed to update user status to Failed for {}: {}",
-                                            email,
-                                            status_err
-                                        );
-                                    }
-                                }
-
-                                // Send failure notification to creator
-                                let notification = json!({
-                                    "status": "failed",
-                                    "total_students": payload.emails.len(),
-                                    "reason": e.to_string(),
-                                    "message": "Failed to register students batch on blockchain. Please try again or contact with admin"
-                                })
-                                .to_string();
-
-                                RedisEmitter::emit_to_rooom(
-                                    &format!("user:{}", payload.creator_user_id),
-                                    &notification,
-                                )
-                                .await;
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::error!(
-                        "Failed to deserialize register students batch message: {}",
-                        e
-                    );
-                    delivery.ack(BasicAckOptions::default()).await?;
-                }
-            }
-        }
-
-        Ok(())
-    }
-
     pub async fn consumer_create_user_db() -> Result<(), anyhow::Error> {
         tracing::info!(
             "Starting consumer for create user db queue: {}",
             CREATE_USER_DB
         );
 
-        let rabbit_conn = RABBITMQ_CONNECTION
-            .get()
-            .expect("Failed to connect to rabbitMQ");
-        let channel = rabbit_conn.create_channel().await.expect("created channel");
+        let channel = rabbitmq().create_channel().await?;
 
         tracing::info!("Created RabbitMQ channel, starting to consume messages...");
 
@@ -1143,48 +61,36 @@ impl RabbitMqConsumer {
 
             match std::str::from_utf8(&delivery.data) {
                 Ok(_payload) => {
+                    // A bad payload would fail the same way on every redelivery, so drop it
                     let deserialize_payload: UserCsvColumn =
-                        serde_json::from_slice::<UserCsvColumn>(&delivery.data)?;
+                        match serde_json::from_slice::<UserCsvColumn>(&delivery.data) {
+                            Ok(payload) => payload,
+                            Err(e) => {
+                                tracing::error!("Failed to decode create user message: {}", e);
+                                delivery.ack(BasicAckOptions::default()).await?;
+                                continue;
+                            }
+                        };
 
                     tracing::info!(
                         "Processing register student message for email: {}",
                         deserialize_payload.email,
                     );
 
-                    let ack_options = BasicAckOptions::default();
-                    if let Err(e) = delivery.ack(ack_options).await {
-                        tracing::error!("Failed to acknowledge register new user message: {}", e);
-                    } else {
-                        tracing::debug!("Message acknowledged, starting user create db...");
-
-                        match Self::create_user_from_csv_payload(&deserialize_payload).await {
-                            Ok(_) => {
-                                if let Some(file_name) = deserialize_payload.file_name.as_deref() {
-                                    if let Some(row_number) = deserialize_payload.row_number {
-                                        if let Err(progress_err) =
-                                            FileHandleTrackProgress::set_current_file_progress(
-                                                file_name, row_number,
-                                            )
-                                            .await
-                                        {
-                                            tracing::error!(
-                                                "Failed to update file progress for {}: {}",
-                                                file_name,
-                                                progress_err
-                                            );
-                                        }
-                                    }
-
-                                    if let Err(success_err) =
-                                        FileHandleTrackProgress::increment_success(file_name).await
-                                    {
+                    match Self::create_user_from_csv_payload(&deserialize_payload).await {
+                        Ok(_) => {
+                            if let Some(file_name) = deserialize_payload.file_name.as_deref() {
+                                let completed = match FileHandleTrackProgress::increment_success(file_name).await {
+                                    Err(success_err) => {
                                         tracing::error!(
                                             "Failed to increment success counter for {}: {}",
                                             file_name,
                                             success_err
                                         );
-                                    } else {
-                                        // Check if process is complete and update status
+                                        false
+                                    }
+                                    Ok(true) => {
+                                        // Last item of the job: update the upload status
                                         let file_repo = FileUploadRepository::new();
                                         if let Err(status_err) = file_repo
                                             .check_and_update_status_on_completion(
@@ -1199,22 +105,41 @@ impl RabbitMqConsumer {
                                                 status_err
                                             );
                                         }
+                                        Self::notify_import_completed(file_name).await;
+                                        true
                                     }
-                                }
+                                    Ok(false) => false,
+                                };
+                                Self::emit_import_progress(file_name, completed).await;
                             }
-                            Err(err) => {
-                                tracing::error!("Failed to create user from CSV payload: {err:?}");
-                                if let Some(file_name) = deserialize_payload.file_name.as_deref() {
-                                    if let Err(failed_err) =
-                                        FileHandleTrackProgress::increment_failed(file_name).await
-                                    {
+                        }
+                        Err(err) => {
+                            tracing::error!("Failed to create user from CSV payload: {err:?}");
+                            if let Some(file_name) = deserialize_payload.file_name.as_deref() {
+                                JobEvents::publish(
+                                    file_name,
+                                    &JobEvent::RowFailed {
+                                        phase: JobPhase::CreateUser,
+                                        row_number: deserialize_payload.row_number,
+                                        email: deserialize_payload.email.clone(),
+                                        reason: err.to_string(),
+                                    },
+                                )
+                                .await;
+                                Self::notify_row_failed(&deserialize_payload, file_name, &err)
+                                    .await;
+
+                                let completed = match FileHandleTrackProgress::increment_failed(file_name).await {
+                                    Err(failed_err) => {
                                         tracing::error!(
                                             "Failed to increment failed counter for {}: {}",
                                             file_name,
                                             failed_err
                                         );
-                                    } else {
-                                        // Check if process is complete and update status
+                                        false
+                                    }
+                                    Ok(true) => {
+                                        // Last item of the job: update the upload status
                                         let file_repo = FileUploadRepository::new();
                                         if let Err(status_err) = file_repo
                                             .check_and_update_status_on_completion(
@@ -1229,11 +154,20 @@ impl RabbitMqConsumer {
                                                 status_err
                                             );
                                         }
+                                        Self::notify_import_completed(file_name).await;
+                                        true
                                     }
-                                }
+                                    Ok(false) => false,
+                                };
+                                Self::emit_import_progress(file_name, completed).await;
                             }
                         }
                     }
+
+                    // Handled failures are recorded on the row, so the job is done either way
+                    if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+                        tracing::error!("Failed to acknowledge create user message: {}", e);
+                    }
                 }
                 Err(e) => {
                     tracing::error!("Failed to consumer message rabbitmq: {}", e);
@@ -1247,6 +181,94 @@ impl RabbitMqConsumer {
 }
 
 impl RabbitMqConsumer {
+    /// Who uploaded the CSV file, the user told about its rows
+    async fn uploader_of(file_name: &str) -> Option<String> {
+        match FileUploadRepository::new().find_by_file_name(file_name).await {
+            Ok(Some(file_upload)) => Some(file_upload.user_id.to_string()),
+            Ok(None) => {
+                tracing::warn!("No upload found for {}, not notifying anyone", file_name);
+                None
+            }
+            Err(e) => {
+                tracing::error!("Failed to find the upload of {}: {}", file_name, e);
+                None
+            }
+        }
+    }
+
+    async fn notify_row_failed(payload: &UserCsvColumn, file_name: &str, error: &anyhow::Error) {
+        let Some(uploader) = Self::uploader_of(file_name).await else {
+            return;
+        };
+
+        let data = Map::from_iter([
+            ("file_name".to_string(), json!(file_name)),
+            ("row_number".to_string(), json!(payload.row_number)),
+            ("email".to_string(), json!(payload.email)),
+            ("reason".to_string(), json!(error.to_string())),
+        ]);
+        notify_user(
+            &uploader,
+            NotificationKind::UserImportRowFailed,
+            false,
+            "Failed to import a user from the CSV file",
+            data,
+        )
+        .await;
+    }
+
+    /// Counters of the import, pushed to the uploader after each row
+    async fn emit_import_progress(file_name: &str, completed: bool) {
+        let progress = match helper_get_current_file_progress(file_name).await {
+            Ok(progress) => progress,
+            Err(e) => {
+                tracing::warn!("Failed to read import progress of {}: {}", file_name, e);
+                return;
+            }
+        };
+        let Some(uploader) = Self::uploader_of(file_name).await else {
+            return;
+        };
+
+        let event = SocketEvent::BatchProgress(BatchProgressPayload {
+            phase: JobPhase::CreateUser,
+            job_key: file_name.to_string(),
+            total: progress.total,
+            success: progress.success,
+            failed: progress.failed,
+            completed,
+        });
+        RedisEmitter::emit_to_room(&user_room(uploader), &event).await;
+    }
+
+    /// Sum up the import once its last row is done, so the uploader knows whether to look
+    /// for failed rows
+    async fn notify_import_completed(file_name: &str) {
+        let Some(uploader) = Self::uploader_of(file_name).await else {
+            return;
+        };
+
+        let mut data = Map::from_iter([("file_name".to_string(), json!(file_name))]);
+        match helper_get_current_file_progress(file_name).await {
+            Ok(progress) => {
+                data.insert("total".to_string(), json!(progress.total));
+                data.insert("success".to_string(), json!(progress.success));
+                data.insert("failed".to_string(), json!(progress.failed));
+            }
+            Err(e) => tracing::warn!("Failed to read import progress of {}: {}", file_name, e),
+        }
+        let succeeded = data.get("failed").and_then(Value::as_u64) == Some(0);
+
+        notify_user(
+            &uploader,
+            NotificationKind::UsersImportCompleted,
+            succeeded,
+            "CSV user import finished",
+            data,
+        )
+        .await;
+    }
+
     async fn create_user_from_csv_payload(payload: &UserCsvColumn) -> anyhow::Result<()> {
         let user_repo = UserRepository::new();
         let wallet_repo = WalletRepository::new();
@@ -1265,9 +287,11 @@ impl RabbitMqConsumer {
         let (wallet_address, wallet_private_key) =
             BlockchainService::generate_wallet().context("Failed to generate wallet")?;
 
-        let encrypted_private_key =
-            encrypt_private_key(&wallet_private_key, &APP_CONFIG.encryption_key)
-                .map_err(|e| anyhow!("Failed to encrypt private key: {e}"))?;
+        let encrypted_private_key = get_key_provider()
+            .await?
+            .encrypt(&wallet_private_key, CONTEXT_WALLET_PRIVATE_KEY)
+            .await
+            .map_err(|e| anyhow!("Failed to encrypt private key: {e}"))?;
 
         let user_id = Uuid::new_v4();
         let wallet_id = Uuid::new_v4();
@@ -1292,8 +316,10 @@ impl RabbitMqConsumer {
             }
         };
 
+        let db = user_repo.get_connection();
         user_repo
             .create(
+                db,
                 user_id,
                 payload.first_name.clone(),
                 payload.last_name.clone(),
@@ -1311,6 +337,7 @@ impl RabbitMqConsumer {
 
         wallet_repo
             .create(
+                db,
                 wallet_id,
                 user_id,
                 wallet_address.clone(),
@@ -1323,7 +350,6 @@ impl RabbitMqConsumer {
             .await
             .context("Failed to create wallet")?;
 
-        let db = user_repo.get_connection();
         let now = Utc::now().naive_utc();
         for major_id in payload.major_ids.iter() {
             let major_id_uuid = Uuid::parse_str(major_id).expect("Failed to parse major id");
@@ -0,0 +1,258 @@
+//! Admin access to the dead-letter queues filled by [`RetryingQueue`](super::retry::RetryingQueue).
+//!
+//! RabbitMQ has no way to read a message in the middle of a queue, so every operation walks
+//! the dead-letter queue with `basic_get`, holds the messages unacked, settles the selected
+//! ones and requeues the rest.
+
+use crate::rabbitmq_service::connection::rabbitmq;
+use crate::rabbitmq_service::retry::{
+    DEAD_LETTERED_AT_HEADER, LAST_ERROR_HEADER, RETRY_COUNT_HEADER, amqp_u32, dead_letter_queue,
+    retry_count,
+};
+use crate::rabbitmq_service::topology::{PERSISTENT, retrying_queues};
+use anyhow::{Context, Result, bail};
+use lapin::message::Delivery;
+use lapin::options::{
+    BasicAckOptions, BasicGetOptions, BasicNackOptions, BasicPublishOptions, QueueDeclareOptions,
+};
+use lapin::types::{AMQPValue, FieldTable};
+use lapin::{BasicProperties, Channel};
+
+#[derive(Debug, Clone)]
+pub struct DeadLetterQueueStats {
+    pub queue: &'static str,
+    pub dead_letter_queue: String,
+    pub message_count: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    pub message_id: Option<String>,
+    pub retry_count: u32,
+    pub last_error: Option<String>,
+    pub dead_lettered_at: Option<i64>,
+    pub payload: String,
+}
+
+impl DeadLetter {
+    fn from_delivery(delivery: &Delivery) -> Self {
+        let header = |name: &str| {
+            delivery
+                .properties
+                .headers()
+                .as_ref()
+                .and_then(|headers| headers.inner().get(name).cloned())
+        };
+
+        Self {
+            message_id: message_id(delivery),
+            retry_count: retry_count(delivery),
+            last_error: header(LAST_ERROR_HEADER).and_then(|value| match value {
+                AMQPValue::LongString(error) => Some(error.to_string()),
+                _ => None,
+            }),
+            dead_lettered_at: header(DEAD_LETTERED_AT_HEADER).and_then(|value| match value {
+                AMQPValue::LongLongInt(timestamp) => Some(timestamp),
+                other => amqp_u32(&other).map(i64::from),
+            }),
+            payload: String::from_utf8_lossy(&delivery.data).into_owned(),
+        }
+    }
+}
+
+fn message_id(delivery: &Delivery) -> Option<String> {
+    delivery
+        .properties
+        .message_id()
+        .as_ref()
+        .map(|id| id.to_string())
+}
+
+/// Which dead-lettered messages an operation applies to
+#[derive(Debug, Clone)]
+pub enum Selection {
+    All,
+    Ids(Vec<String>),
+}
+
+impl Selection {
+    fn contains(&self, delivery: &Delivery) -> bool {
+        match self {
+            Selection::All => true,
+            Selection::Ids(ids) => message_id(delivery).is_some_and(|id| ids.contains(&id)),
+        }
+    }
+}
+
+pub struct DeadLetterService;
+
+impl DeadLetterService {
+    /// Returns the queue name as the `'static` constant, or fails for queues without retries
+    pub fn validate_queue(queue: &str) -> Result<&'static str> {
+        match retrying_queues().find(|name| *name == queue) {
+            Some(name) => Ok(name),
+            None => bail!("Unknown queue '{}'", queue),
+        }
+    }
+
+    pub async fn list() -> Result<Vec<DeadLetterQueueStats>> {
+        let channel = Self::channel().await?;
+        let mut stats = Vec::new();
+        for queue in retrying_queues() {
+            stats.push(DeadLetterQueueStats {
+                queue,
+                dead_letter_queue: dead_letter_queue(queue),
+                message_count: Self::message_count(&channel, queue).await?,
+            });
+        }
+        let _ = channel.close(200, "OK").await;
+        Ok(stats)
+    }
+
+    /// The first `limit` dead letters of `queue`, left in place
+    pub async fn peek(queue: &str, limit: usize) -> Result<Vec<DeadLetter>> {
+        let queue = Self::validate_queue(queue)?;
+        let channel = Self::channel().await?;
+
+        let held = Self::take(&channel, queue, limit).await?;
+        let dead_letters = held.iter().map(DeadLetter::from_delivery).collect();
+        Self::release(held).await;
+
+        let _ = channel.close(200, "OK").await;
+        Ok(dead_letters)
+    }
+
+    /// Put the selected dead letters back on the work queue with a fresh retry budget.
+    /// Returns how many were replayed.
+    pub async fn replay(queue: &str, selection: Selection) -> Result<usize> {
+        let queue = Self::validate_queue(queue)?;
+        let channel = Self::channel().await?;
+
+        let count = Self::message_count(&channel, queue).await? as usize;
+        let held = Self::take(&channel, queue, count).await?;
+        let mut replayed = 0;
+        let mut rest = Vec::new();
+        for delivery in held {
+            if !selection.contains(&delivery) {
+                rest.push(delivery);
+                continue;
+            }
+
+            let published = channel
+                .basic_publish(
+                    "",
+                    queue,
+                    BasicPublishOptions::default(),
+                    &delivery.data,
+                    Self::replay_properties(&delivery),
+                )
+                .await;
+            match published {
+                Ok(_) => {
+                    delivery
+                        .ack(BasicAckOptions::default())
+                        .await
+                        .context("Failed to remove replayed message")?;
+                    replayed += 1;
+                }
+                Err(e) => {
+                    tracing::error!("Failed to replay dead letter to {}: {}", queue, e);
+                    rest.push(delivery);
+                }
+            }
+        }
+        Self::release(rest).await;
+
+        tracing::info!("Replayed {} dead letters to {}", replayed, queue);
+        let _ = channel.close(200, "OK").await;
+        Ok(replayed)
+    }
+
+    /// Drop the selected dead letters for good. Returns how many were discarded.
+    pub async fn discard(queue: &str, selection: Selection) -> Result<usize> {
+        let queue = Self::validate_queue(queue)?;
+        let channel = Self::channel().await?;
+
+        let count = Self::message_count(&channel, queue).await? as usize;
+        let held = Self::take(&channel, queue, count).await?;
+        let mut discarded = 0;
+        let mut rest = Vec::new();
+        for delivery in held {
+            if selection.contains(&delivery) {
+                delivery
+                    .ack(BasicAckOptions::default())
+                    .await
+                    .context("Failed to discard message")?;
+                discarded += 1;
+            } else {
+                rest.push(delivery);
+            }
+        }
+        Self::release(rest).await;
+
+        tracing::warn!("Discarded {} dead letters of {}", discarded, queue);
+        let _ = channel.close(200, "OK").await;
+        Ok(discarded)
+    }
+
+    async fn channel() -> Result<Channel> {
+        rabbitmq().create_channel().await
+    }
+
+    async fn message_count(channel: &Channel, queue: &str) -> Result<u32> {
+        let dlq = dead_letter_queue(queue);
+        let declared = channel
+            .queue_declare(
+                &dlq,
+                QueueDeclareOptions {
+                    passive: true,
+                    ..QueueDeclareOptions::default()
+                },
+                FieldTable::default(),
+            )
+            .await
+            .with_context(|| format!("Failed to inspect queue {}", dlq))?;
+        Ok(declared.message_count())
+    }
+
+    /// Get up to `limit` dead letters without acking them
+    async fn take(channel: &Channel, queue: &str, limit: usize) -> Result<Vec<Delivery>> {
+        let dlq = dead_letter_queue(queue);
+        let mut held = Vec::new();
+        while held.len() < limit {
+            let message = channel
+                .basic_get(&dlq, BasicGetOptions { no_ack: false })
+                .await
+                .with_context(|| format!("Failed to read queue {}", dlq))?;
+            match message {
+                Some(message) => held.push(message.delivery),
+                None => break,
+            }
+        }
+        Ok(held)
+    }
+
+    /// Put messages that were not settled back into the dead-letter queue
+    async fn release(held: Vec<Delivery>) {
+        let requeue = BasicNackOptions {
+            requeue: true,
+            ..BasicNackOptions::default()
+        };
+        for delivery in held {
+            if let Err(e) = delivery.nack(requeue).await {
+                tracing::error!("Failed to requeue dead letter: {}", e);
+            }
+        }
+    }
+
+    fn replay_properties(delivery: &Delivery) -> BasicProperties {
+        let mut headers = delivery.properties.headers().clone().unwrap_or_default();
+        headers.insert(RETRY_COUNT_HEADER.into(), AMQPValue::LongUInt(0));
+
+        delivery
+            .properties
+            .clone()
+            .with_headers(headers)
+            .with_delivery_mode(PERSISTENT)
+    }
+}
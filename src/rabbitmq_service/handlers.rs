@@ -0,0 +1,485 @@
+//! One [`JobHandler`] per blockchain queue; [`JobRunner`](super::job_runner::JobRunner)
+//! does the rest.
+
+use crate::blockchain::BlockchainService;
+use crate::email_service::EmailService;
+use crate::email_service::template::EmailTemplate;
+use crate::entities::sea_orm_active_enums::NotificationKind;
+use crate::rabbitmq_service::job_runner::JobHandler;
+use crate::rabbitmq_service::structs::{
+    ActivateStudentMessage, AssignRoleMessage, DeactivateStudentMessage, RegisterNewManagerMessage,
+    RegisterNewUserMessage, RegisterStudentsBatchMessage, RemoveManagerMessage,
+};
+use crate::rabbitmq_service::topology::{
+    ACTIVATE_STUDENT_CHANNEL, ASSIGN_ROLE_CHANNEL, DEACTIVATE_STUDENT_CHANNEL,
+    REGISTER_NEW_MANAGER_CHANNEL, REGISTER_NEW_USER_CHANNEL, REGISTER_STUDENTS_BATCH_CHANNEL,
+    REMOVE_MANAGER_CHANNEL,
+};
+use crate::redis_service::job_events::{JobEvent, JobEvents, JobPhase};
+use crate::redis_service::redis_emitter::RedisEmitter;
+use crate::redis_service::redis_service::{
+    BlockchainRegistrationProgress, helper_get_blockchain_registration_progress,
+};
+use crate::redis_service::socket_events::{BatchProgressPayload, SocketEvent, user_room};
+use crate::repositories::file_upload_repository::{FileUploadRepository, FileUploadStatus};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{Map, Value, json};
+
+fn email_field(email: &str) -> Map<String, Value> {
+    Map::from_iter([("email".to_string(), json!(email))])
+}
+
+/// Whether a wallet already has a student id on-chain
+async fn is_registered(blockchain: &BlockchainService, wallet_address: &str) -> Result<bool> {
+    Ok(blockchain.get_student_id_by_address(wallet_address).await? > 0)
+}
+
+pub struct RegisterStudentHandler;
+
+impl RegisterStudentHandler {
+    /// Count the student in its upload job, close the job after the last one and push the
+    /// counters to whoever started the upload
+    async fn record_progress(file_upload_history_id: &str, requested_by: &str, succeeded: bool) {
+        let completed = if succeeded {
+            BlockchainRegistrationProgress::increment_success(file_upload_history_id).await
+        } else {
+            BlockchainRegistrationProgress::increment_failed(file_upload_history_id).await
+        };
+
+        match completed {
+            Err(e) => {
+                tracing::error!(
+                    "Failed to update blockchain registration progress for {}: {}",
+                    file_upload_history_id,
+                    e
+                );
+                return;
+            }
+            Ok(true) => {
+                // Last item of the job: update the upload status
+                if let Err(e) = FileUploadRepository::new()
+                    .check_and_update_blockchain_status_on_completion(
+                        file_upload_history_id,
+                        FileUploadStatus::SyncBlockchain,
+                    )
+                    .await
+                {
+                    tracing::error!(
+                        "Failed to check and update blockchain status for {}: {}",
+                        file_upload_history_id,
+                        e
+                    );
+                }
+            }
+            Ok(false) => {}
+        }
+
+        match helper_get_blockchain_registration_progress(file_upload_history_id).await {
+            Ok(progress) => {
+                let event = SocketEvent::BatchProgress(BatchProgressPayload {
+                    phase: JobPhase::BlockchainRegistration,
+                    job_key: file_upload_history_id.to_string(),
+                    total: progress.total,
+                    success: progress.success,
+                    failed: progress.failed,
+                    completed: matches!(completed, Ok(true)),
+                });
+                RedisEmitter::emit_to_room(&user_room(requested_by), &event).await;
+            }
+            Err(e) => tracing::warn!(
+                "Failed to read blockchain registration progress for {}: {}",
+                file_upload_history_id,
+                e
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl JobHandler<RegisterNewUserMessage> for RegisterStudentHandler {
+    const QUEUE: &'static str = REGISTER_NEW_USER_CHANNEL;
+    const CONSUMER_TAG: &'static str = "register_student";
+    const SUCCESS_MESSAGE: &'static str = "Register student on blockchain successfully.";
+    const FAILURE_MESSAGE: &'static str =
+        "Failed to register student on blockchain. Please try again or contact with admin";
+    const SUCCESS_KIND: NotificationKind = NotificationKind::StudentRegistered;
+    const FAILURE_KIND: NotificationKind = NotificationKind::StudentRegistrationFailed;
+
+    fn describe(&self, message: &RegisterNewUserMessage) -> String {
+        format!(
+            "student_code: {}, email: {}",
+            message.student_code, message.email
+        )
+    }
+
+    fn affected_emails<'a>(&self, message: &'a RegisterNewUserMessage) -> Vec<&'a str> {
+        vec![&message.email]
+    }
+
+    fn requested_by<'a>(&self, message: &'a RegisterNewUserMessage) -> &'a str {
+        &message.creator_user_id
+    }
+
+    fn notification_fields(&self, message: &RegisterNewUserMessage) -> Map<String, Value> {
+        let mut fields = Map::new();
+        fields.insert("student_code".to_string(), json!(message.student_code));
+        fields.insert("email".to_string(), json!(message.email));
+        fields
+    }
+
+    async fn handle(&self, message: &RegisterNewUserMessage) -> Result<()> {
+        let blockchain = BlockchainService::new().await?;
+        if is_registered(&blockchain, &message.wallet_address).await? {
+            tracing::info!(
+                "Student {} is already registered on blockchain",
+                message.wallet_address
+            );
+            return Ok(());
+        }
+
+        if let Err(e) = blockchain
+            .register_student(
+                &message.wallet_address,
+                &message.student_code,
+                &message.full_name,
+                &message.email,
+            )
+            .await
+        {
+            // A duplicate that got past the check above reverts with "already registered"
+            if is_registered(&blockchain, &message.wallet_address)
+                .await
+                .unwrap_or(false)
+            {
+                return Ok(());
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn on_success(&self, message: &RegisterNewUserMessage) {
+        if let Some(file_upload_history_id) = message.file_upload_history_id.as_deref() {
+            Self::record_progress(file_upload_history_id, &message.creator_user_id, true).await;
+        }
+
+        // The registration stands either way, so a lost email is only logged, and the job
+        // slot is not held while SMTP retries
+        let email = EmailTemplate::AccountActivation {
+            full_name: message.full_name.clone(),
+            email: message.email.clone(),
+            student_code: message.student_code.clone(),
+        };
+        EmailService::send_in_background(
+            message.email.clone(),
+            email,
+            EmailService::default_locale(),
+        );
+    }
+
+    async fn on_failure(&self, message: &RegisterNewUserMessage, error: &anyhow::Error) {
+        if let Some(file_upload_history_id) = message.file_upload_history_id.as_deref() {
+            JobEvents::publish(
+                file_upload_history_id,
+                &JobEvent::RowFailed {
+                    phase: JobPhase::BlockchainRegistration,
+                    row_number: None,
+                    email: message.email.clone(),
+                    reason: error.to_string(),
+                },
+            )
+            .await;
+            Self::record_progress(file_upload_history_id, &message.creator_user_id, false).await;
+        }
+    }
+}
+
+pub struct RegisterManagerHandler;
+
+#[async_trait]
+impl JobHandler<RegisterNewManagerMessage> for RegisterManagerHandler {
+    const QUEUE: &'static str = REGISTER_NEW_MANAGER_CHANNEL;
+    const CONSUMER_TAG: &'static str = "register_manager";
+    const SUCCESS_MESSAGE: &'static str = "Register manager on blockchain successfully.";
+    const FAILURE_MESSAGE: &'static str =
+        "Failed to register manager on blockchain. Please try again or contact with admin";
+    const SUCCESS_KIND: NotificationKind = NotificationKind::ManagerRegistered;
+    const FAILURE_KIND: NotificationKind = NotificationKind::ManagerRegistrationFailed;
+
+    fn describe(&self, message: &RegisterNewManagerMessage) -> String {
+        format!("address: {}", message.wallet_address)
+    }
+
+    fn affected_emails<'a>(&self, message: &'a RegisterNewManagerMessage) -> Vec<&'a str> {
+        vec![&message.email]
+    }
+
+    fn requested_by<'a>(&self, message: &'a RegisterNewManagerMessage) -> &'a str {
+        &message.creator_user_id
+    }
+
+    fn notification_fields(&self, message: &RegisterNewManagerMessage) -> Map<String, Value> {
+        email_field(&message.email)
+    }
+
+    async fn handle(&self, message: &RegisterNewManagerMessage) -> Result<()> {
+        let blockchain = BlockchainService::new().await?;
+        if blockchain.is_manager(&message.wallet_address).await? {
+            tracing::info!("{} is already a manager", message.wallet_address);
+            return Ok(());
+        }
+
+        blockchain.add_manager(&message.wallet_address).await?;
+        Ok(())
+    }
+}
+
+pub struct AssignRoleHandler;
+
+#[async_trait]
+impl JobHandler<AssignRoleMessage> for AssignRoleHandler {
+    const QUEUE: &'static str = ASSIGN_ROLE_CHANNEL;
+    const CONSUMER_TAG: &'static str = "assign_role";
+    const SUCCESS_MESSAGE: &'static str = "Assign role on blockchain successfully.";
+    const FAILURE_MESSAGE: &'static str =
+        "Failed to assign role on blockchain. Please try again or contact with admin";
+    const SUCCESS_KIND: NotificationKind = NotificationKind::RoleAssigned;
+    const FAILURE_KIND: NotificationKind = NotificationKind::RoleAssignmentFailed;
+
+    fn describe(&self, message: &AssignRoleMessage) -> String {
+        format!("address: {}, role: {}", message.user_address, message.role)
+    }
+
+    fn affected_emails<'a>(&self, message: &'a AssignRoleMessage) -> Vec<&'a str> {
+        vec![&message.email]
+    }
+
+    fn requested_by<'a>(&self, message: &'a AssignRoleMessage) -> &'a str {
+        &message.creator_user_id
+    }
+
+    fn notification_fields(&self, message: &AssignRoleMessage) -> Map<String, Value> {
+        email_field(&message.email)
+    }
+
+    async fn handle(&self, message: &AssignRoleMessage) -> Result<()> {
+        let blockchain = BlockchainService::new().await?;
+        if blockchain
+            .has_role(&message.user_address, message.role)
+            .await?
+        {
+            tracing::info!("{} already has role {}", message.user_address, message.role);
+            return Ok(());
+        }
+
+        blockchain
+            .assign_role(&message.user_address, message.role)
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct RemoveManagerHandler;
+
+#[async_trait]
+impl JobHandler<RemoveManagerMessage> for RemoveManagerHandler {
+    const QUEUE: &'static str = REMOVE_MANAGER_CHANNEL;
+    const CONSUMER_TAG: &'static str = "remove_manager";
+    const SUCCESS_MESSAGE: &'static str = "Remove manager from blockchain successfully.";
+    const FAILURE_MESSAGE: &'static str =
+        "Failed to remove manager from blockchain. Please try again or contact with admin";
+    const SUCCESS_KIND: NotificationKind = NotificationKind::ManagerRemoved;
+    const FAILURE_KIND: NotificationKind = NotificationKind::ManagerRemovalFailed;
+
+    fn describe(&self, message: &RemoveManagerMessage) -> String {
+        format!("address: {}", message.manager_address)
+    }
+
+    fn affected_emails<'a>(&self, message: &'a RemoveManagerMessage) -> Vec<&'a str> {
+        vec![&message.email]
+    }
+
+    fn requested_by<'a>(&self, message: &'a RemoveManagerMessage) -> &'a str {
+        &message.creator_user_id
+    }
+
+    fn notification_fields(&self, message: &RemoveManagerMessage) -> Map<String, Value> {
+        email_field(&message.email)
+    }
+
+    async fn handle(&self, message: &RemoveManagerMessage) -> Result<()> {
+        let blockchain = BlockchainService::new().await?;
+        if !blockchain.is_manager(&message.manager_address).await? {
+            tracing::info!("{} is not a manager anymore", message.manager_address);
+            return Ok(());
+        }
+
+        blockchain.remove_manager(&message.manager_address).await?;
+        Ok(())
+    }
+}
+
+pub struct DeactivateStudentHandler;
+
+#[async_trait]
+impl JobHandler<DeactivateStudentMessage> for DeactivateStudentHandler {
+    const QUEUE: &'static str = DEACTIVATE_STUDENT_CHANNEL;
+    const CONSUMER_TAG: &'static str = "deactivate_student";
+    const SUCCESS_MESSAGE: &'static str = "Deactivate student on blockchain successfully.";
+    const FAILURE_MESSAGE: &'static str =
+        "Failed to deactivate student on blockchain. Please try again or contact with admin";
+    const SUCCESS_KIND: NotificationKind = NotificationKind::StudentDeactivated;
+    const FAILURE_KIND: NotificationKind = NotificationKind::StudentDeactivationFailed;
+
+    fn describe(&self, message: &DeactivateStudentMessage) -> String {
+        format!("student_id: {}", message.student_id)
+    }
+
+    fn affected_emails<'a>(&self, message: &'a DeactivateStudentMessage) -> Vec<&'a str> {
+        vec![&message.email]
+    }
+
+    fn requested_by<'a>(&self, message: &'a DeactivateStudentMessage) -> &'a str {
+        &message.creator_user_id
+    }
+
+    fn notification_fields(&self, message: &DeactivateStudentMessage) -> Map<String, Value> {
+        email_field(&message.email)
+    }
+
+    async fn handle(&self, message: &DeactivateStudentMessage) -> Result<()> {
+        let blockchain = BlockchainService::new().await?;
+        if !blockchain.get_student(message.student_id).await?.is_active {
+            tracing::info!("Student {} is already inactive", message.student_id);
+            return Ok(());
+        }
+
+        blockchain.deactivate_student(message.student_id).await?;
+        Ok(())
+    }
+}
+
+pub struct ActivateStudentHandler;
+
+#[async_trait]
+impl JobHandler<ActivateStudentMessage> for ActivateStudentHandler {
+    const QUEUE: &'static str = ACTIVATE_STUDENT_CHANNEL;
+    const CONSUMER_TAG: &'static str = "activate_student";
+    const SUCCESS_MESSAGE: &'static str = "Activate student on blockchain successfully.";
+    const FAILURE_MESSAGE: &'static str =
+        "Failed to activate student on blockchain. Please try again or contact with admin";
+    const SUCCESS_KIND: NotificationKind = NotificationKind::StudentActivated;
+    const FAILURE_KIND: NotificationKind = NotificationKind::StudentActivationFailed;
+
+    fn describe(&self, message: &ActivateStudentMessage) -> String {
+        format!("student_id: {}", message.student_id)
+    }
+
+    fn affected_emails<'a>(&self, message: &'a ActivateStudentMessage) -> Vec<&'a str> {
+        vec![&message.email]
+    }
+
+    fn requested_by<'a>(&self, message: &'a ActivateStudentMessage) -> &'a str {
+        &message.creator_user_id
+    }
+
+    fn notification_fields(&self, message: &ActivateStudentMessage) -> Map<String, Value> {
+        email_field(&message.email)
+    }
+
+    async fn handle(&self, message: &ActivateStudentMessage) -> Result<()> {
+        let blockchain = BlockchainService::new().await?;
+        if blockchain.get_student(message.student_id).await?.is_active {
+            tracing::info!("Student {} is already active", message.student_id);
+            return Ok(());
+        }
+
+        blockchain.activate_student(message.student_id).await?;
+        Ok(())
+    }
+}
+
+pub struct RegisterStudentsBatchHandler;
+
+#[async_trait]
+impl JobHandler<RegisterStudentsBatchMessage> for RegisterStudentsBatchHandler {
+    const QUEUE: &'static str = REGISTER_STUDENTS_BATCH_CHANNEL;
+    const CONSUMER_TAG: &'static str = "register_students_batch";
+    const SUCCESS_MESSAGE: &'static str = "Batch register students on blockchain successfully.";
+    const FAILURE_MESSAGE: &'static str =
+        "Failed to register students batch on blockchain. Please try again or contact with admin";
+    const SUCCESS_KIND: NotificationKind = NotificationKind::StudentsBatchRegistered;
+    const FAILURE_KIND: NotificationKind = NotificationKind::StudentsBatchRegistrationFailed;
+
+    fn describe(&self, message: &RegisterStudentsBatchMessage) -> String {
+        format!("{} students", message.wallet_addresses.len())
+    }
+
+    fn affected_emails<'a>(&self, message: &'a RegisterStudentsBatchMessage) -> Vec<&'a str> {
+        message.emails.iter().map(String::as_str).collect()
+    }
+
+    fn requested_by<'a>(&self, message: &'a RegisterStudentsBatchMessage) -> &'a str {
+        &message.creator_user_id
+    }
+
+    fn notification_fields(&self, message: &RegisterStudentsBatchMessage) -> Map<String, Value> {
+        // Every student of a failed batch is `Failed`, so the list tells who to register again
+        Map::from_iter([
+            ("total_students".to_string(), json!(message.emails.len())),
+            ("emails".to_string(), json!(message.emails)),
+        ])
+    }
+
+    async fn handle(&self, message: &RegisterStudentsBatchMessage) -> Result<()> {
+        let count = message.wallet_addresses.len();
+        if message.student_codes.len() != count
+            || message.full_names.len() != count
+            || message.emails.len() != count
+        {
+            anyhow::bail!("Batch columns have different lengths");
+        }
+
+        let blockchain = BlockchainService::new().await?;
+
+        // Leave out students registered by an earlier attempt, the contract rejects the
+        // whole batch otherwise
+        let mut wallet_addresses = Vec::new();
+        let mut student_codes = Vec::new();
+        let mut full_names = Vec::new();
+        let mut emails = Vec::new();
+        let students = message
+            .wallet_addresses
+            .iter()
+            .zip(&message.student_codes)
+            .zip(&message.full_names)
+            .zip(&message.emails);
+        for (((wallet_address, student_code), full_name), email) in students {
+            if is_registered(&blockchain, wallet_address).await? {
+                continue;
+            }
+            wallet_addresses.push(wallet_address.clone());
+            student_codes.push(student_code.clone());
+            full_names.push(full_name.clone());
+            emails.push(email.clone());
+        }
+
+        if wallet_addresses.is_empty() {
+            tracing::info!("All students of the batch are already registered on blockchain");
+            return Ok(());
+        }
+        if wallet_addresses.len() < count {
+            tracing::info!(
+                "Registering {} of {} students, the rest are already registered",
+                wallet_addresses.len(),
+                count
+            );
+        }
+
+        blockchain
+            .register_students_batch(wallet_addresses, student_codes, full_names, emails)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,854 @@
+//! Generic consumer loop for blockchain jobs.
+//!
+//! A [`JobHandler`] only says what a job does and who to tell about it. [`JobRunner`] owns
+//! the rest: prefetch and concurrency, decoding, skipping requests that already succeeded,
+//! user status updates, the stored and pushed success/failure notification,
+//! ack/retry/dead-letter through [`RetryingQueue`] and per-queue metrics.
+//!
+//! Settling deliveries and storing outcomes go through [`JobQueue`] and [`JobStore`], so
+//! the processing of one delivery can be exercised without RabbitMQ or the database.
+
+use crate::entities::sea_orm_active_enums::{NotificationKind, UserStatus};
+use crate::rabbitmq_service::connection::rabbitmq;
+use crate::rabbitmq_service::retry::RetryingQueue;
+use crate::rabbitmq_service::structs::{VersionedMessage, decode_message};
+use crate::redis_service::redis_emitter::RedisEmitter;
+use crate::redis_service::socket_events::{JobOutcomePayload, SocketEvent, user_room};
+use crate::repositories::processed_message_repository::{OUTCOME_FAILED, OUTCOME_SUCCEEDED};
+use crate::repositories::{NotificationRepository, ProcessedMessageRepository, UserRepository};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::StreamExt;
+use lapin::message::Delivery;
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use uuid::Uuid;
+
+/// How often each runner logs its counters
+const METRICS_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+#[async_trait]
+pub trait JobHandler<M>: Send + Sync + 'static
+where
+    M: VersionedMessage + Send + Sync + 'static,
+{
+    const QUEUE: &'static str;
+    const CONSUMER_TAG: &'static str;
+    /// Jobs processed at once. Blockchain jobs share the admin signer, so more than one
+    /// in flight risks nonce clashes.
+    const CONCURRENCY: usize = 1;
+    /// Unacked deliveries held by the consumer, including those waiting for a slot
+    const PREFETCH: u16 = 10;
+    /// `message` of the success notification
+    const SUCCESS_MESSAGE: &'static str;
+    /// `message` of the failure notification
+    const FAILURE_MESSAGE: &'static str;
+    /// `kind` of the stored success notification
+    const SUCCESS_KIND: NotificationKind;
+    /// `kind` of the stored failure notification
+    const FAILURE_KIND: NotificationKind;
+
+    /// Short description of the job, for logs
+    fn describe(&self, message: &M) -> String;
+
+    /// Users whose status follows the job: `Sync` on success, `Failed` once it gives up
+    fn affected_emails<'a>(&self, message: &'a M) -> Vec<&'a str>;
+
+    /// User notified of the outcome
+    fn requested_by<'a>(&self, message: &'a M) -> &'a str;
+
+    /// Fields identifying the job in both notifications, stored as their `data`
+    fn notification_fields(&self, message: &M) -> Map<String, Value>;
+
+    /// Perform the job. Deliveries whose request already succeeded never get here, but a
+    /// request that failed after its transaction went through does, so handlers should treat
+    /// "already done" on-chain state as success.
+    async fn handle(&self, message: &M) -> Result<()>;
+
+    /// Runs once the statuses are set to `Sync` and the outcome is recorded, before the
+    /// notification
+    async fn on_success(&self, _message: &M) {}
+
+    /// Runs once the statuses are set to `Failed` and the outcome is recorded, before the
+    /// notification. Not called for attempts that will be retried.
+    async fn on_failure(&self, _message: &M, _error: &anyhow::Error) {}
+}
+
+/// Settles the deliveries of a job queue, [`RetryingQueue`] outside of tests
+#[async_trait]
+pub trait JobQueue: Send + Sync {
+    type Delivery: Send + Sync;
+
+    /// Whether a failure of `delivery` will be retried rather than dead-lettered
+    fn has_retries_left(&self, delivery: &Self::Delivery) -> bool;
+
+    async fn ack(&self, delivery: &Self::Delivery);
+
+    async fn retry(&self, delivery: &Self::Delivery, error: &anyhow::Error);
+
+    /// Bring the delivery back later without spending a retry
+    async fn postpone(&self, delivery: &Self::Delivery, error: &anyhow::Error);
+
+    async fn dead_letter(&self, delivery: &Self::Delivery, error: &anyhow::Error);
+}
+
+#[async_trait]
+impl JobQueue for RetryingQueue {
+    type Delivery = Delivery;
+
+    fn has_retries_left(&self, delivery: &Delivery) -> bool {
+        RetryingQueue::has_retries_left(self, delivery)
+    }
+
+    async fn ack(&self, delivery: &Delivery) {
+        RetryingQueue::ack(self, delivery).await
+    }
+
+    async fn retry(&self, delivery: &Delivery, error: &anyhow::Error) {
+        RetryingQueue::retry(self, delivery, error).await
+    }
+
+    async fn postpone(&self, delivery: &Delivery, error: &anyhow::Error) {
+        RetryingQueue::postpone(self, delivery, error).await
+    }
+
+    async fn dead_letter(&self, delivery: &Delivery, error: &anyhow::Error) {
+        RetryingQueue::dead_letter(self, delivery, error).await
+    }
+}
+
+/// Where jobs keep their outcomes and user statuses and send their notifications,
+/// [`DatabaseJobStore`] outside of tests
+#[async_trait]
+pub trait JobStore: Send + Sync {
+    /// Whether the request behind `key` already succeeded
+    async fn has_succeeded(&self, key: &str) -> Result<bool>;
+
+    async fn set_status(&self, email: &str, status: UserStatus) -> Result<()>;
+
+    async fn record_outcome(
+        &self,
+        queue: &str,
+        key: &str,
+        outcome: &'static str,
+        error: Option<String>,
+    ) -> Result<()>;
+
+    /// Best effort, failures are only logged
+    async fn notify(
+        &self,
+        requested_by: &str,
+        kind: NotificationKind,
+        succeeded: bool,
+        text: &str,
+        data: Map<String, Value>,
+    );
+}
+
+/// Outcomes in `processed_message`, statuses on the users, notifications stored and pushed
+/// through [`notify_user`]
+pub struct DatabaseJobStore;
+
+#[async_trait]
+impl JobStore for DatabaseJobStore {
+    async fn has_succeeded(&self, key: &str) -> Result<bool> {
+        ProcessedMessageRepository::new().has_succeeded(key).await
+    }
+
+    async fn set_status(&self, email: &str, status: UserStatus) -> Result<()> {
+        UserRepository::new()
+            .update_status_by_email(email, status)
+            .await?;
+        Ok(())
+    }
+
+    async fn record_outcome(
+        &self,
+        queue: &str,
+        key: &str,
+        outcome: &'static str,
+        error: Option<String>,
+    ) -> Result<()> {
+        ProcessedMessageRepository::new()
+            .record(key, queue, outcome, error)
+            .await
+    }
+
+    async fn notify(
+        &self,
+        requested_by: &str,
+        kind: NotificationKind,
+        succeeded: bool,
+        text: &str,
+        data: Map<String, Value>,
+    ) {
+        notify_user(requested_by, kind, succeeded, text, data).await;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct JobMetrics {
+    succeeded: AtomicU64,
+    retried: AtomicU64,
+    failed: AtomicU64,
+    /// Messages that could not be decoded
+    rejected: AtomicU64,
+    /// Deliveries of requests that had already succeeded
+    duplicates: AtomicU64,
+    busy_ms: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobMetricsSnapshot {
+    pub succeeded: u64,
+    pub retried: u64,
+    pub failed: u64,
+    pub rejected: u64,
+    pub duplicates: u64,
+    /// Time spent in handlers, retried attempts included
+    pub busy_ms: u64,
+}
+
+impl JobMetrics {
+    fn record(&self, counter: &AtomicU64, started: Instant) {
+        counter.fetch_add(1, Ordering::Relaxed);
+        self.busy_ms
+            .fetch_add(started.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> JobMetricsSnapshot {
+        JobMetricsSnapshot {
+            succeeded: self.succeeded.load(Ordering::Relaxed),
+            retried: self.retried.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            duplicates: self.duplicates.load(Ordering::Relaxed),
+            busy_ms: self.busy_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+pub struct JobRunner<H> {
+    handler: Arc<H>,
+    metrics: Arc<JobMetrics>,
+}
+
+impl<H> JobRunner<H> {
+    pub fn new(handler: H) -> Self {
+        Self {
+            handler: Arc::new(handler),
+            metrics: Arc::new(JobMetrics::default()),
+        }
+    }
+
+    pub fn metrics(&self) -> Arc<JobMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Consume the handler's queue until the channel closes. Jobs in flight keep running;
+    /// their deliveries are redelivered if the channel died before they were settled.
+    pub async fn run<M>(&self) -> Result<()>
+    where
+        H: JobHandler<M>,
+        M: VersionedMessage + Send + Sync + 'static,
+    {
+        tracing::info!("Starting consumer for queue: {}", H::QUEUE);
+
+        let channel = rabbitmq().create_channel().await?;
+        let (queue, mut consumer) =
+            RetryingQueue::consume(channel, H::QUEUE, H::CONSUMER_TAG, H::PREFETCH).await?;
+        let slots = Arc::new(Semaphore::new(H::CONCURRENCY.max(1)));
+        let metrics_logger = tokio::spawn(Self::log_metrics(H::QUEUE, self.metrics.clone()));
+
+        tracing::info!("Consumer for {} started, waiting for messages...", H::QUEUE);
+
+        while let Some(delivery) = consumer.next().await {
+            let delivery = match delivery {
+                Ok(delivery) => delivery,
+                Err(e) => {
+                    tracing::error!("Failed to receive message from {}: {:?}", H::QUEUE, e);
+                    continue;
+                }
+            };
+
+            let message = match decode_message::<M>(&delivery.data) {
+                Ok(message) => message,
+                Err(e) => {
+                    tracing::error!("Failed to decode message from {}: {}", H::QUEUE, e);
+                    self.metrics.rejected.fetch_add(1, Ordering::Relaxed);
+                    queue.dead_letter(&delivery, &e).await;
+                    continue;
+                }
+            };
+
+            let slot = slots
+                .clone()
+                .acquire_owned()
+                .await
+                .context("Job slots closed")?;
+            let key = idempotency_key(&message, &delivery.data);
+            let handler = self.handler.clone();
+            let metrics = self.metrics.clone();
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                Self::process(
+                    &*handler,
+                    &queue,
+                    &DatabaseJobStore,
+                    &metrics,
+                    delivery,
+                    &key,
+                    message,
+                )
+                .await;
+                drop(slot);
+            });
+        }
+
+        metrics_logger.abort();
+        tracing::warn!("Consumer for {} stopped", H::QUEUE);
+        Ok(())
+    }
+
+    /// Run one job and settle its delivery. `key` identifies the request, see
+    /// [`idempotency_key`].
+    async fn process<M, Q, S>(
+        handler: &H,
+        queue: &Q,
+        store: &S,
+        metrics: &JobMetrics,
+        delivery: Q::Delivery,
+        key: &str,
+        message: M,
+    ) where
+        H: JobHandler<M>,
+        M: VersionedMessage + Send + Sync + 'static,
+        Q: JobQueue,
+        S: JobStore,
+    {
+        let description = handler.describe(&message);
+
+        match store.has_succeeded(key).await {
+            Ok(true) => {
+                tracing::info!(
+                    "Skipping {} job that already succeeded: {}",
+                    H::QUEUE,
+                    description
+                );
+                metrics.duplicates.fetch_add(1, Ordering::Relaxed);
+                queue.ack(&delivery).await;
+                return;
+            }
+            Ok(false) => {}
+            // Handlers check the chain state themselves, so running the job again is safe
+            Err(e) => tracing::warn!("Failed to look up processed message {}: {}", key, e),
+        }
+
+        tracing::info!("Processing {} job: {}", H::QUEUE, description);
+
+        let started = Instant::now();
+        match handler.handle(&message).await {
+            Ok(()) => {
+                // Acked only once stored: a lost write is retried, the handler is idempotent
+                if let Err(e) =
+                    Self::persist_outcome(handler, store, &message, key, UserStatus::Sync, None)
+                        .await
+                {
+                    metrics.record(&metrics.retried, started);
+                    Self::redeliver(queue, &delivery, &e).await;
+                    return;
+                }
+                handler.on_success(&message).await;
+                Self::notify(
+                    handler,
+                    store,
+                    &message,
+                    true,
+                    H::SUCCESS_KIND,
+                    H::SUCCESS_MESSAGE,
+                    None,
+                )
+                .await;
+
+                metrics.record(&metrics.succeeded, started);
+                tracing::info!(
+                    "{} job succeeded in {:?}: {}",
+                    H::QUEUE,
+                    started.elapsed(),
+                    description
+                );
+                queue.ack(&delivery).await;
+            }
+            Err(e) if queue.has_retries_left(&delivery) => {
+                metrics.record(&metrics.retried, started);
+                queue.retry(&delivery, &e).await;
+            }
+            Err(e) => {
+                tracing::error!("{} job failed: {}: {}", H::QUEUE, description, e);
+                if let Err(persist_error) = Self::persist_outcome(
+                    handler,
+                    store,
+                    &message,
+                    key,
+                    UserStatus::Failed,
+                    Some(&e),
+                )
+                .await
+                {
+                    metrics.record(&metrics.retried, started);
+                    Self::redeliver(queue, &delivery, &persist_error).await;
+                    return;
+                }
+                handler.on_failure(&message, &e).await;
+                Self::notify(
+                    handler,
+                    store,
+                    &message,
+                    false,
+                    H::FAILURE_KIND,
+                    H::FAILURE_MESSAGE,
+                    Some(&e),
+                )
+                .await;
+
+                metrics.record(&metrics.failed, started);
+                queue.dead_letter(&delivery, &e).await;
+            }
+        }
+    }
+
+    /// Set the users' statuses and record the outcome of the request. `Sync` records a
+    /// success, anything else the failure `error`.
+    async fn persist_outcome<M, S>(
+        handler: &H,
+        store: &S,
+        message: &M,
+        key: &str,
+        status: UserStatus,
+        error: Option<&anyhow::Error>,
+    ) -> Result<()>
+    where
+        H: JobHandler<M>,
+        M: VersionedMessage + Send + Sync + 'static,
+        S: JobStore,
+    {
+        let outcome = match status {
+            UserStatus::Sync => OUTCOME_SUCCEEDED,
+            _ => OUTCOME_FAILED,
+        };
+        Self::set_statuses(handler, store, message, status).await?;
+        store
+            .record_outcome(H::QUEUE, key, outcome, error.map(ToString::to_string))
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to record {} outcome of {} job {}",
+                    outcome,
+                    H::QUEUE,
+                    key
+                )
+            })
+    }
+
+    /// Bring back a delivery whose outcome could not be stored. Final failures have no
+    /// retries left, so they are postponed instead of dead-lettered with the users `Pending`.
+    async fn redeliver<Q: JobQueue>(queue: &Q, delivery: &Q::Delivery, error: &anyhow::Error) {
+        tracing::error!("Failed to store job outcome: {:#}", error);
+        if queue.has_retries_left(delivery) {
+            queue.retry(delivery, error).await;
+        } else {
+            queue.postpone(delivery, error).await;
+        }
+    }
+
+    async fn set_statuses<M, S>(
+        handler: &H,
+        store: &S,
+        message: &M,
+        status: UserStatus,
+    ) -> Result<()>
+    where
+        H: JobHandler<M>,
+        M: VersionedMessage + Send + Sync + 'static,
+        S: JobStore,
+    {
+        for email in handler.affected_emails(message) {
+            store
+                .set_status(email, status.clone())
+                .await
+                .with_context(|| {
+                    format!("Failed to update user status to {:?} for {}", status, email)
+                })?;
+        }
+        Ok(())
+    }
+
+    async fn notify<M, S>(
+        handler: &H,
+        store: &S,
+        message: &M,
+        succeeded: bool,
+        kind: NotificationKind,
+        text: &str,
+        error: Option<&anyhow::Error>,
+    ) where
+        H: JobHandler<M>,
+        M: VersionedMessage + Send + Sync + 'static,
+        S: JobStore,
+    {
+        let mut data = handler.notification_fields(message);
+        if let Some(error) = error {
+            data.insert("reason".to_string(), json!(error.to_string()));
+        }
+        store
+            .notify(handler.requested_by(message), kind, succeeded, text, data)
+            .await;
+    }
+
+    async fn log_metrics(queue: &'static str, metrics: Arc<JobMetrics>) {
+        let mut interval = tokio::time::interval(METRICS_LOG_INTERVAL);
+        let mut last = metrics.snapshot();
+        loop {
+            interval.tick().await;
+            let current = metrics.snapshot();
+            if current != last {
+                tracing::info!(
+                    "{} jobs: {} succeeded, {} retried, {} failed, {} rejected, {} duplicates, {} ms busy",
+                    queue,
+                    current.succeeded,
+                    current.retried,
+                    current.failed,
+                    current.rejected,
+                    current.duplicates,
+                    current.busy_ms
+                );
+                last = current;
+            }
+        }
+    }
+}
+
+/// Store the outcome of a job for the user who asked for it, so it survives them being
+/// offline, then push it to their room. Both are best effort and only logged.
+pub async fn notify_user(
+    requested_by: &str,
+    kind: NotificationKind,
+    succeeded: bool,
+    text: &str,
+    data: Map<String, Value>,
+) {
+    let notification_id = match Uuid::parse_str(requested_by) {
+        Ok(user_id) => match NotificationRepository::new()
+            .create(user_id, kind, text.to_string(), Value::Object(data.clone()))
+            .await
+        {
+            Ok(stored) => Some(stored.notification_id),
+            Err(e) => {
+                tracing::error!(
+                    "Failed to store {:?} notification for {}: {}",
+                    kind,
+                    requested_by,
+                    e
+                );
+                None
+            }
+        },
+        Err(e) => {
+            tracing::warn!(
+                "Not storing {:?} notification for invalid user id {}: {}",
+                kind,
+                requested_by,
+                e
+            );
+            None
+        }
+    };
+
+    let event = SocketEvent::JobOutcome(
+        kind,
+        JobOutcomePayload {
+            notification_id,
+            succeeded,
+            message: text.to_string(),
+            data,
+        },
+    );
+    RedisEmitter::emit_to_room(&user_room(requested_by), &event).await;
+}
+
+/// Key of the request behind a delivery. Messages published before keys existed fall back
+/// to a hash of their body, which redeliveries and replays keep.
+fn idempotency_key<M: VersionedMessage>(message: &M, data: &[u8]) -> String {
+    match message.idempotency_key() {
+        Some(key) => key.to_string(),
+        None => format!("sha256:{}", hex::encode(Sha256::digest(data))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use serde::Deserialize;
+    use std::sync::Mutex;
+
+    #[derive(Deserialize)]
+    struct StubMessage {
+        email: String,
+        requested_by: String,
+    }
+
+    impl VersionedMessage for StubMessage {
+        fn schema_version(&self) -> u16 {
+            2
+        }
+
+        fn idempotency_key(&self) -> Option<&str> {
+            None
+        }
+    }
+
+    fn message() -> StubMessage {
+        StubMessage {
+            email: "student@example.com".to_string(),
+            requested_by: Uuid::nil().to_string(),
+        }
+    }
+
+    /// Succeeds, or fails with `error`
+    struct StubHandler {
+        error: Option<&'static str>,
+        failures: Mutex<Vec<String>>,
+    }
+
+    impl StubHandler {
+        fn new(error: Option<&'static str>) -> Self {
+            Self {
+                error,
+                failures: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl JobHandler<StubMessage> for StubHandler {
+        const QUEUE: &'static str = "stub";
+        const CONSUMER_TAG: &'static str = "stub_consumer";
+        const SUCCESS_MESSAGE: &'static str = "Done";
+        const FAILURE_MESSAGE: &'static str = "Failed";
+        const SUCCESS_KIND: NotificationKind = NotificationKind::StudentRegistered;
+        const FAILURE_KIND: NotificationKind = NotificationKind::StudentRegistrationFailed;
+
+        fn describe(&self, message: &StubMessage) -> String {
+            message.email.clone()
+        }
+
+        fn affected_emails<'a>(&self, message: &'a StubMessage) -> Vec<&'a str> {
+            vec![&message.email]
+        }
+
+        fn requested_by<'a>(&self, message: &'a StubMessage) -> &'a str {
+            &message.requested_by
+        }
+
+        fn notification_fields(&self, message: &StubMessage) -> Map<String, Value> {
+            Map::from_iter([("email".to_string(), json!(message.email))])
+        }
+
+        async fn handle(&self, _message: &StubMessage) -> Result<()> {
+            match self.error {
+                Some(error) => Err(anyhow!(error)),
+                None => Ok(()),
+            }
+        }
+
+        async fn on_failure(&self, _message: &StubMessage, error: &anyhow::Error) {
+            self.failures.lock().unwrap().push(error.to_string());
+        }
+    }
+
+    /// Records how each delivery was settled
+    struct StubQueue {
+        retries_left: bool,
+        settled: Mutex<Vec<&'static str>>,
+    }
+
+    impl StubQueue {
+        fn new(retries_left: bool) -> Self {
+            Self {
+                retries_left,
+                settled: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn settled(&self) -> Vec<&'static str> {
+            self.settled.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl JobQueue for StubQueue {
+        type Delivery = ();
+
+        fn has_retries_left(&self, _delivery: &()) -> bool {
+            self.retries_left
+        }
+
+        async fn ack(&self, _delivery: &()) {
+            self.settled.lock().unwrap().push("ack");
+        }
+
+        async fn retry(&self, _delivery: &(), _error: &anyhow::Error) {
+            self.settled.lock().unwrap().push("retry");
+        }
+
+        async fn postpone(&self, _delivery: &(), _error: &anyhow::Error) {
+            self.settled.lock().unwrap().push("postpone");
+        }
+
+        async fn dead_letter(&self, _delivery: &(), _error: &anyhow::Error) {
+            self.settled.lock().unwrap().push("dead_letter");
+        }
+    }
+
+    #[derive(Default)]
+    struct StubStore {
+        already_succeeded: bool,
+        statuses: Mutex<Vec<(String, UserStatus)>>,
+        outcomes: Mutex<Vec<&'static str>>,
+        notifications: Mutex<Vec<(NotificationKind, bool)>>,
+    }
+
+    #[async_trait]
+    impl JobStore for StubStore {
+        async fn has_succeeded(&self, _key: &str) -> Result<bool> {
+            Ok(self.already_succeeded)
+        }
+
+        async fn set_status(&self, email: &str, status: UserStatus) -> Result<()> {
+            self.statuses
+                .lock()
+                .unwrap()
+                .push((email.to_string(), status));
+            Ok(())
+        }
+
+        async fn record_outcome(
+            &self,
+            _queue: &str,
+            _key: &str,
+            outcome: &'static str,
+            _error: Option<String>,
+        ) -> Result<()> {
+            self.outcomes.lock().unwrap().push(outcome);
+            Ok(())
+        }
+
+        async fn notify(
+            &self,
+            _requested_by: &str,
+            kind: NotificationKind,
+            succeeded: bool,
+            _text: &str,
+            _data: Map<String, Value>,
+        ) {
+            self.notifications.lock().unwrap().push((kind, succeeded));
+        }
+    }
+
+    async fn process(
+        handler: &StubHandler,
+        queue: &StubQueue,
+        store: &StubStore,
+    ) -> JobMetricsSnapshot {
+        let metrics = JobMetrics::default();
+        JobRunner::<StubHandler>::process(handler, queue, store, &metrics, (), "key", message())
+            .await;
+        metrics.snapshot()
+    }
+
+    #[tokio::test]
+    async fn test_success_sets_statuses_and_acks() {
+        let handler = StubHandler::new(None);
+        let queue = StubQueue::new(true);
+        let store = StubStore::default();
+
+        let metrics = process(&handler, &queue, &store).await;
+
+        assert_eq!(
+            *store.statuses.lock().unwrap(),
+            vec![("student@example.com".to_string(), UserStatus::Sync)]
+        );
+        assert_eq!(*store.outcomes.lock().unwrap(), vec![OUTCOME_SUCCEEDED]);
+        assert_eq!(
+            *store.notifications.lock().unwrap(),
+            vec![(NotificationKind::StudentRegistered, true)]
+        );
+        assert_eq!(queue.settled(), vec!["ack"]);
+        assert_eq!(metrics.succeeded, 1);
+    }
+
+    #[tokio::test]
+    async fn test_retryable_error_is_retried() {
+        let handler = StubHandler::new(Some("node unavailable"));
+        let queue = StubQueue::new(true);
+        let store = StubStore::default();
+
+        let metrics = process(&handler, &queue, &store).await;
+
+        assert_eq!(queue.settled(), vec!["retry"]);
+        assert!(store.statuses.lock().unwrap().is_empty());
+        assert!(store.outcomes.lock().unwrap().is_empty());
+        assert!(store.notifications.lock().unwrap().is_empty());
+        assert!(handler.failures.lock().unwrap().is_empty());
+        assert_eq!(metrics.retried, 1);
+    }
+
+    #[tokio::test]
+    async fn test_final_failure_runs_on_failure_and_settles() {
+        let handler = StubHandler::new(Some("reverted"));
+        let queue = StubQueue::new(false);
+        let store = StubStore::default();
+
+        let metrics = process(&handler, &queue, &store).await;
+
+        assert_eq!(
+            *handler.failures.lock().unwrap(),
+            vec!["reverted".to_string()]
+        );
+        assert_eq!(
+            *store.statuses.lock().unwrap(),
+            vec![("student@example.com".to_string(), UserStatus::Failed)]
+        );
+        assert_eq!(*store.outcomes.lock().unwrap(), vec![OUTCOME_FAILED]);
+        assert_eq!(
+            *store.notifications.lock().unwrap(),
+            vec![(NotificationKind::StudentRegistrationFailed, false)]
+        );
+        // Dead-lettering acks the original once the copy is parked
+        assert_eq!(queue.settled(), vec!["dead_letter"]);
+        assert_eq!(metrics.failed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_already_processed_message_is_skipped() {
+        let handler = StubHandler::new(Some("must not run"));
+        let queue = StubQueue::new(true);
+        let store = StubStore {
+            already_succeeded: true,
+            ..StubStore::default()
+        };
+
+        let metrics = process(&handler, &queue, &store).await;
+
+        assert_eq!(queue.settled(), vec!["ack"]);
+        assert!(store.statuses.lock().unwrap().is_empty());
+        assert!(store.notifications.lock().unwrap().is_empty());
+        assert_eq!(metrics.duplicates, 1);
+        assert_eq!(metrics.retried + metrics.failed + metrics.succeeded, 0);
+    }
+}
@@ -1,3 +1,10 @@
+pub mod connection;
 pub mod consumers;
+pub mod dead_letters;
+pub mod handlers;
+pub mod job_runner;
+pub mod outbox;
 pub mod rabbitmq_service;
+pub mod retry;
 pub mod structs;
+pub mod topology;
@@ -0,0 +1,143 @@
+//! Relay for the transactional outbox.
+//!
+//! Handlers that change state and need a message to follow write it to `outbox_event` in
+//! the same database transaction (see [`OutboxRepository::enqueue`]), so a commit can no
+//! longer be followed by a lost publish. The relay publishes due events with publisher
+//! confirms and only marks them sent once the broker acked them: delivery is at least once,
+//! and a crash between the ack and the commit publishes the event again.
+
+use crate::rabbitmq_service::connection::rabbitmq;
+use crate::rabbitmq_service::rabbitmq_service::RabbitMQService;
+use crate::rabbitmq_service::topology::Priority;
+use crate::repositories::OutboxRepository;
+use anyhow::Result;
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use sea_orm::TransactionTrait;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Events published per transaction
+const BATCH_SIZE: u64 = 100;
+/// Fallback for events committed by another instance, which cannot wake this relay
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const INITIAL_RETRY_DELAY_SECS: i64 = 5;
+const MAX_RETRY_DELAY_SECS: i64 = 300;
+/// Sent events are kept this long for troubleshooting
+const SENT_RETENTION_DAYS: i64 = 7;
+const PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+static WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+
+pub struct OutboxRelay;
+
+impl OutboxRelay {
+    /// Have the relay look at the outbox now instead of at its next poll. Call after
+    /// committing a transaction that enqueued events.
+    pub fn wake() {
+        WAKE.notify_one();
+    }
+
+    /// Publish outbox events until the process stops
+    pub async fn run() {
+        tracing::info!("Outbox relay started");
+        let mut purge = tokio::time::interval(PURGE_INTERVAL);
+
+        loop {
+            match Self::relay_batch().await {
+                // A full batch means more events are probably waiting
+                Ok(count) if count as u64 == BATCH_SIZE => continue,
+                Ok(_) => {}
+                Err(e) => tracing::error!("Outbox relay failed: {:#}", e),
+            }
+
+            tokio::select! {
+                _ = WAKE.notified() => {}
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                _ = purge.tick() => Self::purge_sent().await,
+            }
+        }
+    }
+
+    /// Publish one batch of due events, returning how many were attempted
+    async fn relay_batch() -> Result<usize> {
+        // The supervisor already reports the outage; failing every event would only push
+        // their next attempts back
+        if !rabbitmq().is_connected() {
+            return Ok(0);
+        }
+
+        let outbox_repo = OutboxRepository::new();
+        let txn = outbox_repo.get_connection().begin().await?;
+        let events = outbox_repo.lock_due(&txn, BATCH_SIZE).await?;
+        if events.is_empty() {
+            txn.commit().await?;
+            return Ok(0);
+        }
+
+        let channel = RabbitMQService::confirm_channel().await?;
+
+        // Publish the whole batch before waiting, so confirms are not one round trip each
+        let mut confirms = Vec::with_capacity(events.len());
+        for event in &events {
+            let payload = event.payload.to_string();
+            confirms.push(
+                RabbitMQService::start_publish(
+                    &channel,
+                    &event.queue,
+                    Priority::from_value(event.priority),
+                    payload.as_bytes(),
+                )
+                .await,
+            );
+        }
+
+        let mut sent = 0;
+        for (event, confirm) in events.iter().zip(confirms) {
+            let result = match confirm {
+                Ok(confirm) => RabbitMQService::wait_confirm(confirm).await,
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(()) => {
+                    outbox_repo.mark_sent(&txn, event.event_id).await?;
+                    sent += 1;
+                }
+                Err(e) => {
+                    let delay = (INITIAL_RETRY_DELAY_SECS << event.attempts.clamp(0, 16))
+                        .min(MAX_RETRY_DELAY_SECS);
+                    tracing::warn!(
+                        "Failed to relay outbox event {} to {} (attempt {}), retrying in {}s: {:#}",
+                        event.event_id,
+                        event.queue,
+                        event.attempts + 1,
+                        delay,
+                        e
+                    );
+                    outbox_repo
+                        .mark_failed(
+                            &txn,
+                            event.event_id,
+                            &e.to_string(),
+                            Utc::now().naive_utc() + chrono::Duration::seconds(delay),
+                        )
+                        .await?;
+                }
+            }
+        }
+
+        txn.commit().await?;
+        tracing::debug!("Relayed {} of {} outbox events", sent, events.len());
+        Ok(events.len())
+    }
+
+    async fn purge_sent() {
+        let before = Utc::now().naive_utc() - chrono::Duration::days(SENT_RETENTION_DAYS);
+        match OutboxRepository::new().delete_sent_before(before).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Purged {} sent outbox events", count),
+            Err(e) => tracing::error!("Failed to purge sent outbox events: {}", e),
+        }
+    }
+}
@@ -1,320 +1,149 @@
-use crate::config::APP_CONFIG;
-use crate::rabbitmq_service::consumers::{
-    ACTIVATE_STUDENT_CHANNEL, ASSIGN_ROLE_CHANNEL, CREATE_USER_DB, DEACTIVATE_STUDENT_CHANNEL,
-    REGISTER_NEW_MANAGER_CHANNEL, REGISTER_NEW_USER_CHANNEL, REGISTER_STUDENTS_BATCH_CHANNEL,
-    REMOVE_MANAGER_CHANNEL,
-};
+use crate::email_service::template::RenderedEmail;
+use crate::rabbitmq_service::connection::rabbitmq;
 use crate::rabbitmq_service::structs::{
     ActivateStudentMessage, AssignRoleMessage, DeactivateStudentMessage, RegisterNewManagerMessage,
     RegisterNewUserMessage, RegisterStudentsBatchMessage, RemoveManagerMessage,
 };
+use crate::rabbitmq_service::topology::{
+    ACTIVATE_STUDENT_CHANNEL, ASSIGN_ROLE_CHANNEL, CREATE_USER_DB, DEACTIVATE_STUDENT_CHANNEL,
+    EXCHANGE, MAIL_QUEUE, PERSISTENT, Priority, REGISTER_NEW_MANAGER_CHANNEL,
+    REGISTER_NEW_USER_CHANNEL, REGISTER_STUDENTS_BATCH_CHANNEL, REMOVE_MANAGER_CHANNEL,
+};
 use crate::routes::users::dto::UserCsvColumn;
-use lapin::{BasicProperties, Connection, ConnectionProperties, options::*};
+use anyhow::{Context, bail};
+use lapin::publisher_confirm::{Confirmation, PublisherConfirm};
+use lapin::{BasicProperties, Channel, options::*};
 use serde_json::json;
 
+/// Publishers fail straight away while RabbitMQ is down, see [`rabbitmq`], and only return
+/// once the broker has confirmed the message. State changes that must not lose their
+/// message go through the outbox instead, see [`crate::rabbitmq_service::outbox`].
 pub struct RabbitMQService;
 
 impl RabbitMQService {
-    pub async fn new() -> Connection {
-        let connection =
-            Connection::connect(&APP_CONFIG.rabbitmq_uri, ConnectionProperties::default())
-                .await
-                .expect("Failed to connect to RabbitMQ");
-        connection
-    }
-
-    pub async fn create_mail_queue(connection: &Connection) -> Result<(), anyhow::Error> {
-        let channel = connection
-            .create_channel()
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create RabbitMQ channel: {}", e))?;
-
-        channel
-            .queue_declare(
-                "mail_service",
-                QueueDeclareOptions::default(),
-                Default::default(),
-            )
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create RabbitMQ queue: {}", e))?;
-
-        Ok(())
-    }
-
-    pub async fn create_register_new_user_channel(
-        connection: &Connection,
-    ) -> Result<(), anyhow::Error> {
-        let channel = connection
-            .create_channel()
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create RabbitMQ channel: {}", e))?;
-
+    /// Channel in confirm mode: each publish on it resolves once the broker has the message
+    pub async fn confirm_channel() -> Result<Channel, anyhow::Error> {
+        let channel = rabbitmq().create_channel().await?;
         channel
-            .queue_declare(
-                REGISTER_NEW_USER_CHANNEL,
-                QueueDeclareOptions::default(),
-                Default::default(),
-            )
+            .confirm_select(ConfirmSelectOptions::default())
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to create RabbitMQ queue: {}", e))?;
-
-        Ok(())
+            .context("Failed to enable publisher confirms")?;
+        Ok(channel)
     }
 
-    pub async fn create_user_db_channel(connection: &Connection) -> Result<(), anyhow::Error> {
-        let channel = connection
-            .create_channel()
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create RabbitMQ channel: {}", e))?;
-
-        channel
-            .queue_declare(
-                CREATE_USER_DB,
-                QueueDeclareOptions::default(),
-                Default::default(),
+    /// Publish a persistent message for `queue` through [`EXCHANGE`] without waiting for the
+    /// broker. The message is mandatory, so a missing queue comes back instead of being
+    /// dropped.
+    pub async fn start_publish(
+        channel: &Channel,
+        queue: &str,
+        priority: Priority,
+        payload: &[u8],
+    ) -> Result<PublisherConfirm, anyhow::Error> {
+        let properties = BasicProperties::default()
+            .with_delivery_mode(PERSISTENT)
+            .with_priority(priority.value());
+        let confirm = channel
+            .basic_publish(
+                EXCHANGE,
+                queue,
+                BasicPublishOptions {
+                    mandatory: true,
+                    ..Default::default()
+                },
+                payload,
+                properties,
             )
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create RabbitMQ queue: {}", e))?;
-
-        Ok(())
+            .await?;
+        Ok(confirm)
     }
 
-    pub async fn create_register_new_manager_channel(
-        connection: &Connection,
-    ) -> Result<(), anyhow::Error> {
-        let channel = connection
-            .create_channel()
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create RabbitMQ channel: {}", e))?;
-
-        channel
-            .queue_declare(
-                REGISTER_NEW_MANAGER_CHANNEL,
-                QueueDeclareOptions::default(),
-                Default::default(),
-            )
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create RabbitMQ queue: {}", e))?;
-
-        Ok(())
+    /// Wait until the broker has taken responsibility for a message from [`Self::start_publish`]
+    pub async fn wait_confirm(confirm: PublisherConfirm) -> Result<(), anyhow::Error> {
+        match confirm.await? {
+            Confirmation::Ack(None) | Confirmation::NotRequested => Ok(()),
+            Confirmation::Ack(Some(_)) => bail!("Message was unroutable"),
+            Confirmation::Nack(_) => bail!("Message was rejected by the broker"),
+        }
     }
 
-    pub async fn create_all_blockchain_queues(
-        connection: &Connection,
-    ) -> Result<(), anyhow::Error> {
-        let channel = connection
-            .create_channel()
+    async fn publish(queue: &str, payload: &[u8]) -> Result<(), anyhow::Error> {
+        let channel = Self::confirm_channel().await?;
+        let confirm = Self::start_publish(&channel, queue, Priority::Normal, payload).await?;
+        Self::wait_confirm(confirm)
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to create RabbitMQ channel: {}", e))?;
-
-        let queues = [
-            ASSIGN_ROLE_CHANNEL,
-            REMOVE_MANAGER_CHANNEL,
-            DEACTIVATE_STUDENT_CHANNEL,
-            ACTIVATE_STUDENT_CHANNEL,
-            REGISTER_STUDENTS_BATCH_CHANNEL,
-        ];
-
-        for queue in queues.iter() {
-            channel
-                .queue_declare(queue, QueueDeclareOptions::default(), Default::default())
-                .await
-                .map_err(|e| anyhow::anyhow!("Failed to create RabbitMQ queue {}: {}", queue, e))?;
-        }
-
-        Ok(())
+            .with_context(|| format!("Failed to publish to {}", queue))
     }
 
     pub async fn publish_to_mail_queue(
-        connection: &Connection,
         to: &str,
-        subject: &str,
-        email_data: &str,
+        email: &RenderedEmail,
     ) -> Result<(), anyhow::Error> {
         let standard_msg = json!({
             "pattern": "send-email",
             "data": {
                 "to": to,
-                "subject": subject,
-                "text": email_data
+                "subject": email.subject,
+                "text": email.text,
+                "html": email.html
             }
         });
 
-        let channel = connection.create_channel().await?;
-
-        channel
-            .basic_publish(
-                "",
-                "mail_service",
-                BasicPublishOptions::default(),
-                standard_msg.to_string().as_bytes(),
-                BasicProperties::default(),
-            )
-            .await?;
-
-        Ok(())
+        Self::publish(MAIL_QUEUE, standard_msg.to_string().as_bytes()).await
     }
 
     pub async fn publish_to_register_new_user(
-        connection: &Connection,
         message: RegisterNewUserMessage,
     ) -> Result<(), anyhow::Error> {
         let serialize_msg = serde_json::to_string(&message)?;
 
-        let channel = connection.create_channel().await?;
-
-        channel
-            .basic_publish(
-                "",
-                REGISTER_NEW_USER_CHANNEL,
-                BasicPublishOptions::default(),
-                serialize_msg.as_bytes(),
-                BasicProperties::default(),
-            )
-            .await?;
-
-        Ok(())
+        Self::publish(REGISTER_NEW_USER_CHANNEL, serialize_msg.as_bytes()).await
     }
 
     pub async fn publish_to_register_new_manager(
-        connection: &Connection,
         message: RegisterNewManagerMessage,
     ) -> Result<(), anyhow::Error> {
         let seriablize_msg = serde_json::to_string(&message)?;
 
-        let channel = connection.create_channel().await?;
-
-        channel
-            .basic_publish(
-                "",
-                REGISTER_NEW_MANAGER_CHANNEL,
-                BasicPublishOptions::default(),
-                seriablize_msg.as_bytes(),
-                BasicProperties::default(),
-            )
-            .await?;
-
-        Ok(())
+        Self::publish(REGISTER_NEW_MANAGER_CHANNEL, seriablize_msg.as_bytes()).await
     }
 
-    pub async fn publish_to_assign_role(
-        connection: &Connection,
-        message: AssignRoleMessage,
-    ) -> Result<(), anyhow::Error> {
+    pub async fn publish_to_assign_role(message: AssignRoleMessage) -> Result<(), anyhow::Error> {
         let serialize_msg = serde_json::to_string(&message)?;
-        let channel = connection.create_channel().await?;
-
-        channel
-            .basic_publish(
-                "",
-                ASSIGN_ROLE_CHANNEL,
-                BasicPublishOptions::default(),
-                serialize_msg.as_bytes(),
-                BasicProperties::default(),
-            )
-            .await?;
-
-        Ok(())
+        Self::publish(ASSIGN_ROLE_CHANNEL, serialize_msg.as_bytes()).await
     }
 
     pub async fn publish_to_remove_manager(
-        connection: &Connection,
         message: RemoveManagerMessage,
     ) -> Result<(), anyhow::Error> {
         let serialize_msg = serde_json::to_string(&message)?;
-        let channel = connection.create_channel().await?;
-
-        channel
-            .basic_publish(
-                "",
-                REMOVE_MANAGER_CHANNEL,
-                BasicPublishOptions::default(),
-                serialize_msg.as_bytes(),
-                BasicProperties::default(),
-            )
-            .await?;
-
-        Ok(())
+        Self::publish(REMOVE_MANAGER_CHANNEL, serialize_msg.as_bytes()).await
     }
 
     pub async fn publish_to_deactivate_student(
-        connection: &Connection,
         message: DeactivateStudentMessage,
     ) -> Result<(), anyhow::Error> {
         let serialize_msg = serde_json::to_string(&message)?;
-        let channel = connection.create_channel().await?;
-
-        channel
-            .basic_publish(
-                "",
-                DEACTIVATE_STUDENT_CHANNEL,
-                BasicPublishOptions::default(),
-                serialize_msg.as_bytes(),
-                BasicProperties::default(),
-            )
-            .await?;
-
-        Ok(())
+        Self::publish(DEACTIVATE_STUDENT_CHANNEL, serialize_msg.as_bytes()).await
     }
 
     pub async fn publish_to_activate_student(
-        connection: &Connection,
         message: ActivateStudentMessage,
     ) -> Result<(), anyhow::Error> {
         let serialize_msg = serde_json::to_string(&message)?;
-        let channel = connection.create_channel().await?;
-
-        channel
-            .basic_publish(
-                "",
-                ACTIVATE_STUDENT_CHANNEL,
-                BasicPublishOptions::default(),
-                serialize_msg.as_bytes(),
-                BasicProperties::default(),
-            )
-            .await?;
-
-        Ok(())
+        Self::publish(ACTIVATE_STUDENT_CHANNEL, serialize_msg.as_bytes()).await
     }
 
     pub async fn publish_to_register_students_batch(
-        connection: &Connection,
         message: RegisterStudentsBatchMessage,
     ) -> Result<(), anyhow::Error> {
         let serialize_msg = serde_json::to_string(&message)?;
-        let channel = connection.create_channel().await?;
-
-        channel
-            .basic_publish(
-                "",
-                REGISTER_STUDENTS_BATCH_CHANNEL,
-                BasicPublishOptions::default(),
-                serialize_msg.as_bytes(),
-                BasicProperties::default(),
-            )
-            .await?;
-
-        Ok(())
+        Self::publish(REGISTER_STUDENTS_BATCH_CHANNEL, serialize_msg.as_bytes()).await
     }
 
-    pub async fn publish_to_create_user_db(
-        connection: &Connection,
-        message: UserCsvColumn,
-    ) -> Result<(), anyhow::Error> {
+    pub async fn publish_to_create_user_db(message: UserCsvColumn) -> Result<(), anyhow::Error> {
         let serialize_msg = serde_json::to_string(&message)?;
 
-        let channel = connection.create_channel().await?;
-
-        channel
-            .basic_publish(
-                "",
-                CREATE_USER_DB,
-                BasicPublishOptions::default(),
-                serialize_msg.as_bytes(),
-                BasicProperties::default(),
-            )
-            .await?;
-
-        Ok(())
+        Self::publish(CREATE_USER_DB, serialize_msg.as_bytes()).await
     }
 }
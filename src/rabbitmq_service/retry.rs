@@ -0,0 +1,304 @@
+//! Retries and dead-lettering for the job queues.
+//!
+//! A delivery is acked only once its outcome is persisted. A failed job is republished to
+//! a delay queue `<queue>.retry.<delay_ms>`, whose TTL dead-letters it back into `<queue>`,
+//! with the delay doubling on every attempt. After `RABBITMQ_MAX_RETRIES` attempts, or
+//! straight away for messages that cannot be decoded, it goes to the `<queue>.dlx` exchange
+//! and waits in `<queue>.dead` until an admin replays or discards it.
+
+use crate::config::APP_CONFIG;
+use crate::rabbitmq_service::rabbitmq_service::RabbitMQService;
+use crate::rabbitmq_service::topology::PERSISTENT;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use lapin::message::Delivery;
+use lapin::options::{
+    BasicAckOptions, BasicConsumeOptions, BasicNackOptions, BasicPublishOptions, BasicQosOptions,
+    ConfirmSelectOptions,
+};
+use lapin::types::{AMQPValue, FieldTable};
+use lapin::{BasicProperties, Channel, Consumer};
+use std::fmt::Display;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Number of retries already done for a message
+pub const RETRY_COUNT_HEADER: &str = "x-retry-count";
+pub const LAST_ERROR_HEADER: &str = "x-last-error";
+pub const ORIGINAL_QUEUE_HEADER: &str = "x-original-queue";
+/// Unix timestamp of when the message was dead-lettered
+pub const DEAD_LETTERED_AT_HEADER: &str = "x-dead-lettered-at";
+
+pub fn dead_letter_exchange(queue: &str) -> String {
+    format!("{}.dlx", queue)
+}
+
+pub fn dead_letter_queue(queue: &str) -> String {
+    format!("{}.dead", queue)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config() -> Self {
+        Self {
+            max_retries: APP_CONFIG.rabbitmq_max_retries,
+            base_delay: Duration::from_millis(APP_CONFIG.rabbitmq_retry_base_delay_ms),
+        }
+    }
+
+    /// Delay before retry number `retry` (1-based): `base_delay * 2^(retry - 1)`
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+    }
+
+    /// The delay is part of the name, so changing the policy declares new queues instead
+    /// of clashing with the TTL of existing ones
+    pub fn delay_queue(&self, queue: &str, retry: u32) -> String {
+        format!("{}.retry.{}", queue, self.delay(retry).as_millis())
+    }
+}
+
+/// Retries already done for `delivery`, read from its headers
+pub fn retry_count(delivery: &Delivery) -> u32 {
+    delivery
+        .properties
+        .headers()
+        .as_ref()
+        .and_then(|headers| headers.inner().get(RETRY_COUNT_HEADER))
+        .and_then(amqp_u32)
+        .unwrap_or(0)
+}
+
+pub(crate) fn amqp_u32(value: &AMQPValue) -> Option<u32> {
+    match value {
+        AMQPValue::ShortShortUInt(n) => Some(u32::from(*n)),
+        AMQPValue::ShortUInt(n) => Some(u32::from(*n)),
+        AMQPValue::LongUInt(n) => Some(*n),
+        AMQPValue::ShortShortInt(n) => u32::try_from(*n).ok(),
+        AMQPValue::ShortInt(n) => u32::try_from(*n).ok(),
+        AMQPValue::LongInt(n) => u32::try_from(*n).ok(),
+        AMQPValue::LongLongInt(n) => u32::try_from(*n).ok(),
+        _ => None,
+    }
+}
+
+/// Consumer side of a job queue: settles each delivery with an ack, a delayed retry or a
+/// dead-letter
+#[derive(Clone)]
+pub struct RetryingQueue {
+    channel: Channel,
+    queue: &'static str,
+    policy: RetryPolicy,
+}
+
+impl RetryingQueue {
+    /// Start consuming `queue` with at most `prefetch` unacked deliveries. Its delay and
+    /// dead-letter queues are declared with the rest of the topology, see
+    /// [`crate::rabbitmq_service::topology`].
+    pub async fn consume(
+        channel: Channel,
+        queue: &'static str,
+        consumer_tag: &str,
+        prefetch: u16,
+    ) -> Result<(Self, Consumer)> {
+        let retrying = Self {
+            channel,
+            queue,
+            policy: RetryPolicy::from_config(),
+        };
+
+        retrying
+            .channel
+            .basic_qos(prefetch, BasicQosOptions::default())
+            .await
+            .context("Failed to set RabbitMQ prefetch")?;
+
+        // Retry and dead-letter copies must reach the broker before the original is acked
+        retrying
+            .channel
+            .confirm_select(ConfirmSelectOptions::default())
+            .await
+            .context("Failed to enable publisher confirms")?;
+
+        let consumer = retrying
+            .channel
+            .basic_consume(
+                queue,
+                consumer_tag,
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            )
+            .await
+            .context("Failed to start consuming from queue")?;
+
+        Ok((retrying, consumer))
+    }
+
+    /// Whether a failure of `delivery` will be retried rather than dead-lettered
+    pub fn has_retries_left(&self, delivery: &Delivery) -> bool {
+        retry_count(delivery) < self.policy.max_retries
+    }
+
+    /// The job is done and its outcome persisted
+    pub async fn ack(&self, delivery: &Delivery) {
+        if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+            tracing::error!("Failed to acknowledge message from {}: {}", self.queue, e);
+        }
+    }
+
+    /// Schedule another attempt, or dead-letter the message when no retries are left
+    pub async fn retry(&self, delivery: &Delivery, error: &dyn Display) {
+        if !self.has_retries_left(delivery) {
+            return self.dead_letter(delivery, error).await;
+        }
+
+        let retry = retry_count(delivery) + 1;
+        let delay_queue = self.policy.delay_queue(self.queue, retry);
+        let properties = self.properties(delivery, retry, error);
+
+        tracing::warn!(
+            "Job from {} failed, retry {}/{} in {:?}: {}",
+            self.queue,
+            retry,
+            self.policy.max_retries,
+            self.policy.delay(retry),
+            error
+        );
+        self.republish("", &delay_queue, delivery, properties).await;
+    }
+
+    /// Bring the message back after the longest retry delay without spending a retry, for
+    /// jobs that ran out of retries but whose outcome could not be stored
+    pub async fn postpone(&self, delivery: &Delivery, error: &dyn Display) {
+        let retry = retry_count(delivery);
+        let properties = self.properties(delivery, retry, error);
+
+        if self.policy.max_retries == 0 {
+            // No delay queue to wait in
+            tracing::warn!("Requeueing job from {}: {}", self.queue, error);
+            let requeue = BasicNackOptions {
+                requeue: true,
+                ..BasicNackOptions::default()
+            };
+            if let Err(e) = delivery.nack(requeue).await {
+                tracing::error!("Failed to requeue message from {}: {}", self.queue, e);
+            }
+            return;
+        }
+
+        let delay_queue = self.policy.delay_queue(self.queue, self.policy.max_retries);
+        tracing::warn!(
+            "Postponing job from {} by {:?}: {}",
+            self.queue,
+            self.policy.delay(self.policy.max_retries),
+            error
+        );
+        self.republish("", &delay_queue, delivery, properties).await;
+    }
+
+    /// Park the message in the dead-letter queue for an admin to replay or discard
+    pub async fn dead_letter(&self, delivery: &Delivery, error: &dyn Display) {
+        let mut properties = self.properties(delivery, retry_count(delivery), error);
+        if properties.message_id().is_none() {
+            properties = properties.with_message_id(Uuid::new_v4().to_string().into());
+        }
+
+        tracing::error!("Dead-lettering job from {}: {}", self.queue, error);
+        self.republish(&dead_letter_exchange(self.queue), "", delivery, properties)
+            .await;
+    }
+
+    fn properties(&self, delivery: &Delivery, retry: u32, error: &dyn Display) -> BasicProperties {
+        let mut headers = delivery.properties.headers().clone().unwrap_or_default();
+        headers.insert(RETRY_COUNT_HEADER.into(), AMQPValue::LongUInt(retry));
+        headers.insert(
+            LAST_ERROR_HEADER.into(),
+            AMQPValue::LongString(error.to_string().into()),
+        );
+        headers.insert(
+            ORIGINAL_QUEUE_HEADER.into(),
+            AMQPValue::LongString(self.queue.into()),
+        );
+        headers.insert(
+            DEAD_LETTERED_AT_HEADER.into(),
+            AMQPValue::LongLongInt(Utc::now().timestamp()),
+        );
+
+        delivery
+            .properties
+            .clone()
+            .with_headers(headers)
+            .with_delivery_mode(PERSISTENT)
+    }
+
+    /// Publish a copy and ack the original once the broker confirmed it. If the copy is not
+    /// confirmed the original is requeued, so the job is never lost.
+    async fn republish(
+        &self,
+        exchange: &str,
+        routing_key: &str,
+        delivery: &Delivery,
+        properties: BasicProperties,
+    ) {
+        let published = async {
+            let confirm = self
+                .channel
+                .basic_publish(
+                    exchange,
+                    routing_key,
+                    BasicPublishOptions::default(),
+                    &delivery.data,
+                    properties,
+                )
+                .await?;
+            RabbitMQService::wait_confirm(confirm).await
+        }
+        .await;
+
+        match published {
+            Ok(_) => self.ack(delivery).await,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to republish message from {}, requeueing it: {}",
+                    self.queue,
+                    e
+                );
+                let requeue = BasicNackOptions {
+                    requeue: true,
+                    ..BasicNackOptions::default()
+                };
+                if let Err(e) = delivery.nack(requeue).await {
+                    tracing::error!("Failed to requeue message from {}: {}", self.queue, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rabbitmq_service::topology::ASSIGN_ROLE_CHANNEL;
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        let policy = RetryPolicy {
+            max_retries: 4,
+            base_delay: Duration::from_secs(5),
+        };
+
+        assert_eq!(policy.delay(1), Duration::from_secs(5));
+        assert_eq!(policy.delay(2), Duration::from_secs(10));
+        assert_eq!(policy.delay(4), Duration::from_secs(40));
+        assert_eq!(
+            policy.delay_queue(ASSIGN_ROLE_CHANNEL, 3),
+            "auth.role.assign.retry.20000"
+        );
+    }
+}
@@ -1,8 +1,53 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use uuid::Uuid;
+
+/// Schema version written into every blockchain message.
+///
+/// v1 messages embedded a `private_key`; since v2 they only carry identifiers and the
+/// consumer resolves the signer through the key provider. Unknown fields are ignored on
+/// deserialization, so v1 messages still in the queues are drained without their key.
+pub const MESSAGE_SCHEMA_VERSION: u16 = 2;
+
+/// Messages published before `schema_version` existed
+pub const LEGACY_MESSAGE_SCHEMA_VERSION: u16 = 1;
+
+fn legacy_schema_version() -> u16 {
+    LEGACY_MESSAGE_SCHEMA_VERSION
+}
+
+pub trait VersionedMessage: DeserializeOwned {
+    fn schema_version(&self) -> u16;
+
+    /// Shared by every copy of one request, so consumers can tell a redelivery, replay or
+    /// duplicate publish from a new request. Absent on messages published before keys.
+    fn idempotency_key(&self) -> Option<&str>;
+}
+
+/// Key for a new request; build it once per request, never per publish attempt
+pub fn new_idempotency_key() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// Deserialize a queue payload and reject schema versions this consumer does not understand
+pub fn decode_message<T: VersionedMessage>(data: &[u8]) -> anyhow::Result<T> {
+    let message: T = serde_json::from_slice(data)?;
+
+    match message.schema_version() {
+        MESSAGE_SCHEMA_VERSION => Ok(message),
+        LEGACY_MESSAGE_SCHEMA_VERSION => {
+            tracing::warn!("Draining legacy v1 message, embedded private key is ignored");
+            Ok(message)
+        }
+        other => anyhow::bail!("Unsupported message schema version {}", other),
+    }
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RegisterNewUserMessage {
-    pub private_key: String,
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
     pub wallet_address: String,
     pub student_code: String,
     pub full_name: String,
@@ -14,7 +59,10 @@ pub struct RegisterNewUserMessage {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RegisterNewManagerMessage {
-    pub private_key: String,
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
     pub wallet_address: String,
     pub email: String,
     pub creator_user_id: String,
@@ -22,7 +70,10 @@ pub struct RegisterNewManagerMessage {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AssignRoleMessage {
-    pub private_key: String,
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
     pub user_address: String,
     pub role: u8,
     pub email: String,
@@ -31,7 +82,10 @@ pub struct AssignRoleMessage {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RemoveManagerMessage {
-    pub private_key: String,
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
     pub manager_address: String,
     pub email: String,
     pub creator_user_id: String,
@@ -39,7 +93,10 @@ pub struct RemoveManagerMessage {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct DeactivateStudentMessage {
-    pub private_key: String,
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
     pub student_id: u64,
     pub email: String,
     pub creator_user_id: String,
@@ -47,7 +104,10 @@ pub struct DeactivateStudentMessage {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ActivateStudentMessage {
-    pub private_key: String,
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
     pub student_id: u64,
     pub email: String,
     pub creator_user_id: String,
@@ -55,10 +115,116 @@ pub struct ActivateStudentMessage {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RegisterStudentsBatchMessage {
-    pub private_key: String,
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
     pub wallet_addresses: Vec<String>,
     pub student_codes: Vec<String>,
     pub full_names: Vec<String>,
     pub emails: Vec<String>,
     pub creator_user_id: String,
 }
+
+impl VersionedMessage for RegisterNewUserMessage {
+    fn schema_version(&self) -> u16 {
+        self.schema_version
+    }
+
+    fn idempotency_key(&self) -> Option<&str> {
+        self.idempotency_key.as_deref()
+    }
+}
+
+impl VersionedMessage for RegisterNewManagerMessage {
+    fn schema_version(&self) -> u16 {
+        self.schema_version
+    }
+
+    fn idempotency_key(&self) -> Option<&str> {
+        self.idempotency_key.as_deref()
+    }
+}
+
+impl VersionedMessage for AssignRoleMessage {
+    fn schema_version(&self) -> u16 {
+        self.schema_version
+    }
+
+    fn idempotency_key(&self) -> Option<&str> {
+        self.idempotency_key.as_deref()
+    }
+}
+
+impl VersionedMessage for RemoveManagerMessage {
+    fn schema_version(&self) -> u16 {
+        self.schema_version
+    }
+
+    fn idempotency_key(&self) -> Option<&str> {
+        self.idempotency_key.as_deref()
+    }
+}
+
+impl VersionedMessage for DeactivateStudentMessage {
+    fn schema_version(&self) -> u16 {
+        self.schema_version
+    }
+
+    fn idempotency_key(&self) -> Option<&str> {
+        self.idempotency_key.as_deref()
+    }
+}
+
+impl VersionedMessage for ActivateStudentMessage {
+    fn schema_version(&self) -> u16 {
+        self.schema_version
+    }
+
+    fn idempotency_key(&self) -> Option<&str> {
+        self.idempotency_key.as_deref()
+    }
+}
+
+impl VersionedMessage for RegisterStudentsBatchMessage {
+    fn schema_version(&self) -> u16 {
+        self.schema_version
+    }
+
+    fn idempotency_key(&self) -> Option<&str> {
+        self.idempotency_key.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_message_versions() {
+        let legacy =
+            r#"{"private_key":"0xdead","student_id":7,"email":"a@b.c","creator_user_id":"u1"}"#;
+        let message = decode_message::<ActivateStudentMessage>(legacy.as_bytes()).unwrap();
+        assert_eq!(message.schema_version, LEGACY_MESSAGE_SCHEMA_VERSION);
+        assert_eq!(message.student_id, 7);
+
+        assert_eq!(message.idempotency_key(), None);
+
+        let key = new_idempotency_key();
+        let current = serde_json::to_vec(&ActivateStudentMessage {
+            schema_version: MESSAGE_SCHEMA_VERSION,
+            idempotency_key: Some(key.clone()),
+            student_id: 7,
+            email: "a@b.c".to_string(),
+            creator_user_id: "u1".to_string(),
+        })
+        .unwrap();
+        assert!(!String::from_utf8_lossy(&current).contains("private_key"));
+        let decoded = decode_message::<ActivateStudentMessage>(&current).unwrap();
+        assert_eq!(decoded.idempotency_key(), Some(key.as_str()));
+
+        let future =
+            r#"{"schema_version":99,"student_id":7,"email":"a@b.c","creator_user_id":"u1"}"#;
+        assert!(decode_message::<ActivateStudentMessage>(future.as_bytes()).is_err());
+    }
+}
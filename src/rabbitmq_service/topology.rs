@@ -0,0 +1,324 @@
+//! The RabbitMQ topology of the service, declared from [`QUEUES`] by both binaries.
+//!
+//! Everything is published as a persistent message to the durable topic exchange
+//! [`EXCHANGE`], with the queue name as routing key, and every queue is bound to it under
+//! its own name. Queues are durable, except [`MAIL_QUEUE`] which belongs to the mail
+//! service. Other services can bind their own queues to patterns such as `auth.student.*`
+//! to follow these events.
+//!
+//! Job queues additionally get message priorities, a dead-letter exchange `<queue>.dlx`
+//! feeding `<queue>.dead`, and the delay queues of [`RetryPolicy`], see
+//! [`crate::rabbitmq_service::retry`].
+
+use crate::rabbitmq_service::retry::{RetryPolicy, dead_letter_exchange, dead_letter_queue};
+use anyhow::{Context, Result};
+use lapin::options::{ExchangeDeclareOptions, QueueBindOptions, QueueDeclareOptions};
+use lapin::types::{AMQPValue, FieldTable, ShortString};
+use lapin::{Channel, Connection, ExchangeKind};
+use std::time::Duration;
+
+pub const EXCHANGE: &str = "auth.events";
+
+/// Consumed by the mail service, which declares it too, non-durable: redeclaring it with
+/// other arguments fails with `PRECONDITION_FAILED`, so it keeps the ones it has
+pub const MAIL_QUEUE: &str = "mail_service";
+pub const CREATE_USER_DB: &str = "auth.user.create_db";
+pub const REGISTER_NEW_USER_CHANNEL: &str = "auth.student.register";
+pub const REGISTER_STUDENTS_BATCH_CHANNEL: &str = "auth.student.register_batch";
+pub const ACTIVATE_STUDENT_CHANNEL: &str = "auth.student.activate";
+pub const DEACTIVATE_STUDENT_CHANNEL: &str = "auth.student.deactivate";
+pub const REGISTER_NEW_MANAGER_CHANNEL: &str = "auth.manager.register";
+pub const REMOVE_MANAGER_CHANNEL: &str = "auth.manager.remove";
+pub const ASSIGN_ROLE_CHANNEL: &str = "auth.role.assign";
+
+/// AMQP delivery mode of messages written to disk by the broker
+pub const PERSISTENT: u8 = 2;
+
+/// Dead letters nobody replayed or discarded are dropped after this long
+const DEAD_LETTER_TTL: Duration = Duration::from_secs(30 * 24 * 3600);
+
+/// Order of messages within a queue declared with `prioritized`, highest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// Rows of bulk imports and activations
+    Low,
+    Normal,
+    /// Single actions someone is waiting on
+    High,
+}
+
+impl Priority {
+    /// The `x-max-priority` of prioritized queues. The broker keeps a sub-queue per level,
+    /// so this stays small.
+    pub const MAX: u8 = 9;
+
+    pub fn value(self) -> u8 {
+        match self {
+            Priority::Low => 1,
+            Priority::Normal => 5,
+            Priority::High => Self::MAX,
+        }
+    }
+
+    /// The closest priority to a stored value
+    pub fn from_value(value: i16) -> Self {
+        match value {
+            i16::MIN..=2 => Priority::Low,
+            3..=6 => Priority::Normal,
+            _ => Priority::High,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct QueueSpec {
+    pub name: &'static str,
+    /// Survives a broker restart. Only queues owned by another service are not.
+    pub durable: bool,
+    /// Deliver by [`Priority`] rather than strictly in publish order
+    pub prioritized: bool,
+    /// Failed jobs are retried through delay queues, then dead-lettered
+    pub retrying: bool,
+}
+
+impl QueueSpec {
+    /// Declared by another service, with that service's arguments
+    const fn external(name: &'static str) -> Self {
+        Self {
+            name,
+            durable: false,
+            prioritized: false,
+            retrying: false,
+        }
+    }
+
+    const fn plain(name: &'static str) -> Self {
+        Self {
+            name,
+            durable: true,
+            prioritized: false,
+            retrying: false,
+        }
+    }
+
+    const fn job(name: &'static str) -> Self {
+        Self {
+            name,
+            durable: true,
+            prioritized: true,
+            retrying: true,
+        }
+    }
+}
+
+/// Every queue this service publishes to or consumes from
+pub const QUEUES: &[QueueSpec] = &[
+    QueueSpec::external(MAIL_QUEUE),
+    QueueSpec::plain(CREATE_USER_DB),
+    QueueSpec::job(REGISTER_NEW_USER_CHANNEL),
+    QueueSpec::job(REGISTER_STUDENTS_BATCH_CHANNEL),
+    QueueSpec::job(ACTIVATE_STUDENT_CHANNEL),
+    QueueSpec::job(DEACTIVATE_STUDENT_CHANNEL),
+    QueueSpec::job(REGISTER_NEW_MANAGER_CHANNEL),
+    QueueSpec::job(REMOVE_MANAGER_CHANNEL),
+    QueueSpec::job(ASSIGN_ROLE_CHANNEL),
+];
+
+/// Names the queues had before the topology was declared from one place, with the queue
+/// that replaced each. Drained by the `migrate_rabbitmq_queues` binary.
+pub const LEGACY_QUEUES: &[(&str, &str)] = &[
+    ("create::user::db", CREATE_USER_DB),
+    ("create::new::user", REGISTER_NEW_USER_CHANNEL),
+    (
+        "blockchain::register::students::batch",
+        REGISTER_STUDENTS_BATCH_CHANNEL,
+    ),
+    ("blockchain::activate::student", ACTIVATE_STUDENT_CHANNEL),
+    (
+        "blockchain::deactivate::student",
+        DEACTIVATE_STUDENT_CHANNEL,
+    ),
+    ("create:new:manager", REGISTER_NEW_MANAGER_CHANNEL),
+    ("blockchain::remove::manager", REMOVE_MANAGER_CHANNEL),
+    ("blockchain::assign::role", ASSIGN_ROLE_CHANNEL),
+];
+
+/// Queues whose consumers retry and dead-letter failed jobs
+pub fn retrying_queues() -> impl Iterator<Item = &'static str> {
+    QUEUES
+        .iter()
+        .filter(|spec| spec.retrying)
+        .map(|spec| spec.name)
+}
+
+/// Declare the exchange, every queue of [`QUEUES`] and their bindings. Runs on each new
+/// connection, so anything deleted while the broker was down comes back.
+pub async fn declare(connection: &Connection) -> Result<()> {
+    let channel = connection
+        .create_channel()
+        .await
+        .context("Failed to create RabbitMQ channel")?;
+
+    channel
+        .exchange_declare(
+            EXCHANGE,
+            ExchangeKind::Topic,
+            durable_exchange(),
+            FieldTable::default(),
+        )
+        .await
+        .with_context(|| format!("Failed to declare exchange {}", EXCHANGE))?;
+
+    let policy = RetryPolicy::from_config();
+    for spec in QUEUES {
+        declare_queue(&channel, spec, &policy).await?;
+    }
+
+    let _ = channel.close(200, "OK").await;
+    Ok(())
+}
+
+async fn declare_queue(channel: &Channel, spec: &QueueSpec, policy: &RetryPolicy) -> Result<()> {
+    let mut arguments = FieldTable::default();
+    if spec.prioritized {
+        arguments.insert(
+            ShortString::from("x-max-priority"),
+            AMQPValue::ShortShortUInt(Priority::MAX),
+        );
+    }
+    if spec.retrying {
+        declare_dead_letters(channel, spec.name).await?;
+        // Catches what the broker itself rejects, such as messages over a length limit
+        arguments.insert(
+            ShortString::from("x-dead-letter-exchange"),
+            AMQPValue::LongString(dead_letter_exchange(spec.name).into()),
+        );
+    }
+
+    let options = if spec.durable {
+        durable_queue()
+    } else {
+        QueueDeclareOptions::default()
+    };
+    channel
+        .queue_declare(spec.name, options, arguments)
+        .await
+        .with_context(|| format!("Failed to declare queue {}", spec.name))?;
+    channel
+        .queue_bind(
+            spec.name,
+            EXCHANGE,
+            spec.name,
+            QueueBindOptions::default(),
+            FieldTable::default(),
+        )
+        .await
+        .with_context(|| format!("Failed to bind {} to {}", spec.name, EXCHANGE))?;
+
+    if spec.retrying {
+        declare_delay_queues(channel, spec.name, policy).await?;
+    }
+    Ok(())
+}
+
+async fn declare_dead_letters(channel: &Channel, queue: &str) -> Result<()> {
+    let dlx = dead_letter_exchange(queue);
+    let dlq = dead_letter_queue(queue);
+
+    channel
+        .exchange_declare(
+            &dlx,
+            ExchangeKind::Fanout,
+            durable_exchange(),
+            FieldTable::default(),
+        )
+        .await
+        .with_context(|| format!("Failed to declare exchange {}", dlx))?;
+
+    let mut arguments = FieldTable::default();
+    arguments.insert(
+        ShortString::from("x-message-ttl"),
+        AMQPValue::LongLongInt(DEAD_LETTER_TTL.as_millis() as i64),
+    );
+    channel
+        .queue_declare(&dlq, durable_queue(), arguments)
+        .await
+        .with_context(|| format!("Failed to declare queue {}", dlq))?;
+    channel
+        .queue_bind(
+            &dlq,
+            &dlx,
+            "",
+            QueueBindOptions::default(),
+            FieldTable::default(),
+        )
+        .await
+        .with_context(|| format!("Failed to bind {} to {}", dlq, dlx))?;
+    Ok(())
+}
+
+async fn declare_delay_queues(channel: &Channel, queue: &str, policy: &RetryPolicy) -> Result<()> {
+    for retry in 1..=policy.max_retries {
+        // Expired messages go back to the work queue through the default exchange
+        let mut arguments = FieldTable::default();
+        arguments.insert(
+            ShortString::from("x-message-ttl"),
+            AMQPValue::LongLongInt(policy.delay(retry).as_millis() as i64),
+        );
+        arguments.insert(
+            ShortString::from("x-dead-letter-exchange"),
+            AMQPValue::LongString("".into()),
+        );
+        arguments.insert(
+            ShortString::from("x-dead-letter-routing-key"),
+            AMQPValue::LongString(queue.into()),
+        );
+
+        let delay_queue = policy.delay_queue(queue, retry);
+        channel
+            .queue_declare(&delay_queue, durable_queue(), arguments)
+            .await
+            .with_context(|| format!("Failed to declare queue {}", delay_queue))?;
+    }
+    Ok(())
+}
+
+fn durable_exchange() -> ExchangeDeclareOptions {
+    ExchangeDeclareOptions {
+        durable: true,
+        ..ExchangeDeclareOptions::default()
+    }
+}
+
+fn durable_queue() -> QueueDeclareOptions {
+    QueueDeclareOptions {
+        durable: true,
+        ..QueueDeclareOptions::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_legacy_queue_has_a_replacement() {
+        for (legacy, queue) in LEGACY_QUEUES {
+            assert!(QUEUES.iter().any(|spec| spec.name == *queue), "{}", legacy);
+            assert!(!QUEUES.iter().any(|spec| spec.name == *legacy));
+        }
+    }
+
+    #[test]
+    fn test_mail_queue_keeps_its_arguments() {
+        let mail = QUEUES.iter().find(|spec| spec.name == MAIL_QUEUE).unwrap();
+        assert!(!mail.durable && !mail.prioritized && !mail.retrying);
+    }
+
+    #[test]
+    fn test_priority_round_trips() {
+        for priority in [Priority::Low, Priority::Normal, Priority::High] {
+            assert_eq!(Priority::from_value(i16::from(priority.value())), priority);
+        }
+    }
+}
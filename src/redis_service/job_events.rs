@@ -0,0 +1,91 @@
+use crate::kv_store::get_kv_store;
+use anyhow::{Context, Result};
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+/// Step of a CSV import a job event belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPhase {
+    Upload,
+    CreateUser,
+    BlockchainRegistration,
+}
+
+/// Change notification published next to the Redis progress counters.
+///
+/// Counters stay the source of truth: a `Progress` event only tells watchers to re-read
+/// them, so a dropped message is recovered by the next one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobEvent {
+    Progress {
+        phase: JobPhase,
+    },
+    RowFailed {
+        phase: JobPhase,
+        row_number: Option<u64>,
+        email: String,
+        reason: String,
+    },
+}
+
+pub struct JobEvents;
+
+impl JobEvents {
+    /// Jobs are keyed by `file_upload_history_id` or, for the CSV steps, by file name
+    pub fn channel(job_key: &str) -> String {
+        format!("job_events:{}", job_key)
+    }
+
+    /// Best effort: progress counters are already updated, so a failed publish is only logged
+    pub async fn publish(job_key: &str, event: &JobEvent) {
+        if let Err(e) = Self::try_publish(job_key, event).await {
+            tracing::warn!("Failed to publish job event for {}: {}", job_key, e);
+        }
+    }
+
+    async fn try_publish(job_key: &str, event: &JobEvent) -> Result<()> {
+        let payload = serde_json::to_string(event).context("Failed to serialize job event")?;
+        get_kv_store()
+            .await?
+            .publish(&Self::channel(job_key), &payload)
+            .await
+    }
+
+    /// Subscribe to the events of every given job key on a dedicated connection
+    pub async fn subscribe(job_keys: &[&str]) -> Result<impl Stream<Item = JobEvent> + Send> {
+        let channels: Vec<String> = job_keys.iter().map(|key| Self::channel(key)).collect();
+        let messages = get_kv_store()
+            .await?
+            .subscribe(&channels)
+            .await
+            .context("Failed to subscribe to job events")?;
+
+        Ok(messages.filter_map(|payload| async move {
+            serde_json::from_str(&payload)
+                .map_err(|e| tracing::warn!("Ignoring malformed job event: {}", e))
+                .ok()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_event_wire_format() {
+        let event = JobEvent::RowFailed {
+            phase: JobPhase::CreateUser,
+            row_number: Some(7),
+            email: "student@example.com".to_string(),
+            reason: "Email already exists".to_string(),
+        };
+
+        let payload = serde_json::to_string(&event).unwrap();
+        assert!(payload.contains("\"type\":\"row_failed\""));
+        assert!(payload.contains("\"phase\":\"create_user\""));
+        assert_eq!(serde_json::from_str::<JobEvent>(&payload).unwrap(), event);
+    }
+}
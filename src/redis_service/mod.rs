@@ -1,3 +1,6 @@
 // pub mod notification;
+pub mod connection;
+pub mod job_events;
 pub mod redis_emitter;
 pub mod redis_service;
+pub mod socket_events;
@@ -1,9 +1,22 @@
-use crate::config::APP_CONFIG;
-use redis::{AsyncCommands, aio::MultiplexedConnection};
+use crate::redis_service::connection::{RedisConnection, get_redis_connection};
+use crate::redis_service::socket_events::SocketEvent;
+use anyhow::{Context, Result, anyhow};
+use redis::AsyncCommands;
 use socketioxide_emitter::{Driver, IoEmitter};
+use std::future::Future;
+use std::time::Duration;
 use tokio::sync::OnceCell;
-pub struct RedisConnection(MultiplexedConnection);
-impl Driver for RedisConnection {
+
+/// Attempts at emitting one event before it is dropped
+const EMIT_ATTEMPTS: u32 = 3;
+/// Wait before the second attempt, doubled for each one after
+const EMIT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Publishes Socket.IO packets on the shared Redis connection. Channels are not prefixed:
+/// they belong to the Socket.IO adapter, not to this service.
+pub struct RedisEmitterDriver(RedisConnection);
+
+impl Driver for RedisEmitterDriver {
     type Error = redis::RedisError;
 
     async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
@@ -15,39 +28,181 @@ impl Driver for RedisConnection {
     }
 }
 
-pub static REDIS_EMITTER: OnceCell<RedisConnection> = OnceCell::const_new();
+pub static REDIS_EMITTER: OnceCell<RedisEmitterDriver> = OnceCell::const_new();
 
-pub async fn get_redis_emitter_conn() -> &'static RedisConnection {
+/// A failed connection is not cached, the next emit tries again
+pub async fn get_redis_emitter_conn() -> Result<&'static RedisEmitterDriver> {
     REDIS_EMITTER
-        .get_or_init(|| async {
-            let client = redis::Client::open(APP_CONFIG.redis_url.as_str())
-                .expect("Failed to create Redis client");
-            let conn = client
-                .get_multiplexed_tokio_connection()
+        .get_or_try_init(|| async {
+            let conn = get_redis_connection()
                 .await
-                .expect("Failed to connect to Redis");
-            RedisConnection(conn)
+                .context("Failed to connect to Redis")?;
+            Ok(RedisEmitterDriver(conn))
         })
         .await
 }
 
+/// Pushes [`SocketEvent`]s to the Socket.IO server through Redis.
+///
+/// Events are best effort: whatever they report is also stored or re-readable, so a
+/// Redis outage is retried a few times, then logged, and never fails the caller.
 pub struct RedisEmitter;
 
 impl RedisEmitter {
-    pub async fn emit_to_rooom(room: &str, msg: &str) {
-        let redis_emitter = get_redis_emitter_conn().await;
-        IoEmitter::new()
-            .to(room.to_string())
-            .emit("event", msg, redis_emitter)
+    pub async fn emit_to_room(room: &str, event: &SocketEvent) {
+        if let Err(e) = Self::try_emit(Some(room), event).await {
+            tracing::warn!("Dropped {} event for {}: {:#}", event.name(), room, e);
+        }
+    }
+
+    /// [`Self::emit_to_room`] without waiting for it, for request handlers
+    pub fn emit_in_background(room: String, event: SocketEvent) {
+        tokio::spawn(async move {
+            Self::emit_to_room(&room, &event).await;
+        });
+    }
+
+    pub async fn emit_to_all(event: &SocketEvent) {
+        if let Err(e) = Self::try_emit(None, event).await {
+            tracing::warn!("Dropped {} event: {:#}", event.name(), e);
+        }
+    }
+
+    /// Emit `event` to `room`, or to every socket, retrying with a doubling delay
+    pub async fn try_emit(room: Option<&str>, event: &SocketEvent) -> Result<()> {
+        Self::try_emit_with(get_redis_emitter_conn, room, event).await
+    }
+
+    /// [`Self::try_emit`] through the driver `connect` returns, asked again on every attempt
+    async fn try_emit_with<'d, D, F, Fut>(
+        connect: F,
+        room: Option<&str>,
+        event: &SocketEvent,
+    ) -> Result<()>
+    where
+        D: Driver + 'd,
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<&'d D>>,
+    {
+        let name = event.name();
+        let payload = event
+            .payload()
+            .with_context(|| format!("Failed to serialize {} event", name))?;
+
+        let mut delay = EMIT_RETRY_DELAY;
+        let mut attempt = 1;
+        loop {
+            let emitted = match connect().await {
+                Ok(driver) => Self::emit_once(driver, room, &name, &payload).await,
+                Err(e) => Err(e),
+            };
+            match emitted {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < EMIT_ATTEMPTS => {
+                    tracing::debug!(
+                        "Emitting {} event failed (attempt {}/{}): {:#}",
+                        name,
+                        attempt,
+                        EMIT_ATTEMPTS,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    return Err(e.context(format!("Gave up after {} attempts", EMIT_ATTEMPTS)));
+                }
+            }
+        }
+    }
+
+    async fn emit_once<D: Driver>(
+        driver: &D,
+        room: Option<&str>,
+        name: &str,
+        payload: &serde_json::Value,
+    ) -> Result<()> {
+        let emitter = match room {
+            Some(room) => IoEmitter::new().to(room.to_string()),
+            None => IoEmitter::new(),
+        };
+        emitter
+            .emit(name, payload, driver)
             .await
-            .expect("Failed to emit redis");
+            .map_err(|e| anyhow!("Failed to emit {} event: {:?}", name, e))?;
+        Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis_service::socket_events::RequestScheduledPayload;
+    use chrono::Utc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use uuid::Uuid;
 
-    pub async fn emit_to_all(msg: &str) {
-        let redis_emitter = get_redis_emitter_conn().await;
-        IoEmitter::new()
-            .emit("event", msg, redis_emitter)
+    /// Fails its first `failures` emits
+    struct FlakyDriver {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    impl FlakyDriver {
+        fn new(failures: u32) -> Self {
+            Self {
+                failures,
+                calls: AtomicU32::new(0),
+            }
+        }
+    }
+
+    impl Driver for FlakyDriver {
+        type Error = std::io::Error;
+
+        async fn emit(&self, _channel: String, _data: Vec<u8>) -> Result<(), Self::Error> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if call <= self.failures {
+                return Err(std::io::Error::other("connection refused"));
+            }
+            Ok(())
+        }
+    }
+
+    fn event() -> SocketEvent {
+        SocketEvent::RequestScheduled(RequestScheduledPayload {
+            request_id: Uuid::nil(),
+            scheduled_at: Utc::now().naive_utc(),
+            note: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_emit_gives_up_after_all_attempts() {
+        let driver = &FlakyDriver::new(u32::MAX);
+
+        let result = RedisEmitter::try_emit_with(
+            move || async move { Ok(driver) },
+            Some("user:1"),
+            &event(),
+        )
+        .await;
+
+        let error = result.expect_err("a driver that always fails cannot emit");
+        let gave_up = format!("Gave up after {} attempts", EMIT_ATTEMPTS);
+        assert!(format!("{:#}", error).contains(&gave_up));
+        assert_eq!(driver.calls.load(Ordering::SeqCst), EMIT_ATTEMPTS);
+    }
+
+    #[tokio::test]
+    async fn test_emit_retries_until_it_succeeds() {
+        let driver = &FlakyDriver::new(EMIT_ATTEMPTS - 1);
+
+        RedisEmitter::try_emit_with(move || async move { Ok(driver) }, None, &event())
             .await
-            .expect("Failed to emit redis");
+            .unwrap();
+
+        assert_eq!(driver.calls.load(Ordering::SeqCst), EMIT_ATTEMPTS);
     }
 }
@@ -1,39 +1,16 @@
 use crate::config::{
-    APP_CONFIG, FILE_TRACKER_EXPRIED_TIME, JWT_EXPRIED_TIME, MFA_CODE_REUSE_TTL_SECONDS,
-    MFA_LOCK_DURATION_SECONDS, MFA_MAX_FAIL_ATTEMPTS,
+    FILE_TRACKER_EXPRIED_TIME, JWT_EXPRIED_TIME, MFA_CODE_REUSE_TTL_SECONDS,
+    MFA_LOCK_DURATION_SECONDS, MFA_MAX_FAIL_ATTEMPTS, MFA_TRUSTED_DEVICE_TTL_SECONDS,
 };
+use crate::kv_store::{
+    PROGRESS_COMPLETED, PROGRESS_CURRENT, PROGRESS_FAILED, PROGRESS_SUCCESS, PROGRESS_TOTAL,
+    get_kv_store,
+};
+use crate::redis_service::job_events::{JobEvent, JobEvents, JobPhase};
 use anyhow::{Context, Result};
 use chrono::Utc;
-use once_cell::sync::Lazy;
-use redis::AsyncCommands;
-use redis::aio::ConnectionManager;
 use serde::{Deserialize, Serialize};
-
-pub static REDIS_CLIENT: Lazy<redis::Client> = Lazy::new(|| {
-    redis::Client::open(APP_CONFIG.redis_url.as_str()).expect("Failed to create Redis client")
-});
-
-pub async fn init_redis_connection() -> Result<()> {
-    // Test connection
-    let mut conn = REDIS_CLIENT
-        .get_connection_manager()
-        .await
-        .context("Failed to get Redis connection")?;
-
-    let _: String = redis::cmd("PING")
-        .query_async(&mut conn)
-        .await
-        .context("Failed to ping Redis")?;
-
-    Ok(())
-}
-
-pub async fn get_redis() -> Result<ConnectionManager> {
-    REDIS_CLIENT
-        .get_connection_manager()
-        .await
-        .context("Failed to get Redis connection")
-}
+use std::collections::HashMap;
 
 // MFA attempts stored in Redis
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,12 +53,10 @@ pub struct MfaRedisService;
 impl MfaRedisService {
     /// Get MFA attempts for a user
     pub async fn get_mfa_attempts(user_id: &str) -> Result<MfaAttempts> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
+        let store = get_kv_store().await?;
         let key = format!("mfa:attempts:{}", user_id);
 
-        match redis.get::<_, Option<String>>(&key).await? {
+        match store.get(&key).await? {
             Some(json) => {
                 let mfa_attempts: MfaAttempts =
                     serde_json::from_str(&json).context("Failed to deserialize MFA attempts")?;
@@ -100,46 +75,158 @@ impl MfaRedisService {
 
     /// Set MFA attempts for a user
     pub async fn set_mfa_attempts(user_id: &str, mfa_attempts: &MfaAttempts) -> Result<()> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
+        let store = get_kv_store().await?;
         let key = format!("mfa:attempts:{}", user_id);
 
         let json =
             serde_json::to_string(mfa_attempts).context("Failed to serialize MFA attempts")?;
 
-        let _: () = redis.set_ex(&key, json, MFA_LOCK_DURATION_SECONDS).await?;
+        store.set_ex(&key, &json, MFA_LOCK_DURATION_SECONDS).await?;
         Ok(())
     }
 
     /// Reset MFA attempts for a user
     pub async fn reset_mfa_attempts(user_id: &str) -> Result<()> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
+        let store = get_kv_store().await?;
         let key = format!("mfa:attempts:{}", user_id);
-        let _: () = redis.del(&key).await?;
+        store.del(&key).await?;
         Ok(())
     }
 
     /// Check if a code has been used before
     pub async fn is_mfa_code_used(user_id: &str, code: &str) -> Result<bool> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
+        let store = get_kv_store().await?;
         let key = format!("mfa:used_code:{}:{}", user_id, code);
-        let exists: bool = redis.exists(&key).await?;
-        Ok(exists)
+        Ok(store.exists(&key).await?)
     }
 
     /// Mark a code as used (with TTL)
     pub async fn mark_mfa_code_as_used(user_id: &str, code: &str) -> Result<()> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
+        let store = get_kv_store().await?;
         let key = format!("mfa:used_code:{}:{}", user_id, code);
         let now = Utc::now().timestamp();
-        let _: () = redis.set_ex(&key, now, MFA_CODE_REUSE_TTL_SECONDS).await?;
+        store
+            .set_ex(&key, &now.to_string(), MFA_CODE_REUSE_TTL_SECONDS)
+            .await?;
+        Ok(())
+    }
+}
+
+// Trusted ("remember this browser") devices that may skip MFA at login
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedDevice {
+    pub device_id: String,
+    pub label: String,
+    pub created_at: i64,   // Unix timestamp
+    pub last_used_at: i64, // Unix timestamp
+    pub expires_at: i64,   // Unix timestamp
+}
+
+pub struct TrustedDeviceService;
+
+impl TrustedDeviceService {
+    fn device_key(user_id: &str, device_id: &str) -> String {
+        format!("mfa:trusted_device:{}:{}", user_id, device_id)
+    }
+
+    fn index_key(user_id: &str) -> String {
+        format!("mfa:trusted_devices:{}", user_id)
+    }
+
+    /// Register a new trusted device for a user
+    pub async fn add_device(user_id: &str, device_id: &str, label: &str) -> Result<TrustedDevice> {
+        let store = get_kv_store().await?;
+
+        let now = Utc::now().timestamp();
+        let device = TrustedDevice {
+            device_id: device_id.to_string(),
+            label: label.to_string(),
+            created_at: now,
+            last_used_at: now,
+            expires_at: now + MFA_TRUSTED_DEVICE_TTL_SECONDS as i64,
+        };
+
+        let json = serde_json::to_string(&device).context("Failed to serialize trusted device")?;
+        store
+            .set_ex(
+                &Self::device_key(user_id, device_id),
+                &json,
+                MFA_TRUSTED_DEVICE_TTL_SECONDS,
+            )
+            .await?;
+
+        let index_key = Self::index_key(user_id);
+        store.sadd(&index_key, device_id).await?;
+        store
+            .expire(&index_key, MFA_TRUSTED_DEVICE_TTL_SECONDS)
+            .await?;
+
+        Ok(device)
+    }
+
+    /// Check that a device is still trusted and refresh its last used time
+    pub async fn touch_device(user_id: &str, device_id: &str) -> Result<bool> {
+        let store = get_kv_store().await?;
+        let key = Self::device_key(user_id, device_id);
+
+        let Some(json) = store.get(&key).await? else {
+            return Ok(false);
+        };
+
+        let mut device: TrustedDevice =
+            serde_json::from_str(&json).context("Failed to deserialize trusted device")?;
+        device.last_used_at = Utc::now().timestamp();
+
+        let json = serde_json::to_string(&device).context("Failed to serialize trusted device")?;
+        let ttl = (device.expires_at - device.last_used_at).max(1) as u64;
+        store.set_ex(&key, &json, ttl).await?;
+
+        Ok(true)
+    }
+
+    /// List trusted devices of a user, dropping index entries that already expired
+    pub async fn list_devices(user_id: &str) -> Result<Vec<TrustedDevice>> {
+        let store = get_kv_store().await?;
+        let index_key = Self::index_key(user_id);
+
+        let device_ids = store.smembers(&index_key).await?;
+        let mut devices = Vec::with_capacity(device_ids.len());
+
+        for device_id in device_ids {
+            let key = Self::device_key(user_id, &device_id);
+            match store.get(&key).await? {
+                Some(json) => devices.push(
+                    serde_json::from_str(&json).context("Failed to deserialize trusted device")?,
+                ),
+                None => {
+                    store.srem(&index_key, &device_id).await?;
+                }
+            }
+        }
+
+        devices.sort_by_key(|device: &TrustedDevice| std::cmp::Reverse(device.last_used_at));
+        Ok(devices)
+    }
+
+    /// Revoke one trusted device. Returns false if the device was not found.
+    pub async fn revoke_device(user_id: &str, device_id: &str) -> Result<bool> {
+        let store = get_kv_store().await?;
+
+        let removed = store.del(&Self::device_key(user_id, device_id)).await?;
+        store.srem(&Self::index_key(user_id), device_id).await?;
+        Ok(removed)
+    }
+
+    /// Revoke every trusted device of a user (e.g. after a password change)
+    pub async fn revoke_all_devices(user_id: &str) -> Result<()> {
+        let store = get_kv_store().await?;
+        let index_key = Self::index_key(user_id);
+
+        let device_ids = store.smembers(&index_key).await?;
+        for device_id in device_ids {
+            store.del(&Self::device_key(user_id, &device_id)).await?;
+        }
+        store.del(&index_key).await?;
         Ok(())
     }
 }
@@ -148,24 +235,21 @@ pub struct JwtBlacklist;
 
 impl JwtBlacklist {
     pub async fn add_jwt_to_blacklist(user_id: &str, jwt: &str) -> Result<()> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
+        let store = get_kv_store().await?;
 
         let key = format!("jwt:blacklist:{}:{}", user_id, jwt);
         let now = Utc::now().timestamp();
-        let _: () = redis.set_ex(&key, now, JWT_EXPRIED_TIME as u64).await?;
+        store
+            .set_ex(&key, &now.to_string(), JWT_EXPRIED_TIME as u64)
+            .await?;
         Ok(())
     }
 
     pub async fn check_jwt_in_blacklist(user_id: &str, jwt: &str) -> Result<bool> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
+        let store = get_kv_store().await?;
 
         let key = format!("jwt:blacklist:{}:{}", user_id, jwt);
-        let exists: bool = redis.exists(&key).await?;
-        Ok(exists)
+        Ok(store.exists(&key).await?)
     }
 }
 
@@ -182,416 +266,289 @@ pub struct FileProgress {
     pub failed: u64,
 }
 
-impl FileHandleTrackProgress {
-    pub async fn set_total_file_handle(file_name: &str, total: u64) -> Result<()> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
-
-        let key = format!("file_handle_tracker:{}", file_name);
-        let _: () = redis
-            .set_ex(&key, total, FILE_TRACKER_EXPRIED_TIME as u64)
-            .await?;
-
-        Ok(())
-    }
-
-    pub async fn set_current_file_progress(file_name: &str, current: u64) -> Result<()> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
-
-        let key = format!("file_handle_progress:{}", file_name);
+impl FileProgress {
+    fn from_fields(fields: &HashMap<String, u64>) -> Self {
+        let field = |name: &str| fields.get(name).copied().unwrap_or(0);
 
-        if current == 0 {
-            let _: () = redis
-                .set_ex(&key, 0u64, FILE_TRACKER_EXPRIED_TIME as u64)
-                .await?;
-            return Ok(());
+        let total = field(PROGRESS_TOTAL);
+        let mut current = field(PROGRESS_CURRENT);
+        if total > 0 && current > total {
+            current = total;
         }
 
-        let existing: Option<u64> = redis
-            .get(&key)
-            .await
-            .context("Failed to read current file progress from Redis")?;
-        let next_value = existing.map(|value| value.max(current)).unwrap_or(current);
-
-        let _: () = redis
-            .set_ex(&key, next_value, FILE_TRACKER_EXPRIED_TIME as u64)
-            .await?;
-
-        Ok(())
-    }
-
-    pub async fn reset_success_failed(file_name: &str) -> Result<()> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
-
-        let success_key = format!("file_handle_success:{}", file_name);
-        let failed_key = format!("file_handle_failed:{}", file_name);
-
-        let _: () = redis
-            .set_ex(&success_key, 0u64, FILE_TRACKER_EXPRIED_TIME as u64)
-            .await?;
-        let _: () = redis
-            .set_ex(&failed_key, 0u64, FILE_TRACKER_EXPRIED_TIME as u64)
-            .await?;
-
-        Ok(())
-    }
+        let percent = if total == 0 {
+            0
+        } else {
+            current.saturating_mul(100).checked_div(total).unwrap_or(0)
+        };
 
-    pub async fn increment_success(file_name: &str) -> Result<()> {
-        increment_counter(
-            &format!("file_handle_success:{}", file_name),
-            FILE_TRACKER_EXPRIED_TIME as u64,
-        )
-        .await
+        Self {
+            total,
+            current,
+            percent,
+            success: field(PROGRESS_SUCCESS),
+            failed: field(PROGRESS_FAILED),
+        }
     }
+}
 
-    pub async fn increment_failed(file_name: &str) -> Result<()> {
-        increment_counter(
-            &format!("file_handle_failed:{}", file_name),
+/// Reset the progress hash of a job to `total` items, none processed yet
+async fn start_progress(key: &str, total: u64) -> Result<()> {
+    let store = get_kv_store().await?;
+
+    store.del(key).await?;
+    store
+        .hset_ex(
+            key,
+            &[
+                (PROGRESS_TOTAL, total),
+                (PROGRESS_CURRENT, 0),
+                (PROGRESS_SUCCESS, 0),
+                (PROGRESS_FAILED, 0),
+                (PROGRESS_COMPLETED, 0),
+            ],
             FILE_TRACKER_EXPRIED_TIME as u64,
         )
         .await
-    }
 }
 
-pub async fn helper_get_current_file_progress(file_name: &str) -> Result<FileProgress> {
-    let mut redis = get_redis()
+async fn read_progress(key: &str) -> Result<FileProgress> {
+    let fields = get_kv_store()
+        .await?
+        .hget_all(key)
         .await
-        .context("Failed to get Redis connection")?;
+        .with_context(|| format!("Failed to get progress {} from Redis", key))?;
+    Ok(FileProgress::from_fields(&fields))
+}
 
-    let key_total_file_handle = format!("file_handle_tracker:{}", file_name);
-    let key_current_file_handle = format!("file_handle_progress:{}", file_name);
-    let key_success = format!("file_handle_success:{}", file_name);
-    let key_failed = format!("file_handle_failed:{}", file_name);
+impl FileHandleTrackProgress {
+    fn key(file_name: &str) -> String {
+        format!("job_progress:create_user:{}", file_name)
+    }
 
-    let total_file_handle: Option<u64> = redis
-        .get(&key_total_file_handle)
-        .await
-        .context("Failed to get total file handle from Redis")?;
+    /// Start tracking the user creation of `total` CSV rows
+    pub async fn start(file_name: &str, total: u64) -> Result<()> {
+        start_progress(&Self::key(file_name), total).await
+    }
 
-    let current_file_handle: Option<u64> = redis
-        .get(&key_current_file_handle)
-        .await
-        .context("Failed to get current file handle from Redis")?;
+    /// Returns true when this row was the last one of the file
+    pub async fn increment_success(file_name: &str) -> Result<bool> {
+        Self::increment(file_name, PROGRESS_SUCCESS).await
+    }
 
-    let total = total_file_handle.unwrap_or(0);
-    let mut current = current_file_handle.unwrap_or(0);
-    if total > 0 && current > total {
-        current = total;
+    /// Returns true when this row was the last one of the file
+    pub async fn increment_failed(file_name: &str) -> Result<bool> {
+        Self::increment(file_name, PROGRESS_FAILED).await
     }
 
-    let percent = if total == 0 {
-        0
-    } else {
-        current.saturating_mul(100).checked_div(total).unwrap_or(0)
-    };
+    async fn increment(file_name: &str, field: &str) -> Result<bool> {
+        let completed = get_kv_store()
+            .await?
+            .hincr_progress(
+                &Self::key(file_name),
+                field,
+                FILE_TRACKER_EXPRIED_TIME as u64,
+            )
+            .await?;
+        JobEvents::publish(
+            file_name,
+            &JobEvent::Progress {
+                phase: JobPhase::CreateUser,
+            },
+        )
+        .await;
+        Ok(completed)
+    }
+}
 
-    let success: Option<u64> = redis
-        .get(&key_success)
-        .await
-        .context("Failed to get success count from Redis")?;
-    let failed: Option<u64> = redis
-        .get(&key_failed)
-        .await
-        .context("Failed to get failed count from Redis")?;
-
-    Ok(FileProgress {
-        total,
-        current,
-        percent,
-        success: success.unwrap_or(0),
-        failed: failed.unwrap_or(0),
-    })
+pub async fn helper_get_current_file_progress(file_name: &str) -> Result<FileProgress> {
+    read_progress(&FileHandleTrackProgress::key(file_name)).await
 }
 
 pub struct BlockchainRegistrationProgress;
 
 impl BlockchainRegistrationProgress {
-    pub async fn set_total(
-        file_upload_history_id: &str,
-        total: u64,
-    ) -> Result<()> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
-
-        let key = format!("blockchain_registration_tracker:{}", file_upload_history_id);
-        let _: () = redis
-            .set_ex(&key, total, FILE_TRACKER_EXPRIED_TIME as u64)
-            .await?;
-
-        Ok(())
-    }
-
-    pub async fn reset_progress(
-        file_upload_history_id: &str,
-    ) -> Result<()> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
-
-        let key = format!(
-            "blockchain_registration_progress:{}",
-            file_upload_history_id
-        );
-
-        let _: () = redis
-            .set_ex(&key, 0u64, FILE_TRACKER_EXPRIED_TIME as u64)
-            .await?;
-
-        let success_key = format!(
-            "blockchain_registration_success:{}",
-            file_upload_history_id
-        );
-        let failed_key = format!(
-            "blockchain_registration_failed:{}",
+    fn key(file_upload_history_id: &str) -> String {
+        format!(
+            "job_progress:blockchain_registration:{}",
             file_upload_history_id
-        );
-
-        let _: () = redis
-            .set_ex(&success_key, 0u64, FILE_TRACKER_EXPRIED_TIME as u64)
-            .await?;
-        let _: () = redis
-            .set_ex(&failed_key, 0u64, FILE_TRACKER_EXPRIED_TIME as u64)
-            .await?;
-
-        Ok(())
+        )
     }
 
-    async fn increment(
-        key: &str,
-        ttl: u64,
-    ) -> Result<u64> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
-
-        let current: Option<u64> = redis.get(key).await?;
-        let next_value = current.map(|v| v + 1).unwrap_or(1);
-
-        let _: () = redis
-            .set_ex(key, next_value, ttl)
-            .await?;
-
-        Ok(next_value)
+    /// Start tracking the blockchain registration of `total` students
+    pub async fn start(file_upload_history_id: &str, total: u64) -> Result<()> {
+        start_progress(&Self::key(file_upload_history_id), total).await
     }
 
-    async fn increment_progress_internal(
-        file_upload_history_id: &str,
-    ) -> Result<u64> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
-
-        let key = format!("blockchain_registration_progress:{}", file_upload_history_id);
-
-        let current: Option<u64> = redis.get(&key).await?;
-        let next_value = current.map(|v| v + 1).unwrap_or(1);
-
-        let _: () = redis
-            .set_ex(&key, next_value, FILE_TRACKER_EXPRIED_TIME as u64)
-            .await?;
-
-        Ok(next_value)
+    /// Returns true when this student was the last one of the upload
+    pub async fn increment_success(file_upload_history_id: &str) -> Result<bool> {
+        Self::increment(file_upload_history_id, PROGRESS_SUCCESS).await
     }
 
-    pub async fn increment_success(
-        file_upload_history_id: &str,
-    ) -> Result<()> {
-        Self::increment_progress_internal(file_upload_history_id).await?;
-        Self::increment(
-            &format!(
-                "blockchain_registration_success:{}",
-                file_upload_history_id
-            ),
-            FILE_TRACKER_EXPRIED_TIME as u64,
-        )
-        .await?;
-        Ok(())
+    /// Returns true when this student was the last one of the upload
+    pub async fn increment_failed(file_upload_history_id: &str) -> Result<bool> {
+        Self::increment(file_upload_history_id, PROGRESS_FAILED).await
     }
 
-    pub async fn increment_failed(
-        file_upload_history_id: &str,
-    ) -> Result<()> {
-        Self::increment_progress_internal(file_upload_history_id).await?;
-        Self::increment(
-            &format!(
-                "blockchain_registration_failed:{}",
-                file_upload_history_id
-            ),
-            FILE_TRACKER_EXPRIED_TIME as u64,
+    async fn increment(file_upload_history_id: &str, field: &str) -> Result<bool> {
+        let completed = get_kv_store()
+            .await?
+            .hincr_progress(
+                &Self::key(file_upload_history_id),
+                field,
+                FILE_TRACKER_EXPRIED_TIME as u64,
+            )
+            .await?;
+        JobEvents::publish(
+            file_upload_history_id,
+            &JobEvent::Progress {
+                phase: JobPhase::BlockchainRegistration,
+            },
         )
-        .await?;
-        Ok(())
+        .await;
+        Ok(completed)
     }
 }
 
 pub async fn helper_get_blockchain_registration_progress(
     file_upload_history_id: &str,
 ) -> Result<FileProgress> {
-    let mut redis = get_redis()
-        .await
-        .context("Failed to get Redis connection")?;
-
-    let key_total = format!("blockchain_registration_tracker:{}", file_upload_history_id);
-    let key_current = format!("blockchain_registration_progress:{}", file_upload_history_id);
-    let key_success = format!("blockchain_registration_success:{}", file_upload_history_id);
-    let key_failed = format!("blockchain_registration_failed:{}", file_upload_history_id);
-
-    let total: Option<u64> = redis
-        .get(&key_total)
-        .await
-        .context("Failed to get total blockchain registration from Redis")?;
-
-    let current: Option<u64> = redis
-        .get(&key_current)
-        .await
-        .context("Failed to get current blockchain registration from Redis")?;
-
-    let total = total.unwrap_or(0);
-    let mut current = current.unwrap_or(0);
-    if total > 0 && current > total {
-        current = total;
-    }
-
-    let percent = if total == 0 {
-        0
-    } else {
-        current.saturating_mul(100).checked_div(total).unwrap_or(0)
-    };
-
-    let success: Option<u64> = redis
-        .get(&key_success)
-        .await
-        .context("Failed to get success blockchain registration from Redis")?;
-    let failed: Option<u64> = redis
-        .get(&key_failed)
-        .await
-        .context("Failed to get failed blockchain registration from Redis")?;
-
-    Ok(FileProgress {
-        total,
-        current,
-        percent,
-        success: success.unwrap_or(0),
-        failed: failed.unwrap_or(0),
-    })
+    read_progress(&BlockchainRegistrationProgress::key(file_upload_history_id)).await
 }
 
 impl ChunkUploadProgress {
+    fn key(file_name: &str) -> String {
+        format!("job_progress:upload:{}", file_name)
+    }
+
     /// Set total chunks for a file upload (only if not already set)
     pub async fn set_total_chunks(file_name: &str, total: u64) -> Result<()> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
-
-        let key = format!("chunk_upload_tracker:{}", file_name);
-        
-        // Check if total already exists
-        let existing: Option<u64> = redis.get(&key).await?;
-        
         // Only set if not already set (to handle out-of-order chunks)
-        if existing.is_none() {
-            let _: () = redis
-                .set_ex(&key, total, FILE_TRACKER_EXPRIED_TIME as u64)
-                .await?;
-        }
+        get_kv_store()
+            .await?
+            .hset_nx_ex(
+                &Self::key(file_name),
+                PROGRESS_TOTAL,
+                total,
+                FILE_TRACKER_EXPRIED_TIME as u64,
+            )
+            .await?;
 
         Ok(())
     }
 
-    /// Mark a chunk as uploaded
+    /// Mark a chunk as uploaded. A retried chunk is only counted once.
     pub async fn mark_chunk_uploaded(file_name: &str, chunk_number: usize) -> Result<()> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
-
-        let key = format!("chunk_uploaded:{}:{}", file_name, chunk_number);
-        let _: () = redis
-            .set_ex(&key, 1u64, FILE_TRACKER_EXPRIED_TIME as u64)
+        let store = get_kv_store().await?;
+        let key = Self::key(file_name);
+
+        let first_upload = store
+            .hset_nx_ex(
+                &key,
+                &format!("chunk:{}", chunk_number),
+                1,
+                FILE_TRACKER_EXPRIED_TIME as u64,
+            )
             .await?;
 
-        // Update current progress (count of uploaded chunks)
-        let progress_key = format!("chunk_upload_progress:{}", file_name);
-        let current: Option<u64> = redis.get(&progress_key).await?;
-        let next_value = current.map(|v| v + 1).unwrap_or(1);
-
-        let _: () = redis
-            .set_ex(&progress_key, next_value, FILE_TRACKER_EXPRIED_TIME as u64)
-            .await?;
+        if first_upload {
+            store
+                .hincr_ex(&key, PROGRESS_CURRENT, 1, FILE_TRACKER_EXPRIED_TIME as u64)
+                .await?;
+        }
 
+        JobEvents::publish(
+            file_name,
+            &JobEvent::Progress {
+                phase: JobPhase::Upload,
+            },
+        )
+        .await;
         Ok(())
     }
 
     /// Get chunk upload progress
     pub async fn get_progress(file_name: &str) -> Result<FileProgress> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
-
-        let key_total = format!("chunk_upload_tracker:{}", file_name);
-        let key_current = format!("chunk_upload_progress:{}", file_name);
-
-        let total: Option<u64> = redis
-            .get(&key_total)
-            .await
-            .context("Failed to get total chunks from Redis")?;
-
-        let current: Option<u64> = redis
-            .get(&key_current)
-            .await
-            .context("Failed to get current chunk progress from Redis")?;
-
-        let total = total.unwrap_or(0);
-        let mut current = current.unwrap_or(0);
-        if total > 0 && current > total {
-            current = total;
-        }
-
-        let percent = if total == 0 {
-            0
-        } else {
-            current.saturating_mul(100).checked_div(total).unwrap_or(0)
-        };
+        let progress = read_progress(&Self::key(file_name)).await?;
 
         Ok(FileProgress {
-            total,
-            current,
-            percent,
-            success: current, // For chunk upload, success = current (chunks uploaded)
-            failed: 0,       // No failed concept for chunk upload
+            success: progress.current, // For chunk upload, success = current (chunks uploaded)
+            failed: 0,                 // No failed concept for chunk upload
+            ..progress
         })
     }
 
     /// Reset chunk upload progress
     pub async fn reset_progress(file_name: &str) -> Result<()> {
-        let mut redis = get_redis()
-            .await
-            .context("Failed to get Redis connection")?;
-
-        let key_total = format!("chunk_upload_tracker:{}", file_name);
-        let key_current = format!("chunk_upload_progress:{}", file_name);
-
-        // Delete keys
-        let _: () = redis.del(&key_total).await?;
-        let _: () = redis.del(&key_current).await?;
+        get_kv_store().await?.del(&Self::key(file_name)).await?;
 
         Ok(())
     }
 }
 
-async fn increment_counter(key: &str, ttl: u64) -> Result<()> {
-    let mut redis = get_redis()
-        .await
-        .context("Failed to get Redis connection")?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kv_store::{MemoryKvStore, install_kv_store};
+    use std::sync::Arc;
+
+    /// Every test of this binary shares the in-memory store, so each one uses its own keys
+    async fn use_memory_store() {
+        let _ = install_kv_store(Arc::new(MemoryKvStore::new()));
+        assert_eq!(get_kv_store().await.unwrap().name(), "memory");
+    }
 
-    let current: Option<u64> = redis.get(key).await?;
-    let next_value = current.map(|v| v + 1).unwrap_or(1);
+    #[tokio::test]
+    async fn test_jwt_blacklist() {
+        use_memory_store().await;
 
-    let _: () = redis.set_ex(key, next_value, ttl).await?;
-    Ok(())
+        assert!(
+            !JwtBlacklist::check_jwt_in_blacklist("blacklist-user", "token")
+                .await
+                .unwrap()
+        );
+        JwtBlacklist::add_jwt_to_blacklist("blacklist-user", "token")
+            .await
+            .unwrap();
+        assert!(
+            JwtBlacklist::check_jwt_in_blacklist("blacklist-user", "token")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !JwtBlacklist::check_jwt_in_blacklist("blacklist-user", "other-token")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_progress_completes_on_last_row() {
+        use_memory_store().await;
+
+        FileHandleTrackProgress::start("users.csv", 3)
+            .await
+            .unwrap();
+        assert!(
+            !FileHandleTrackProgress::increment_success("users.csv")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !FileHandleTrackProgress::increment_failed("users.csv")
+                .await
+                .unwrap()
+        );
+        assert!(
+            FileHandleTrackProgress::increment_success("users.csv")
+                .await
+                .unwrap()
+        );
+
+        let progress = helper_get_current_file_progress("users.csv").await.unwrap();
+        assert_eq!(progress.total, 3);
+        assert_eq!(progress.current, 3);
+        assert_eq!(progress.success, 2);
+        assert_eq!(progress.failed, 1);
+    }
 }
@@ -0,0 +1,122 @@
+use crate::entities::sea_orm_active_enums::NotificationKind;
+use crate::redis_service::job_events::JobPhase;
+use chrono::NaiveDateTime;
+use sea_orm::ActiveEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt::Display;
+use uuid::Uuid;
+
+/// Socket.IO room of everything addressed to one user
+pub fn user_room(user_id: impl Display) -> String {
+    format!("user:{}", user_id)
+}
+
+/// Outcome of a blockchain job, for the user who requested it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobOutcomePayload {
+    /// `None` when the notification could not be stored
+    pub notification_id: Option<Uuid>,
+    pub succeeded: bool,
+    pub message: String,
+    /// Fields identifying the job, the stored notification's `data`
+    pub data: Map<String, Value>,
+}
+
+/// Counters of a CSV import step, sent after each row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchProgressPayload {
+    pub phase: JobPhase,
+    pub job_key: String,
+    pub total: u64,
+    pub success: u64,
+    pub failed: u64,
+    pub completed: bool,
+}
+
+/// A manager scheduled one of the user's requests
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestScheduledPayload {
+    pub request_id: Uuid,
+    pub scheduled_at: NaiveDateTime,
+    pub note: Option<String>,
+}
+
+/// Event pushed to Socket.IO clients.
+///
+/// The name of an event never changes. Its payload is sent as `{"version": n, "data": ...}`
+/// and a change old clients cannot read bumps `version` instead.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SocketEvent {
+    /// Named after the notification kind: `student_registered`,
+    /// `students_batch_registration_failed`, ...
+    JobOutcome(NotificationKind, JobOutcomePayload),
+    BatchProgress(BatchProgressPayload),
+    RequestScheduled(RequestScheduledPayload),
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: u16,
+    data: &'a T,
+}
+
+impl SocketEvent {
+    pub fn name(&self) -> String {
+        match self {
+            SocketEvent::JobOutcome(kind, _) => kind.to_value(),
+            SocketEvent::BatchProgress(_) => "batch_progress".to_string(),
+            SocketEvent::RequestScheduled(_) => "request_scheduled".to_string(),
+        }
+    }
+
+    pub fn version(&self) -> u16 {
+        match self {
+            SocketEvent::JobOutcome(..) => 1,
+            SocketEvent::BatchProgress(_) => 1,
+            SocketEvent::RequestScheduled(_) => 1,
+        }
+    }
+
+    pub fn payload(&self) -> serde_json::Result<Value> {
+        let version = self.version();
+        match self {
+            SocketEvent::JobOutcome(_, data) => serde_json::to_value(Envelope { version, data }),
+            SocketEvent::BatchProgress(data) => serde_json::to_value(Envelope { version, data }),
+            SocketEvent::RequestScheduled(data) => serde_json::to_value(Envelope { version, data }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_job_outcome_wire_format() {
+        let event = SocketEvent::JobOutcome(
+            NotificationKind::StudentRegistrationFailed,
+            JobOutcomePayload {
+                notification_id: None,
+                succeeded: false,
+                message: "Failed".to_string(),
+                data: Map::from_iter([("email".to_string(), json!("student@example.com"))]),
+            },
+        );
+
+        assert_eq!(event.name(), "student_registration_failed");
+        assert_eq!(
+            event.payload().unwrap(),
+            json!({
+                "version": 1,
+                "data": {
+                    "notification_id": null,
+                    "succeeded": false,
+                    "message": "Failed",
+                    "data": {"email": "student@example.com"}
+                }
+            })
+        );
+    }
+}
@@ -15,7 +15,7 @@ pub enum FileUploadStatus {
 }
 
 impl FileUploadStatus {
-    fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             FileUploadStatus::Pending => "pending",
             FileUploadStatus::Sync => "sync",
@@ -196,7 +196,6 @@ impl FileUploadRepository {
         let processed = progress.success + progress.failed;
         
         // Check if process is complete (processed >= total and total > 0)
-        // Use processed instead of current because current only tracks successful rows
         if progress.total > 0 && processed >= progress.total {
             // Find file upload history by file name
             if let Some(file_upload) = self.find_by_file_name(file_name).await? {
@@ -245,7 +244,6 @@ impl FileUploadRepository {
         let processed = progress.success + progress.failed;
         
         // Check if process is complete (processed >= total and total > 0)
-        // Use processed instead of current because current only tracks successful rows
         if progress.total > 0 && processed >= progress.total {
             // If all rows failed, update status to Failed
             let final_status = if progress.failed > 0 && progress.success == 0 {
@@ -0,0 +1,130 @@
+use crate::entities::{notification, sea_orm_active_enums::NotificationKind};
+use crate::static_service::DATABASE_CONNECTION;
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+pub struct NotificationRepository;
+
+impl NotificationRepository {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn get_connection(&self) -> &'static DatabaseConnection {
+        DATABASE_CONNECTION
+            .get()
+            .expect("DATABASE_CONNECTION not set")
+    }
+
+    /// Store an unread notification for `user_id`
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        kind: NotificationKind,
+        message: String,
+        data: Value,
+    ) -> Result<notification::Model> {
+        let db = self.get_connection();
+
+        let notification = notification::ActiveModel {
+            user_id: Set(user_id),
+            kind: Set(kind),
+            message: Set(message),
+            data: Set(data),
+            read_at: Set(None),
+            ..Default::default()
+        };
+
+        let result = notification.insert(db).await?;
+        Ok(result)
+    }
+
+    /// Get the notifications of a user, newest first, with pagination
+    pub async fn find_by_user_with_pagination(
+        &self,
+        user_id: Uuid,
+        unread_only: bool,
+        page: u32,
+        page_size: u32,
+    ) -> Result<(Vec<notification::Model>, u64)> {
+        let db = self.get_connection();
+        let mut query =
+            notification::Entity::find().filter(notification::Column::UserId.eq(user_id));
+
+        if unread_only {
+            query = query.filter(notification::Column::ReadAt.is_null());
+        }
+
+        let total = query.clone().count(db).await?;
+
+        let offset = (page - 1) * page_size;
+        let notifications = query
+            .order_by_desc(notification::Column::CreatedAt)
+            .limit(page_size as u64)
+            .offset(offset as u64)
+            .all(db)
+            .await?;
+
+        Ok((notifications, total))
+    }
+
+    /// Count the notifications a user has not read yet
+    pub async fn count_unread(&self, user_id: Uuid) -> Result<u64> {
+        let db = self.get_connection();
+        let count = notification::Entity::find()
+            .filter(notification::Column::UserId.eq(user_id))
+            .filter(notification::Column::ReadAt.is_null())
+            .count(db)
+            .await?;
+        Ok(count)
+    }
+
+    /// Mark one notification of a user as read. Returns `None` when the user has no such
+    /// notification; one that was already read keeps its original `read_at`.
+    pub async fn mark_read(
+        &self,
+        user_id: Uuid,
+        notification_id: Uuid,
+    ) -> Result<Option<notification::Model>> {
+        let db = self.get_connection();
+
+        let Some(notification) = notification::Entity::find_by_id(notification_id)
+            .filter(notification::Column::UserId.eq(user_id))
+            .one(db)
+            .await?
+        else {
+            return Ok(None);
+        };
+        if notification.read_at.is_some() {
+            return Ok(Some(notification));
+        }
+
+        let mut notification: notification::ActiveModel = notification.into();
+        notification.read_at = Set(Some(Utc::now().naive_utc()));
+
+        let result = notification.update(db).await?;
+        Ok(Some(result))
+    }
+
+    /// Mark every unread notification of a user as read, returning how many were
+    pub async fn mark_all_read(&self, user_id: Uuid) -> Result<u64> {
+        let db = self.get_connection();
+        let result = notification::Entity::update_many()
+            .col_expr(
+                notification::Column::ReadAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .filter(notification::Column::UserId.eq(user_id))
+            .filter(notification::Column::ReadAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
@@ -0,0 +1,129 @@
+use crate::entities::outbox_event;
+use crate::rabbitmq_service::topology::Priority;
+use crate::static_service::DATABASE_CONNECTION;
+use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
+use sea_orm::sea_query::{Expr, LockBehavior, LockType};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+pub struct OutboxRepository;
+
+impl OutboxRepository {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn get_connection(&self) -> &'static DatabaseConnection {
+        DATABASE_CONNECTION
+            .get()
+            .expect("DATABASE_CONNECTION not set")
+    }
+
+    /// Queue `message` for `queue`. Pass the transaction that writes the rows the message
+    /// is about, so the message exists if and only if they do.
+    pub async fn enqueue<C, T>(
+        &self,
+        db: &C,
+        queue: &str,
+        priority: Priority,
+        message: &T,
+    ) -> Result<Uuid>
+    where
+        C: ConnectionTrait,
+        T: Serialize,
+    {
+        let now = Utc::now().naive_utc();
+        let event = outbox_event::ActiveModel {
+            event_id: Set(Uuid::new_v4()),
+            queue: Set(queue.to_string()),
+            payload: Set(serde_json::to_value(message)?),
+            priority: Set(i16::from(priority.value())),
+            attempts: Set(0),
+            last_error: Set(None),
+            created_at: Set(now),
+            next_attempt_at: Set(now),
+            sent_at: Set(None),
+        };
+
+        let result = event.insert(db).await?;
+        Ok(result.event_id)
+    }
+
+    /// Oldest unsent events that are due, locked until `db` ends. Rows locked by another
+    /// relay are skipped rather than waited for.
+    pub async fn lock_due<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        limit: u64,
+    ) -> Result<Vec<outbox_event::Model>> {
+        let events = outbox_event::Entity::find()
+            .filter(outbox_event::Column::SentAt.is_null())
+            .filter(outbox_event::Column::NextAttemptAt.lte(Utc::now().naive_utc()))
+            .order_by_asc(outbox_event::Column::CreatedAt)
+            .limit(limit)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+            .all(db)
+            .await?;
+        Ok(events)
+    }
+
+    pub async fn mark_sent<C: ConnectionTrait>(&self, db: &C, event_id: Uuid) -> Result<()> {
+        outbox_event::Entity::update_many()
+            .col_expr(
+                outbox_event::Column::SentAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .filter(outbox_event::Column::EventId.eq(event_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn mark_failed<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        event_id: Uuid,
+        error: &str,
+        next_attempt_at: NaiveDateTime,
+    ) -> Result<()> {
+        outbox_event::Entity::update_many()
+            .col_expr(
+                outbox_event::Column::Attempts,
+                Expr::col(outbox_event::Column::Attempts).add(1),
+            )
+            .col_expr(outbox_event::Column::LastError, Expr::value(error))
+            .col_expr(
+                outbox_event::Column::NextAttemptAt,
+                Expr::value(next_attempt_at),
+            )
+            .filter(outbox_event::Column::EventId.eq(event_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Events not yet confirmed by the broker
+    pub async fn count_pending(&self) -> Result<u64> {
+        let db = self.get_connection();
+        let count = outbox_event::Entity::find()
+            .filter(outbox_event::Column::SentAt.is_null())
+            .count(db)
+            .await?;
+        Ok(count)
+    }
+
+    /// Delete events sent before `before`, returning how many were removed
+    pub async fn delete_sent_before(&self, before: NaiveDateTime) -> Result<u64> {
+        let db = self.get_connection();
+        let result = outbox_event::Entity::delete_many()
+            .filter(outbox_event::Column::SentAt.lt(before))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
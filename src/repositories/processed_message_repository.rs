@@ -0,0 +1,67 @@
+use crate::entities::processed_message;
+use crate::static_service::DATABASE_CONNECTION;
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+
+pub const OUTCOME_SUCCEEDED: &str = "succeeded";
+pub const OUTCOME_FAILED: &str = "failed";
+
+/// Final outcome of each message a consumer processed, by idempotency key
+pub struct ProcessedMessageRepository;
+
+impl ProcessedMessageRepository {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn get_connection(&self) -> &'static DatabaseConnection {
+        DATABASE_CONNECTION
+            .get()
+            .expect("DATABASE_CONNECTION not set")
+    }
+
+    /// Whether a message with this key was already processed successfully. Failed ones
+    /// may run again, that is what replaying a dead letter is for.
+    pub async fn has_succeeded(&self, idempotency_key: &str) -> Result<bool> {
+        let db = self.get_connection();
+        let processed = processed_message::Entity::find_by_id(idempotency_key.to_string())
+            .filter(processed_message::Column::Outcome.eq(OUTCOME_SUCCEEDED))
+            .one(db)
+            .await?;
+        Ok(processed.is_some())
+    }
+
+    /// Record the outcome of the latest attempt, replacing the previous one
+    pub async fn record(
+        &self,
+        idempotency_key: &str,
+        queue: &str,
+        outcome: &'static str,
+        error: Option<String>,
+    ) -> Result<()> {
+        let db = self.get_connection();
+        let processed = processed_message::ActiveModel {
+            idempotency_key: Set(idempotency_key.to_string()),
+            queue: Set(queue.to_string()),
+            outcome: Set(outcome.to_string()),
+            error: Set(error),
+            processed_at: Set(Utc::now().naive_utc()),
+        };
+
+        processed_message::Entity::insert(processed)
+            .on_conflict(
+                OnConflict::column(processed_message::Column::IdempotencyKey)
+                    .update_columns([
+                        processed_message::Column::Outcome,
+                        processed_message::Column::Error,
+                        processed_message::Column::ProcessedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+        Ok(())
+    }
+}
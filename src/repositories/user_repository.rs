@@ -2,9 +2,11 @@ use crate::entities::sea_orm_active_enums::{RoleEnum, UserStatus};
 use crate::entities::{user, user_major, wallet};
 use crate::static_service::DATABASE_CONNECTION;
 use anyhow::Result;
+use chrono::NaiveDateTime;
+use sea_orm::sea_query::Expr;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DeleteResult, EntityTrait, PaginatorTrait,
-    QueryFilter, QueryOrder, QuerySelect, Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DeleteResult, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set,
 };
 use uuid::Uuid;
 
@@ -30,6 +32,16 @@ impl UserRepository {
         Ok(user)
     }
 
+    pub async fn find_by_ids(&self, user_ids: Vec<Uuid>) -> Result<Vec<user::Model>> {
+        let db = self.get_connection();
+        let users = user::Entity::find()
+            .filter(user::Column::UserId.is_in(user_ids))
+            .filter(user::Column::DeletedAt.is_null())
+            .all(db)
+            .await?;
+        Ok(users)
+    }
+
     pub async fn find_by_email(&self, email: &str) -> Result<Option<user::Model>> {
         let db = self.get_connection();
         let user = user::Entity::find()
@@ -131,8 +143,10 @@ impl UserRepository {
         }
     }
 
-    pub async fn create(
+    /// Insert a user through `db`, which may be a transaction
+    pub async fn create<C: ConnectionTrait>(
         &self,
+        db: &C,
         user_id: Uuid,
         first_name: String,
         last_name: String,
@@ -145,7 +159,6 @@ impl UserRepository {
         is_priority: bool,
         student_code: Option<String>,
     ) -> Result<user::Model> {
-        let db = self.get_connection();
         let now = chrono::Utc::now().naive_utc();
         let mut user_model = user::ActiveModel {
             user_id: Set(user_id),
@@ -234,9 +247,12 @@ impl UserRepository {
         Ok(result)
     }
 
-    /// Soft delete user by setting deleted_at timestamp
-    pub async fn soft_delete(&self, user_id: Uuid) -> Result<user::Model> {
-        let db = self.get_connection();
+    /// Soft delete user by setting deleted_at timestamp, through `db` which may be a transaction
+    pub async fn soft_delete<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Uuid,
+    ) -> Result<user::Model> {
         let user = user::Entity::find_by_id(user_id)
             .one(db)
             .await?
@@ -369,6 +385,30 @@ impl UserRepository {
         let result = active_user.update(db).await?;
         Ok(result)
     }
+
+    /// Start the MFA enrollment grace period of a user unless it already started, and
+    /// return when it did
+    pub async fn start_mfa_grace(
+        &self,
+        user_id: Uuid,
+        now: NaiveDateTime,
+    ) -> Result<NaiveDateTime> {
+        let db = self.get_connection();
+        user::Entity::update_many()
+            .col_expr(user::Column::MfaGraceStartedAt, Expr::value(now))
+            .filter(user::Column::UserId.eq(user_id))
+            .filter(user::Column::MfaGraceStartedAt.is_null())
+            .exec(db)
+            .await?;
+
+        // Another request may have started it first
+        let started_at = user::Entity::find_by_id(user_id)
+            .one(db)
+            .await?
+            .and_then(|user| user.mfa_grace_started_at)
+            .unwrap_or(now);
+        Ok(started_at)
+    }
 }
 
 #[derive(Default)]
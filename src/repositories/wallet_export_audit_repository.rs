@@ -0,0 +1,51 @@
+use crate::entities::wallet_export_audit;
+use crate::static_service::DATABASE_CONNECTION;
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+use uuid::Uuid;
+
+pub const EXPORT_OUTCOME_SUCCESS: &str = "success";
+pub const EXPORT_OUTCOME_DENIED: &str = "denied";
+pub const EXPORT_OUTCOME_FAILED: &str = "failed";
+
+/// One private key export attempt, successful or not
+pub struct WalletExportAuditEntry {
+    pub user_id: Uuid,
+    pub actor_user_id: Option<Uuid>,
+    pub peer_addr: Option<String>,
+    pub wrapped: bool,
+    pub outcome: &'static str,
+    pub reason: Option<String>,
+}
+
+pub struct WalletExportAuditRepository;
+
+impl WalletExportAuditRepository {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn get_connection(&self) -> &'static DatabaseConnection {
+        DATABASE_CONNECTION
+            .get()
+            .expect("DATABASE_CONNECTION not set")
+    }
+
+    pub async fn record(&self, entry: WalletExportAuditEntry) -> Result<wallet_export_audit::Model> {
+        let db = self.get_connection();
+        let audit = wallet_export_audit::ActiveModel {
+            audit_id: Set(Uuid::new_v4()),
+            user_id: Set(entry.user_id),
+            actor_user_id: Set(entry.actor_user_id),
+            peer_addr: Set(entry.peer_addr),
+            wrapped: Set(entry.wrapped),
+            outcome: Set(entry.outcome.to_string()),
+            reason: Set(entry.reason),
+            created_at: Set(Utc::now().naive_utc()),
+        };
+
+        let result = audit.insert(db).await?;
+        Ok(result)
+    }
+}
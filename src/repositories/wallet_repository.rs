@@ -2,7 +2,8 @@ use crate::entities::wallet;
 use crate::static_service::DATABASE_CONNECTION;
 use anyhow::Result;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DeleteResult, EntityTrait, QueryFilter, Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DeleteResult, EntityTrait,
+    QueryFilter, Set,
 };
 use uuid::Uuid;
 
@@ -37,8 +38,10 @@ impl WalletRepository {
         Ok(wallet_info)
     }
 
-    pub async fn create(
+    /// Insert a wallet through `db`, which may be a transaction
+    pub async fn create<C: ConnectionTrait>(
         &self,
+        db: &C,
         wallet_id: Uuid,
         user_id: Uuid,
         address: String,
@@ -48,7 +51,6 @@ impl WalletRepository {
         status: String,
         network_id: String,
     ) -> Result<wallet::Model> {
-        let db = self.get_connection();
         let now = chrono::Utc::now().naive_utc();
         let wallet_model = wallet::ActiveModel {
             wallet_id: Set(wallet_id),
@@ -1,3 +1,4 @@
+use crate::middleware::mfa_policy::MfaPolicyState;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -12,6 +13,10 @@ pub struct LoginRequest {
 
     #[schema(example = "123456")]
     pub authenticator_code: Option<String>,
+
+    /// Trust this browser and skip MFA on it for the next 30 days
+    #[schema(example = false)]
+    pub remember_device: Option<bool>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -22,6 +27,7 @@ pub struct LoginResponse {
     pub user_id: String,
     pub email: String,
     pub role: String,
+    pub mfa_policy: MfaPolicyState,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -78,3 +84,19 @@ pub struct ChangePasswordRequest {
 pub struct ChangePasswordResponse {
     pub message: String,
 }
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StepUpRequest {
+    #[schema(example = "123456")]
+    pub authenticator_code: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StepUpResponse {
+    pub step_up_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub auth_time: i64,
+    pub amr: Vec<String>,
+}
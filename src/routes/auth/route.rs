@@ -1,19 +1,34 @@
-use axum::{Json, Router, http::StatusCode, routing::post};
+use axum::{
+    Json, Router,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+};
 use axum_extra::TypedHeader;
-use axum_extra::headers::{Authorization, authorization::Bearer};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use axum_extra::headers::{Authorization, UserAgent, authorization::Bearer};
 
 use super::dto::{
     ChangePasswordRequest, ChangePasswordResponse, ForgotPasswordRequest, ForgotPasswordResponse,
     LoginRequest, LoginResponse, LogoutResponse, ResetPasswordRequest, ResetPasswordResponse,
+    StepUpRequest, StepUpResponse,
+};
+use crate::config::{
+    APP_CONFIG, JWT_EXPRIED_TIME, MFA_STEP_UP_TOKEN_EXPIRED_TIME, MFA_TRUSTED_DEVICE_COOKIE,
+    MFA_TRUSTED_DEVICE_TTL_SECONDS,
 };
-use crate::config::JWT_EXPRIED_TIME;
+use crate::email_service::EmailService;
+use crate::email_service::template::EmailTemplate;
 use crate::entities::sea_orm_active_enums::RoleEnum;
 use crate::extractor::AuthClaims;
-use crate::rabbitmq_service::consumers::get_rabbitmq_connetion;
-use crate::rabbitmq_service::rabbitmq_service::RabbitMQService;
-use crate::redis_service::redis_service::JwtBlacklist;
+use crate::mfa_service::MfaService;
+use crate::middleware::mfa_policy::MfaPolicy;
+use crate::redis_service::redis_service::{JwtBlacklist, TrustedDeviceService};
 use crate::repositories::{OtpVerifyRepository, UserMfaRepository, UserRepository};
-use crate::utils::gen_otp_code::gen_code;
+use crate::utils::gen_otp_code::{OTP_EXPIRES_IN_MINUTES, gen_code};
+use crate::utils::step_up_token::create_step_up_token;
+use crate::utils::trusted_device_token::{
+    create_trusted_device_token, decode_trusted_device_token,
+};
 use chrono::Utc;
 use do_an_lib::jwt::JwtManager;
 use do_an_lib::structs::token_claims::UserRole;
@@ -22,6 +37,7 @@ pub fn create_route() -> Router {
     Router::new()
         .route("/api/v1/auth/login", post(login))
         .route("/api/v1/auth/logout", post(logout))
+        .route("/api/v1/auth/step-up", post(step_up))
         .route("/api/v1/auth/forgot-password", post(forgot_password))
         .route("/api/v1/auth/reset-password", post(reset_password))
         .route("/api/v1/auth/change-password", post(change_password))
@@ -33,15 +49,17 @@ pub fn create_route() -> Router {
     path = "/api/v1/auth/login",
     request_body = LoginRequest,
     responses(
-        (status = 200, description = "Login successful", body = LoginResponse),
+        (status = 200, description = "Login successful (check mfa_policy for enrollment required state)", body = LoginResponse),
         (status = 401, description = "Invalid credentials"),
         (status = 500, description = "Internal server error")
     ),
     tag = "Authentication"
 )]
 pub async fn login(
+    jar: CookieJar,
+    user_agent: Option<TypedHeader<UserAgent>>,
     Json(payload): Json<LoginRequest>,
-) -> Result<(StatusCode, Json<LoginResponse>), (StatusCode, String)> {
+) -> Result<(StatusCode, CookieJar, Json<LoginResponse>), (StatusCode, String)> {
     let user_repo = UserRepository::new();
 
     // Find user by email (this already filters deleted_at IS NULL)
@@ -90,8 +108,30 @@ pub async fn login(
             )
         })?;
 
+    // Evaluate the per-role MFA policy; users that must enroll still get a token,
+    // but `AuthClaims` restricts them to the enrollment endpoints once the grace period is over
+    let mfa_policy = MfaPolicy::from_config()
+        .evaluate_user(&user_info, mfa_enabled.is_some())
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to evaluate MFA policy: {}", e),
+            )
+        })?;
+
+    // A trusted device cookie lets the user skip the authenticator code
+    let trusted_device = match jar.get(MFA_TRUSTED_DEVICE_COOKIE) {
+        Some(cookie) if mfa_enabled.is_some() => {
+            is_trusted_device(&user_id_str, cookie.value()).await
+        }
+        _ => false,
+    };
+
+    let mut jar = jar;
+
     // If MFA is enabled, verify the authenticator code
-    if let Some(_mfa_record) = mfa_enabled {
+    if mfa_enabled.is_some() && !trusted_device {
         let authenticator_code = payload.authenticator_code.ok_or_else(|| {
             (
                 StatusCode::BAD_REQUEST,
@@ -99,48 +139,15 @@ pub async fn login(
             )
         })?;
 
-        use crate::repositories::mfa_verify_result::MfaVerifyResult;
-
-        let verify_result = mfa_repo
-            .verify_mfa_code(&user_id_str, &authenticator_code)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to verify MFA code: {}", e),
-                )
-            })?;
-
-        match verify_result {
-            MfaVerifyResult::Success => {
-                // Continue with login
-            }
-            MfaVerifyResult::Locked { locked_until } => {
-                let message = if let Some(until) = locked_until {
-                    format!("MFA is locked until {} (too many failed attempts)", until)
-                } else {
-                    "MFA is locked due to too many failed attempts".to_string()
-                };
-                return Err((StatusCode::FORBIDDEN, message));
-            }
-            MfaVerifyResult::CodeAlreadyUsed => {
-                return Err((
-                    StatusCode::UNAUTHORIZED,
-                    "MFA code has already been used".to_string(),
-                ));
-            }
-            MfaVerifyResult::InvalidCode => {
-                return Err((
-                    StatusCode::UNAUTHORIZED,
-                    "Invalid authenticator code".to_string(),
-                ));
-            }
-            MfaVerifyResult::MfaNotEnabled => {
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    "MFA is not enabled for this user".to_string(),
-                ));
-            }
+        MfaService::new()
+            .verify(&user_id_str, &authenticator_code)
+            .await?;
+
+        if payload.remember_device.unwrap_or(false) {
+            let label = user_agent
+                .map(|TypedHeader(agent)| agent.as_str().to_string())
+                .unwrap_or_else(|| "Unknown device".to_string());
+            jar = jar.add(issue_trusted_device_cookie(&user_id_str, &label).await?);
         }
     }
 
@@ -184,9 +191,68 @@ pub async fn login(
         user_id: user_info.user_id.to_string(),
         email: user_info.email,
         role: role_str.to_string(),
+        mfa_policy,
     };
 
-    Ok((StatusCode::OK, Json(response)))
+    Ok((StatusCode::OK, jar, Json(response)))
+}
+
+/// Check the trusted device cookie: signature, owner and that it was not revoked
+async fn is_trusted_device(user_id: &str, token: &str) -> bool {
+    let Ok(claims) = decode_trusted_device_token(token, user_id) else {
+        return false;
+    };
+
+    match TrustedDeviceService::touch_device(user_id, &claims.did).await {
+        Ok(trusted) => trusted,
+        Err(e) => {
+            tracing::error!("Failed to check trusted device for user {}: {}", user_id, e);
+            false
+        }
+    }
+}
+
+/// A password change invalidates every trusted device of the user
+async fn revoke_trusted_devices(user_id: &str) -> Result<(), (StatusCode, String)> {
+    TrustedDeviceService::revoke_all_devices(user_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to revoke trusted devices: {}", e),
+            )
+        })
+}
+
+async fn issue_trusted_device_cookie(
+    user_id: &str,
+    label: &str,
+) -> Result<Cookie<'static>, (StatusCode, String)> {
+    let device_id = uuid::Uuid::new_v4().to_string();
+
+    TrustedDeviceService::add_device(user_id, &device_id, label)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to register trusted device: {}", e),
+            )
+        })?;
+
+    let token = create_trusted_device_token(user_id, &device_id).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to create trusted device token: {}", e),
+        )
+    })?;
+
+    Ok(Cookie::build((MFA_TRUSTED_DEVICE_COOKIE, token))
+        .path("/api/v1/auth")
+        .http_only(true)
+        .secure(APP_CONFIG.app_env != "local")
+        .same_site(SameSite::Strict)
+        .max_age(time::Duration::seconds(MFA_TRUSTED_DEVICE_TTL_SECONDS as i64))
+        .build())
 }
 
 /// Logout endpoint - blacklist JWT token
@@ -225,6 +291,47 @@ pub async fn logout(
     Ok((StatusCode::OK, Json(response)))
 }
 
+/// Step-up endpoint - verifies MFA once and returns a short-lived elevated token
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/step-up",
+    request_body = StepUpRequest,
+    responses(
+        (status = 200, description = "MFA verified, step-up token issued", body = StepUpResponse),
+        (status = 400, description = "MFA is not enabled for this user"),
+        (status = 401, description = "Invalid or reused authenticator code"),
+        (status = 403, description = "MFA is locked"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Authentication"
+)]
+pub async fn step_up(
+    AuthClaims(auth_claims): AuthClaims,
+    Json(payload): Json<StepUpRequest>,
+) -> Result<(StatusCode, Json<StepUpResponse>), (StatusCode, String)> {
+    MfaService::new()
+        .verify(&auth_claims.user_id, &payload.authenticator_code)
+        .await?;
+
+    let (step_up_token, claims) = create_step_up_token(&auth_claims.user_id).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to create step-up token: {}", e),
+        )
+    })?;
+
+    let response = StepUpResponse {
+        step_up_token,
+        token_type: "StepUp".to_string(),
+        expires_in: MFA_STEP_UP_TOKEN_EXPIRED_TIME,
+        auth_time: claims.auth_time,
+        amr: claims.amr,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
 /// Forgot password endpoint - sends OTP to email via RabbitMQ
 #[utoipa::path(
     post,
@@ -238,6 +345,7 @@ pub async fn logout(
     tag = "Authentication"
 )]
 pub async fn forgot_password(
+    headers: HeaderMap,
     Json(payload): Json<ForgotPasswordRequest>,
 ) -> Result<(StatusCode, Json<ForgotPasswordResponse>), (StatusCode, String)> {
     let user_repo = UserRepository::new();
@@ -286,26 +394,16 @@ pub async fn forgot_password(
         })?;
 
     // Send OTP via RabbitMQ to email service
-    let rabbitmq_conn = get_rabbitmq_connetion().await;
-    let email_subject = "Reset Password OTP";
-    let email_body = format!(
-        "Your OTP code for password reset is: {}. This code will expire in 10 minutes.",
-        otp_code
-    );
+    let email = EmailTemplate::ResetPassword {
+        otp_code,
+        expires_in_minutes: OTP_EXPIRES_IN_MINUTES,
+    };
 
-    RabbitMQService::publish_to_mail_queue(
-        rabbitmq_conn,
-        &payload.email,
-        email_subject,
-        &email_body,
-    )
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to send email: {}", e),
-        )
-    })?;
+    EmailService::send_in_background(
+        payload.email.clone(),
+        email,
+        EmailService::locale_from_headers(&headers),
+    );
 
     let response = ForgotPasswordResponse {
         message: "OTP code has been sent to your email".to_string(),
@@ -426,6 +524,9 @@ pub async fn reset_password(
             )
         })?;
 
+    // Only once the new password is stored, so a failed update keeps the devices trusted
+    revoke_trusted_devices(&user_info.user_id.to_string()).await?;
+
     let response = ResetPasswordResponse {
         message: "Password has been reset successfully".to_string(),
     };
@@ -519,6 +620,8 @@ pub async fn change_password(
             )
         })?;
 
+    revoke_trusted_devices(&auth_claims.user_id).await?;
+
     let response = ChangePasswordResponse {
         message: "Password has been changed successfully".to_string(),
     };
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::rabbitmq_service::dead_letters::{DeadLetter, DeadLetterQueueStats};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DeadLetterQueueResponse {
+    pub queue: String,
+    pub dead_letter_queue: String,
+    pub message_count: u32,
+}
+
+impl From<DeadLetterQueueStats> for DeadLetterQueueResponse {
+    fn from(stats: DeadLetterQueueStats) -> Self {
+        Self {
+            queue: stats.queue.to_string(),
+            dead_letter_queue: stats.dead_letter_queue,
+            message_count: stats.message_count,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DeadLetterQueueListResponse {
+    pub queues: Vec<DeadLetterQueueResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DeadLetterResponse {
+    pub message_id: Option<String>,
+    pub retry_count: u32,
+    pub last_error: Option<String>,
+    /// Unix timestamp
+    pub dead_lettered_at: Option<i64>,
+    /// Message body as sent by the producer
+    pub payload: String,
+}
+
+impl From<DeadLetter> for DeadLetterResponse {
+    fn from(dead_letter: DeadLetter) -> Self {
+        Self {
+            message_id: dead_letter.message_id,
+            retry_count: dead_letter.retry_count,
+            last_error: dead_letter.last_error,
+            dead_lettered_at: dead_letter.dead_lettered_at,
+            payload: dead_letter.payload,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DeadLetterListResponse {
+    pub queue: String,
+    pub messages: Vec<DeadLetterResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterQueryParams {
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+fn default_limit() -> usize {
+    50
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReplayDeadLettersRequest {
+    /// Messages to replay; all of the queue when omitted
+    pub message_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DiscardDeadLettersRequest {
+    pub message_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DeadLetterActionResponse {
+    pub queue: String,
+    pub processed: usize,
+}
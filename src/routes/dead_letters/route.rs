@@ -0,0 +1,223 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query},
+    http::StatusCode,
+    routing::{get, post},
+};
+
+use super::dto::{
+    DeadLetterActionResponse, DeadLetterListResponse, DeadLetterQueryParams,
+    DeadLetterQueueListResponse, DiscardDeadLettersRequest, ReplayDeadLettersRequest,
+};
+use crate::extractor::{AuthClaims, MfaErrorResponse, RequireRecentMfa};
+use crate::middleware::permission;
+use crate::rabbitmq_service::dead_letters::{DeadLetterService, Selection};
+
+/// Most messages returned by one peek
+const MAX_PEEK_LIMIT: usize = 500;
+
+pub fn create_route() -> Router {
+    Router::new()
+        .route("/api/v1/admin/dead-letters", get(list_dead_letter_queues))
+        .route("/api/v1/admin/dead-letters/{queue}", get(get_dead_letters))
+        .route(
+            "/api/v1/admin/dead-letters/{queue}/replay",
+            post(replay_dead_letters),
+        )
+        .route(
+            "/api/v1/admin/dead-letters/{queue}/discard",
+            post(discard_dead_letters),
+        )
+}
+
+fn validate_queue(queue: &str) -> Result<(), (StatusCode, String)> {
+    DeadLetterService::validate_queue(queue)
+        .map(|_| ())
+        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))
+}
+
+/// Dead-letter queues with their message counts (Admin only)
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/dead-letters",
+    responses(
+        (status = 200, description = "Dead-letter queues retrieved", body = DeadLetterQueueListResponse),
+        (status = 403, description = "Forbidden - Admin only"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Dead Letters"
+)]
+pub async fn list_dead_letter_queues(
+    AuthClaims(auth_claims): AuthClaims,
+) -> Result<(StatusCode, Json<DeadLetterQueueListResponse>), (StatusCode, String)> {
+    permission::is_admin(&auth_claims)?;
+
+    let queues = DeadLetterService::list().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to list dead-letter queues: {}", e),
+        )
+    })?;
+
+    Ok((
+        StatusCode::OK,
+        Json(DeadLetterQueueListResponse {
+            queues: queues.into_iter().map(Into::into).collect(),
+        }),
+    ))
+}
+
+/// Inspect dead-lettered messages of a queue without removing them (Admin only)
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/dead-letters/{queue}",
+    params(
+        ("queue" = String, Path, description = "Work queue name"),
+        ("limit" = Option<usize>, Query, description = "Maximum messages to return (default: 50, max: 500)")
+    ),
+    responses(
+        (status = 200, description = "Dead letters retrieved", body = DeadLetterListResponse),
+        (status = 403, description = "Forbidden - Admin only"),
+        (status = 404, description = "Unknown queue"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Dead Letters"
+)]
+pub async fn get_dead_letters(
+    AuthClaims(auth_claims): AuthClaims,
+    Path(queue): Path<String>,
+    Query(params): Query<DeadLetterQueryParams>,
+) -> Result<(StatusCode, Json<DeadLetterListResponse>), (StatusCode, String)> {
+    permission::is_admin(&auth_claims)?;
+    validate_queue(&queue)?;
+
+    let messages = DeadLetterService::peek(&queue, params.limit.min(MAX_PEEK_LIMIT))
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read dead letters: {}", e),
+            )
+        })?;
+
+    Ok((
+        StatusCode::OK,
+        Json(DeadLetterListResponse {
+            queue,
+            messages: messages.into_iter().map(Into::into).collect(),
+        }),
+    ))
+}
+
+/// Send dead-lettered messages back to their work queue with a fresh retry budget (Admin only)
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/dead-letters/{queue}/replay",
+    request_body = ReplayDeadLettersRequest,
+    params(
+        ("queue" = String, Path, description = "Work queue name"),
+        ("X-Step-Up-Token" = Option<String>, Header, description = "Step-up token, required if MFA is enabled")
+    ),
+    responses(
+        (status = 200, description = "Dead letters replayed", body = DeadLetterActionResponse),
+        (status = 401, description = "Missing or expired step-up token", body = MfaErrorResponse),
+        (status = 403, description = "Forbidden - Admin only"),
+        (status = 404, description = "Unknown queue"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Dead Letters"
+)]
+pub async fn replay_dead_letters(
+    RequireRecentMfa(auth_claims): RequireRecentMfa,
+    Path(queue): Path<String>,
+    Json(payload): Json<ReplayDeadLettersRequest>,
+) -> Result<(StatusCode, Json<DeadLetterActionResponse>), (StatusCode, String)> {
+    permission::is_admin(&auth_claims)?;
+    validate_queue(&queue)?;
+
+    let selection = match payload.message_ids {
+        Some(ids) => Selection::Ids(ids),
+        None => Selection::All,
+    };
+    let processed = DeadLetterService::replay(&queue, selection)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to replay dead letters: {}", e),
+            )
+        })?;
+
+    tracing::info!(
+        "Admin {} replayed {} dead letters of {}",
+        auth_claims.user_id,
+        processed,
+        queue
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(DeadLetterActionResponse { queue, processed }),
+    ))
+}
+
+/// Permanently remove dead-lettered messages (Admin only)
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/dead-letters/{queue}/discard",
+    request_body = DiscardDeadLettersRequest,
+    params(
+        ("queue" = String, Path, description = "Work queue name"),
+        ("X-Step-Up-Token" = Option<String>, Header, description = "Step-up token, required if MFA is enabled")
+    ),
+    responses(
+        (status = 200, description = "Dead letters discarded", body = DeadLetterActionResponse),
+        (status = 400, description = "No message ids given"),
+        (status = 401, description = "Missing or expired step-up token", body = MfaErrorResponse),
+        (status = 403, description = "Forbidden - Admin only"),
+        (status = 404, description = "Unknown queue"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Dead Letters"
+)]
+pub async fn discard_dead_letters(
+    RequireRecentMfa(auth_claims): RequireRecentMfa,
+    Path(queue): Path<String>,
+    Json(payload): Json<DiscardDeadLettersRequest>,
+) -> Result<(StatusCode, Json<DeadLetterActionResponse>), (StatusCode, String)> {
+    permission::is_admin(&auth_claims)?;
+    validate_queue(&queue)?;
+
+    // Discarding is irreversible, so it never applies to a whole queue implicitly
+    if payload.message_ids.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "message_ids cannot be empty".to_string(),
+        ));
+    }
+
+    let processed = DeadLetterService::discard(&queue, Selection::Ids(payload.message_ids))
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to discard dead letters: {}", e),
+            )
+        })?;
+
+    tracing::warn!(
+        "Admin {} discarded {} dead letters of {}",
+        auth_claims.user_id,
+        processed,
+        queue
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(DeadLetterActionResponse { queue, processed }),
+    ))
+}
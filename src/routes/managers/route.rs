@@ -4,9 +4,10 @@ use super::dto::{
 };
 use crate::blockchain::{get_user_blockchain_service, get_user_private_key};
 use crate::extractor::AuthClaims;
-use crate::rabbitmq_service::consumers::RABBITMQ_CONNECTION;
 use crate::rabbitmq_service::rabbitmq_service::RabbitMQService;
-use crate::rabbitmq_service::structs::RemoveManagerMessage;
+use crate::rabbitmq_service::structs::{
+    MESSAGE_SCHEMA_VERSION, RemoveManagerMessage, new_idempotency_key,
+};
 use crate::repositories::UserRepository;
 use axum::{
     Json, Router,
@@ -72,26 +73,16 @@ pub async fn add_manager(
         })?
         .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
 
-    // All blockchain transactions use admin key to pay for gas
-    // Permission checking is handled in backend routes
-    let private_key = crate::config::APP_CONFIG.admin_private_key.clone();
-
     // Publish message to RabbitMQ
-    let rabbitmq_conn = RABBITMQ_CONNECTION.get().ok_or_else(|| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "RabbitMQ connection not initialized".to_string(),
-        )
-    })?;
-
     let message = crate::rabbitmq_service::structs::RegisterNewManagerMessage {
-        private_key,
+        schema_version: MESSAGE_SCHEMA_VERSION,
+        idempotency_key: Some(new_idempotency_key()),
         wallet_address: payload.manager_address.clone(),
         email: user.email.clone(),
         creator_user_id: auth_claims.user_id.clone(),
     };
 
-    RabbitMQService::publish_to_register_new_manager(rabbitmq_conn, message)
+    RabbitMQService::publish_to_register_new_manager(message)
         .await
         .map_err(|e| {
             (
@@ -156,26 +147,16 @@ pub async fn remove_manager(
         })?
         .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
 
-    // All blockchain transactions use admin key to pay for gas
-    // Permission checking is handled in backend routes
-    let private_key = crate::config::APP_CONFIG.admin_private_key.clone();
-
     // Publish message to RabbitMQ
-    let rabbitmq_conn = RABBITMQ_CONNECTION.get().ok_or_else(|| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "RabbitMQ connection not initialized".to_string(),
-        )
-    })?;
-
     let message = RemoveManagerMessage {
-        private_key,
+        schema_version: MESSAGE_SCHEMA_VERSION,
+        idempotency_key: Some(new_idempotency_key()),
         manager_address: payload.manager_address.clone(),
         email: user.email.clone(),
         creator_user_id: auth_claims.user_id.clone(),
     };
 
-    RabbitMQService::publish_to_remove_manager(rabbitmq_conn, message)
+    RabbitMQService::publish_to_remove_manager(message)
         .await
         .map_err(|e| {
             (
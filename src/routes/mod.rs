@@ -1,9 +1,11 @@
 pub mod auth;
+pub mod dead_letters;
 pub mod departments;
 pub mod documents;
 pub mod health;
 pub mod majors;
 pub mod managers;
+pub mod notifications;
 pub mod profile;
 pub mod requests;
 pub mod stats;
@@ -0,0 +1,47 @@
+use crate::entities::sea_orm_active_enums::NotificationKind;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationResponse {
+    pub notification_id: String,
+    pub kind: NotificationKind,
+    pub message: String,
+    /// Fields of the job the notification is about, such as `email` or `reason`
+    pub data: serde_json::Value,
+    pub read_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationListResponse {
+    pub notifications: Vec<NotificationResponse>,
+    pub total: u64,
+    pub unread_count: u64,
+    pub page: u32,
+    pub page_size: u32,
+    pub total_pages: u64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct NotificationQueryParams {
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_page_size")]
+    pub page_size: u32,
+    #[serde(default)]
+    pub unread_only: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MarkAllReadResponse {
+    pub updated: u64,
+}
+
+fn default_page() -> u32 {
+    1
+}
+
+fn default_page_size() -> u32 {
+    20
+}
@@ -0,0 +1,5 @@
+pub mod dto;
+pub mod route;
+
+pub use dto::*;
+pub use route::create_route;
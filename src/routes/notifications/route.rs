@@ -0,0 +1,177 @@
+use super::dto::{
+    MarkAllReadResponse, NotificationListResponse, NotificationQueryParams, NotificationResponse,
+};
+use crate::entities::notification;
+use crate::extractor::AuthClaims;
+use crate::repositories::NotificationRepository;
+use axum::{
+    Json, Router,
+    extract::{Path, Query},
+    http::StatusCode,
+    routing::{get, put},
+};
+use uuid::Uuid;
+
+pub fn create_route() -> Router {
+    Router::new()
+        .route("/api/v1/notifications", get(get_my_notifications))
+        .route(
+            "/api/v1/notifications/read-all",
+            put(mark_all_notifications_read),
+        )
+        .route(
+            "/api/v1/notifications/{notification_id}/read",
+            put(mark_notification_read),
+        )
+}
+
+fn to_response(n: notification::Model) -> NotificationResponse {
+    NotificationResponse {
+        notification_id: n.notification_id.to_string(),
+        kind: n.kind,
+        message: n.message,
+        data: n.data,
+        read_at: n.read_at.map(|d| d.to_string()),
+        created_at: n.created_at.to_string(),
+    }
+}
+
+fn parse_user_id(user_id: &str) -> Result<Uuid, (StatusCode, String)> {
+    Uuid::parse_str(user_id).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Invalid user_id: {}", e),
+        )
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/notifications",
+    params(
+        ("page" = Option<u32>, Query, description = "Page number (default: 1)"),
+        ("page_size" = Option<u32>, Query, description = "Page size (default: 20)"),
+        ("unread_only" = Option<bool>, Query, description = "Only unread notifications (default: false)")
+    ),
+    responses(
+        (status = 200, description = "Notifications retrieved successfully", body = NotificationListResponse),
+        (status = 500, description = "Internal server error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Notifications"
+)]
+pub async fn get_my_notifications(
+    AuthClaims(auth_claims): AuthClaims,
+    Query(params): Query<NotificationQueryParams>,
+) -> Result<(StatusCode, Json<NotificationListResponse>), (StatusCode, String)> {
+    let user_id = parse_user_id(&auth_claims.user_id)?;
+
+    let page = if params.page == 0 { 1 } else { params.page };
+    let page_size = if params.page_size == 0 || params.page_size > 100 {
+        20
+    } else {
+        params.page_size
+    };
+
+    let notification_repo = NotificationRepository::new();
+    let (notifications, total) = notification_repo
+        .find_by_user_with_pagination(user_id, params.unread_only, page, page_size)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to get notifications: {}", e),
+            )
+        })?;
+    let unread_count = notification_repo.count_unread(user_id).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to count unread notifications: {}", e),
+        )
+    })?;
+
+    let total_pages = (total as f64 / page_size as f64).ceil() as u64;
+
+    Ok((
+        StatusCode::OK,
+        Json(NotificationListResponse {
+            notifications: notifications.into_iter().map(to_response).collect(),
+            total,
+            unread_count,
+            page,
+            page_size,
+            total_pages,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/notifications/{notification_id}/read",
+    params(
+        ("notification_id" = String, Path, description = "Notification ID")
+    ),
+    responses(
+        (status = 200, description = "Notification marked as read", body = NotificationResponse),
+        (status = 400, description = "Invalid notification ID"),
+        (status = 404, description = "Notification not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Notifications"
+)]
+pub async fn mark_notification_read(
+    AuthClaims(auth_claims): AuthClaims,
+    Path(notification_id): Path<String>,
+) -> Result<(StatusCode, Json<NotificationResponse>), (StatusCode, String)> {
+    let user_id = parse_user_id(&auth_claims.user_id)?;
+
+    let notification_uuid = Uuid::parse_str(&notification_id).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid notification_id: {}", e),
+        )
+    })?;
+
+    // Scoped to the caller, so other users' notifications are reported as missing
+    let notification = NotificationRepository::new()
+        .mark_read(user_id, notification_uuid)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to mark notification as read: {}", e),
+            )
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "Notification not found".to_string()))?;
+
+    Ok((StatusCode::OK, Json(to_response(notification))))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/notifications/read-all",
+    responses(
+        (status = 200, description = "All notifications marked as read", body = MarkAllReadResponse),
+        (status = 500, description = "Internal server error")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Notifications"
+)]
+pub async fn mark_all_notifications_read(
+    AuthClaims(auth_claims): AuthClaims,
+) -> Result<(StatusCode, Json<MarkAllReadResponse>), (StatusCode, String)> {
+    let user_id = parse_user_id(&auth_claims.user_id)?;
+
+    let updated = NotificationRepository::new()
+        .mark_all_read(user_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to mark notifications as read: {}", e),
+            )
+        })?;
+
+    Ok((StatusCode::OK, Json(MarkAllReadResponse { updated })))
+}
@@ -5,7 +5,6 @@ use utoipa::ToSchema;
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateRequestRequest {
     pub content: String,
-    pub authenticator_code: Option<String>, // Required if MFA is enabled
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -21,9 +20,8 @@ pub struct RequestResponse {
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ScheduleRequestRequest {
-    pub scheduled_at: String,               // Format: YYYY-MM-DDTHH:MM:SS
-    pub message: Option<String>,            // Optional message to include in email
-    pub authenticator_code: Option<String>, // Required if MFA is enabled
+    pub scheduled_at: String,     // Format: YYYY-MM-DDTHH:MM:SS
+    pub message: Option<String>,  // Optional message to include in email
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
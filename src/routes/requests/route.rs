@@ -2,12 +2,13 @@ use super::dto::{
     CreateRequestRequest, RequestListResponse, RequestQueryParams, RequestResponse,
     ScheduleRequestRequest, ScheduleRequestResponse,
 };
+use crate::email_service::EmailService;
+use crate::email_service::template::EmailTemplate;
 use crate::entities::sea_orm_active_enums::RequestStatus;
-use crate::extractor::AuthClaims;
-use crate::rabbitmq_service::consumers::get_rabbitmq_connetion;
-use crate::rabbitmq_service::rabbitmq_service::RabbitMQService;
-use crate::repositories::mfa_verify_result::MfaVerifyResult;
-use crate::repositories::{RequestRepository, UserMfaRepository, UserRepository};
+use crate::extractor::{AuthClaims, MfaErrorResponse, RequireRecentMfa};
+use crate::redis_service::redis_emitter::RedisEmitter;
+use crate::redis_service::socket_events::{RequestScheduledPayload, SocketEvent, user_room};
+use crate::repositories::{RequestRepository, UserRepository};
 use axum::{
     Json, Router,
     extract::{Path, Query},
@@ -34,16 +35,20 @@ pub fn create_route() -> Router {
     post,
     path = "/api/v1/requests",
     request_body = CreateRequestRequest,
+    params(
+        ("X-Step-Up-Token" = Option<String>, Header, description = "Step-up token, required if MFA is enabled")
+    ),
     responses(
         (status = 201, description = "Request created successfully", body = RequestResponse),
         (status = 400, description = "Bad request"),
+        (status = 401, description = "Missing or expired step-up token", body = MfaErrorResponse),
         (status = 500, description = "Internal server error")
     ),
     security(("bearer_auth" = [])),
     tag = "Requests"
 )]
 pub async fn create_request(
-    AuthClaims(auth_claims): AuthClaims,
+    RequireRecentMfa(auth_claims): RequireRecentMfa,
     Json(payload): Json<CreateRequestRequest>,
 ) -> Result<(StatusCode, Json<RequestResponse>), (StatusCode, String)> {
     let user_id = uuid::Uuid::parse_str(&auth_claims.user_id).map_err(|e| {
@@ -60,70 +65,6 @@ pub async fn create_request(
         ));
     }
 
-    // Check if user has MFA enabled and verify code
-    let mfa_repo = UserMfaRepository::new();
-    let mfa_enabled = mfa_repo
-        .find_enabled_by_user_id(user_id)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to check MFA status: {}", e),
-            )
-        })?;
-
-    // If MFA is enabled, verify the authenticator code
-    if let Some(_mfa_record) = mfa_enabled {
-        let authenticator_code = payload.authenticator_code.ok_or_else(|| {
-            (
-                StatusCode::BAD_REQUEST,
-                "MFA is enabled. Please provide authenticator_code".to_string(),
-            )
-        })?;
-
-        let verify_result = mfa_repo
-            .verify_mfa_code(&auth_claims.user_id, &authenticator_code)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to verify MFA code: {}", e),
-                )
-            })?;
-
-        match verify_result {
-            MfaVerifyResult::Success => {
-                // Continue with request creation
-            }
-            MfaVerifyResult::Locked { locked_until } => {
-                let message = if let Some(until) = locked_until {
-                    format!("MFA is locked until {} (too many failed attempts)", until)
-                } else {
-                    "MFA is locked due to too many failed attempts".to_string()
-                };
-                return Err((StatusCode::FORBIDDEN, message));
-            }
-            MfaVerifyResult::CodeAlreadyUsed => {
-                return Err((
-                    StatusCode::UNAUTHORIZED,
-                    "MFA code has already been used".to_string(),
-                ));
-            }
-            MfaVerifyResult::InvalidCode => {
-                return Err((
-                    StatusCode::UNAUTHORIZED,
-                    "Invalid authenticator code".to_string(),
-                ));
-            }
-            MfaVerifyResult::MfaNotEnabled => {
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    "MFA is not enabled for this user".to_string(),
-                ));
-            }
-        }
-    }
-
     let request_repo = RequestRepository::new();
     let request = request_repo
         .create(user_id, payload.content)
@@ -210,9 +151,14 @@ pub async fn get_my_requests(
     post,
     path = "/api/v1/requests/{request_id}/schedule",
     request_body = ScheduleRequestRequest,
+    params(
+        ("request_id" = String, Path, description = "Request ID"),
+        ("X-Step-Up-Token" = Option<String>, Header, description = "Step-up token, required if MFA is enabled")
+    ),
     responses(
-        (status = 200, description = "Request scheduled and email sent successfully", body = ScheduleRequestResponse),
+        (status = 200, description = "Request scheduled, email sent in the background", body = ScheduleRequestResponse),
         (status = 400, description = "Bad request"),
+        (status = 401, description = "Missing or expired step-up token", body = MfaErrorResponse),
         (status = 403, description = "Forbidden - Manager only"),
         (status = 404, description = "Request not found"),
         (status = 500, description = "Internal server error")
@@ -221,7 +167,7 @@ pub async fn get_my_requests(
     tag = "Requests"
 )]
 pub async fn schedule_request(
-    AuthClaims(auth_claims): AuthClaims,
+    RequireRecentMfa(auth_claims): RequireRecentMfa,
     Path(request_id): Path<String>,
     Json(payload): Json<ScheduleRequestRequest>,
 ) -> Result<(StatusCode, Json<ScheduleRequestResponse>), (StatusCode, String)> {
@@ -240,70 +186,6 @@ pub async fn schedule_request(
         )
     })?;
 
-    // Check if manager has MFA enabled and verify code
-    let mfa_repo = UserMfaRepository::new();
-    let mfa_enabled = mfa_repo
-        .find_enabled_by_user_id(manager_user_id)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to check MFA status: {}", e),
-            )
-        })?;
-
-    // If MFA is enabled, verify the authenticator code
-    if let Some(_mfa_record) = mfa_enabled {
-        let authenticator_code = payload.authenticator_code.ok_or_else(|| {
-            (
-                StatusCode::BAD_REQUEST,
-                "MFA is enabled. Please provide authenticator_code".to_string(),
-            )
-        })?;
-
-        let verify_result = mfa_repo
-            .verify_mfa_code(&auth_claims.user_id, &authenticator_code)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to verify MFA code: {}", e),
-                )
-            })?;
-
-        match verify_result {
-            MfaVerifyResult::Success => {
-                // Continue with scheduling
-            }
-            MfaVerifyResult::Locked { locked_until } => {
-                let message = if let Some(until) = locked_until {
-                    format!("MFA is locked until {} (too many failed attempts)", until)
-                } else {
-                    "MFA is locked due to too many failed attempts".to_string()
-                };
-                return Err((StatusCode::FORBIDDEN, message));
-            }
-            MfaVerifyResult::CodeAlreadyUsed => {
-                return Err((
-                    StatusCode::UNAUTHORIZED,
-                    "MFA code has already been used".to_string(),
-                ));
-            }
-            MfaVerifyResult::InvalidCode => {
-                return Err((
-                    StatusCode::UNAUTHORIZED,
-                    "Invalid authenticator code".to_string(),
-                ));
-            }
-            MfaVerifyResult::MfaNotEnabled => {
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    "MFA is not enabled for this user".to_string(),
-                ));
-            }
-        }
-    }
-
     let request_uuid = uuid::Uuid::parse_str(&request_id).map_err(|e| {
         (
             StatusCode::BAD_REQUEST,
@@ -359,31 +241,28 @@ pub async fn schedule_request(
         })?
         .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
 
-    // Send email via RabbitMQ
-    let rabbitmq_conn = get_rabbitmq_connetion().await;
-    let email_subject = "Lịch hẹn xử lý yêu cầu";
-    let email_body = format!(
-        "Xin chào {},\n\nYêu cầu của bạn đã được lên lịch xử lý vào thời gian: {}\n\nNội dung yêu cầu: {}\n\n{}\n\nTrân trọng,\nHệ thống quản lý",
-        user.first_name,
-        scheduled_at.format("%d/%m/%Y %H:%M"),
-        request.content,
-        payload.message.as_deref().unwrap_or("")
-    );
+    let event = SocketEvent::RequestScheduled(RequestScheduledPayload {
+        request_id: updated_request.request_id,
+        scheduled_at,
+        note: payload.message.clone(),
+    });
+    RedisEmitter::emit_in_background(user_room(user.user_id), event);
+
+    // The request owner is not the caller, so their language is unknown
+    let email = EmailTemplate::RequestScheduled {
+        first_name: user.first_name.clone(),
+        scheduled_at,
+        request_content: request.content.clone(),
+        note: payload.message.clone(),
+    };
 
-    RabbitMQService::publish_to_mail_queue(rabbitmq_conn, &user.email, email_subject, &email_body)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to send email: {}", e),
-            )
-        })?;
+    EmailService::send_in_background(user.email.clone(), email, EmailService::default_locale());
 
     Ok((
         StatusCode::OK,
         Json(ScheduleRequestResponse {
             success: true,
-            message: "Request scheduled and email sent successfully".to_string(),
+            message: "Request scheduled, the user is being notified by email".to_string(),
             request: RequestResponse {
                 request_id: updated_request.request_id.to_string(),
                 user_id: updated_request.user_id.to_string(),
@@ -11,9 +11,10 @@ use super::dto::{
 };
 use crate::blockchain::{get_user_blockchain_service, get_user_private_key};
 use crate::extractor::AuthClaims;
-use crate::rabbitmq_service::consumers::RABBITMQ_CONNECTION;
 use crate::rabbitmq_service::rabbitmq_service::RabbitMQService;
-use crate::rabbitmq_service::structs::{ActivateStudentMessage, DeactivateStudentMessage};
+use crate::rabbitmq_service::structs::{
+    ActivateStudentMessage, DeactivateStudentMessage, MESSAGE_SCHEMA_VERSION, new_idempotency_key,
+};
 use crate::repositories::UserRepository;
 use do_an_lib::structs::token_claims::UserRole;
 
@@ -248,26 +249,16 @@ pub async fn deactivate_student(
         })?
         .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
 
-    // All blockchain transactions use admin key to pay for gas
-    // Permission checking is handled in backend routes
-    let private_key = crate::config::APP_CONFIG.admin_private_key.clone();
-
     // Publish message to RabbitMQ
-    let rabbitmq_conn = RABBITMQ_CONNECTION.get().ok_or_else(|| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "RabbitMQ connection not initialized".to_string(),
-        )
-    })?;
-
     let message = DeactivateStudentMessage {
-        private_key,
+        schema_version: MESSAGE_SCHEMA_VERSION,
+        idempotency_key: Some(new_idempotency_key()),
         student_id,
         email: user.email.clone(),
         creator_user_id: auth_claims.user_id.clone(),
     };
 
-    RabbitMQService::publish_to_deactivate_student(rabbitmq_conn, message)
+    RabbitMQService::publish_to_deactivate_student(message)
         .await
         .map_err(|e| {
             (
@@ -333,26 +324,16 @@ pub async fn activate_student(
         })?
         .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
 
-    // All blockchain transactions use admin key to pay for gas
-    // Permission checking is handled in backend routes
-    let private_key = crate::config::APP_CONFIG.admin_private_key.clone();
-
     // Publish message to RabbitMQ
-    let rabbitmq_conn = RABBITMQ_CONNECTION.get().ok_or_else(|| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "RabbitMQ connection not initialized".to_string(),
-        )
-    })?;
-
     let message = ActivateStudentMessage {
-        private_key,
+        schema_version: MESSAGE_SCHEMA_VERSION,
+        idempotency_key: Some(new_idempotency_key()),
         student_id,
         email: user.email.clone(),
         creator_user_id: auth_claims.user_id.clone(),
     };
 
-    RabbitMQService::publish_to_activate_student(rabbitmq_conn, message)
+    RabbitMQService::publish_to_activate_student(message)
         .await
         .map_err(|e| {
             (
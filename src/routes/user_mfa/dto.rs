@@ -1,3 +1,4 @@
+use crate::middleware::mfa_policy::MfaPolicyState;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -69,4 +70,28 @@ pub struct VerifyMfaCodeTestResponseDto {
 pub struct MfaStatusResponseDto {
     pub is_enabled: bool,
     pub message: Option<String>,
+    pub policy_applies: bool,
+    pub policy: MfaPolicyState,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedDeviceResponseDto {
+    pub device_id: String,
+    pub label: String,
+    pub created_at: i64,
+    pub last_used_at: i64,
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedDeviceListResponseDto {
+    pub devices: Vec<TrustedDeviceResponseDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokeTrustedDeviceResponseDto {
+    pub message: String,
 }
@@ -1,15 +1,26 @@
-use crate::config::{APP_CONFIG, OTP_ISSUER};
+use crate::config::{APP_CONFIG, MFA_ENABLE_OTP_EXPIRES_IN_MINUTES, OTP_ISSUER};
+use crate::email_service::EmailService;
+use crate::email_service::template::EmailTemplate;
 use crate::extractor::AuthClaims;
-use crate::rabbitmq_service::rabbitmq_service::RabbitMQService;
+use crate::key_provider::get_key_provider;
+use crate::mfa_service::MfaService;
+use crate::middleware::mfa_policy::{MfaPolicy, MfaPolicyState};
 use crate::repositories::{OtpVerifyRepository, UserMfaRepository, UserRepository};
+use crate::redis_service::redis_service::TrustedDeviceService;
 use crate::routes::user_mfa::dto::{
     EnableMfaRequestDto, EnableMfaResponseDto, MfaStatusResponseDto, ReqEnableMfaResponseDto,
+    RevokeTrustedDeviceResponseDto, TrustedDeviceListResponseDto, TrustedDeviceResponseDto,
     VerifyMfaCodeTestRequestDto, VerifyMfaCodeTestResponseDto,
 };
-use crate::utils::encryption::encrypt;
+use crate::utils::envelope_encryption::CONTEXT_MFA_SECRET;
 use crate::utils::gen_otp_code::gen_code;
 use anyhow::Context;
-use axum::{Json, Router, http::StatusCode, routing::{get, post}};
+use axum::{
+    Json, Router,
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get, post},
+};
 use chrono::{Duration, Utc};
 use google_authenticator::GoogleAuthenticator;
 use urlencoding::encode;
@@ -21,6 +32,14 @@ pub fn create_route() -> Router {
         .route("/api/v1/user-mfa/enable", post(req_enable_mfa))
         .route("/api/v1/user-mfa/enable-mfa", post(enable_mfa))
         .route("/api/v1/user-mfa/verify", post(verify_mfa_code_test))
+        .route(
+            "/api/v1/user-mfa/trusted-devices",
+            get(get_trusted_devices).delete(revoke_all_trusted_devices),
+        )
+        .route(
+            "/api/v1/user-mfa/trusted-devices/{device_id}",
+            delete(revoke_trusted_device),
+        )
 }
 
 #[utoipa::path(
@@ -53,13 +72,44 @@ pub async fn get_mfa_status(
         .map(|mfa| mfa.is_enabled)
         .unwrap_or(false);
 
+    let user_info = UserRepository::new()
+        .find_by_id(user_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to query database: {}", e),
+            )
+        })?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
+
+    let policy = MfaPolicy::from_config()
+        .evaluate_user(&user_info, is_enabled)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to evaluate MFA policy: {}", e),
+            )
+        })?;
+
+    let message = match &policy {
+        _ if is_enabled => "MFA is enabled".to_string(),
+        MfaPolicyState::GracePeriod { deadline } => format!(
+            "MFA is required for your role. Please enable MFA before {}",
+            deadline
+        ),
+        MfaPolicyState::EnrollmentRequired => {
+            "MFA is required for your role. Please enable MFA to continue.".to_string()
+        }
+        _ => "MFA is not enabled. Please enable MFA to use this feature.".to_string(),
+    };
+
     let response = MfaStatusResponseDto {
         is_enabled,
-        message: if is_enabled {
-            Some("MFA is enabled".to_string())
-        } else {
-            Some("MFA is not enabled. Please enable MFA to use this feature.".to_string())
-        },
+        message: Some(message),
+        policy_applies: policy.policy_applies(),
+        policy,
     };
 
     Ok((StatusCode::OK, Json(response)))
@@ -81,6 +131,7 @@ pub async fn get_mfa_status(
 #[axum::debug_handler]
 pub async fn req_enable_mfa(
     AuthClaims(claims): AuthClaims,
+    headers: HeaderMap,
 ) -> Result<(StatusCode, Json<ReqEnableMfaResponseDto>), (StatusCode, String)> {
     let user_repo = UserRepository::new();
     let mfa_repo = UserMfaRepository::new();
@@ -125,7 +176,8 @@ pub async fn req_enable_mfa(
         )
     })?;
 
-    let expires_at = Utc::now().naive_utc() + Duration::minutes(5);
+    let expires_at =
+        Utc::now().naive_utc() + Duration::minutes(MFA_ENABLE_OTP_EXPIRES_IN_MINUTES);
 
     otp_repo
         .create(
@@ -144,26 +196,16 @@ pub async fn req_enable_mfa(
             )
         })?;
 
-    let email_body = format!(
-        "Your OTP code to enable MFA is: {:?}. This code will expire in 10 minutes.",
-        otp_code
-    );
+    let email = EmailTemplate::EnableMfa {
+        otp_code,
+        expires_in_minutes: MFA_ENABLE_OTP_EXPIRES_IN_MINUTES,
+    };
 
-    let rabbit_mq_connection = RabbitMQService::new().await;
-    RabbitMQService::publish_to_mail_queue(
-        &rabbit_mq_connection,
-        &user_info.email,
-        "Enable MFA - Verification Code",
-        &email_body,
-    )
-    .await
-    .context("Failed to send email")
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to send email: {}", e),
-        )
-    })?;
+    EmailService::send_in_background(
+        user_info.email.clone(),
+        email,
+        EmailService::locale_from_headers(&headers),
+    );
 
     let response = ReqEnableMfaResponseDto {
         message: "Check your email to enable mfa".to_string(),
@@ -242,12 +284,22 @@ pub async fn enable_mfa(
         OTP_ISSUER
     );
 
-    let encode_secret = encrypt(&APP_CONFIG.encryption_key, &secret).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to encrypt secret: {}", e),
-        )
-    })?;
+    let encode_secret = get_key_provider()
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to load key provider: {}", e),
+            )
+        })?
+        .encrypt(&secret, CONTEXT_MFA_SECRET)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to encrypt secret: {}", e),
+            )
+        })?;
 
     // Mark OTP as verified before enabling MFA to prevent reuse
     otp_repo
@@ -301,56 +353,24 @@ pub async fn verify_mfa_code_test(
     AuthClaims(claims): AuthClaims,
     Json(body): Json<VerifyMfaCodeTestRequestDto>,
 ) -> Result<(StatusCode, Json<VerifyMfaCodeTestResponseDto>), (StatusCode, String)> {
-    let mfa_repo = UserMfaRepository::new();
-
-    use crate::repositories::mfa_verify_result::MfaVerifyResult;
-
-    let result = mfa_repo
-        .verify_mfa_code(&claims.user_id, &body.authenticator_code)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to verify MFA code: {}", e),
-            )
-        })?;
+    let result = MfaService::new()
+        .verify(&claims.user_id, &body.authenticator_code)
+        .await;
 
     let (is_valid, message, reason, locked_until) = match result {
-        MfaVerifyResult::Success => (
+        Ok(()) => (
             true,
             "MFA code verified successfully".to_string(),
             "success".to_string(),
             None,
         ),
-        MfaVerifyResult::Locked { locked_until } => (
-            false,
-            format!(
-                "MFA is locked{}",
-                locked_until
-                    .map(|u| format!(" until {}", u))
-                    .unwrap_or_default()
-            ),
-            "locked".to_string(),
-            locked_until,
-        ),
-        MfaVerifyResult::CodeAlreadyUsed => (
+        Err(e) if e.is_rejection() => (
             false,
-            "MFA code has already been used".to_string(),
-            "code_already_used".to_string(),
-            None,
-        ),
-        MfaVerifyResult::InvalidCode => (
-            false,
-            "Invalid MFA code".to_string(),
-            "invalid_code".to_string(),
-            None,
-        ),
-        MfaVerifyResult::MfaNotEnabled => (
-            false,
-            "MFA is not enabled for this user".to_string(),
-            "mfa_not_enabled".to_string(),
-            None,
+            e.to_string(),
+            e.reason().to_string(),
+            e.locked_until(),
         ),
+        Err(e) => return Err(e.into()),
     };
 
     let response = VerifyMfaCodeTestResponseDto {
@@ -362,3 +382,114 @@ pub async fn verify_mfa_code_test(
 
     Ok((StatusCode::OK, Json(response)))
 }
+
+#[utoipa::path(
+    get,
+    tag = "security-settings",
+    path = "/api/v1/user-mfa/trusted-devices",
+    responses(
+        (status = 200, description = "Trusted devices retrieved successfully", body = TrustedDeviceListResponseDto),
+        (status = 500, description = "Internal server error"),
+    ),
+    security(("bearer_auth" = []))
+)]
+#[axum::debug_handler]
+pub async fn get_trusted_devices(
+    AuthClaims(claims): AuthClaims,
+) -> Result<(StatusCode, Json<TrustedDeviceListResponseDto>), (StatusCode, String)> {
+    let devices = TrustedDeviceService::list_devices(&claims.user_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to get trusted devices: {}", e),
+            )
+        })?;
+
+    let response = TrustedDeviceListResponseDto {
+        devices: devices
+            .into_iter()
+            .map(|device| TrustedDeviceResponseDto {
+                device_id: device.device_id,
+                label: device.label,
+                created_at: device.created_at,
+                last_used_at: device.last_used_at,
+                expires_at: device.expires_at,
+            })
+            .collect(),
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "security-settings",
+    path = "/api/v1/user-mfa/trusted-devices/{device_id}",
+    params(
+        ("device_id" = String, Path, description = "Trusted device ID")
+    ),
+    responses(
+        (status = 200, description = "Trusted device revoked", body = RevokeTrustedDeviceResponseDto),
+        (status = 404, description = "Trusted device not found"),
+        (status = 500, description = "Internal server error"),
+    ),
+    security(("bearer_auth" = []))
+)]
+#[axum::debug_handler]
+pub async fn revoke_trusted_device(
+    AuthClaims(claims): AuthClaims,
+    Path(device_id): Path<String>,
+) -> Result<(StatusCode, Json<RevokeTrustedDeviceResponseDto>), (StatusCode, String)> {
+    let revoked = TrustedDeviceService::revoke_device(&claims.user_id, &device_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to revoke trusted device: {}", e),
+            )
+        })?;
+
+    if !revoked {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Trusted device not found".to_string(),
+        ));
+    }
+
+    let response = RevokeTrustedDeviceResponseDto {
+        message: "Trusted device revoked successfully".to_string(),
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "security-settings",
+    path = "/api/v1/user-mfa/trusted-devices",
+    responses(
+        (status = 200, description = "All trusted devices revoked", body = RevokeTrustedDeviceResponseDto),
+        (status = 500, description = "Internal server error"),
+    ),
+    security(("bearer_auth" = []))
+)]
+#[axum::debug_handler]
+pub async fn revoke_all_trusted_devices(
+    AuthClaims(claims): AuthClaims,
+) -> Result<(StatusCode, Json<RevokeTrustedDeviceResponseDto>), (StatusCode, String)> {
+    TrustedDeviceService::revoke_all_devices(&claims.user_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to revoke trusted devices: {}", e),
+            )
+        })?;
+
+    let response = RevokeTrustedDeviceResponseDto {
+        message: "All trusted devices revoked successfully".to_string(),
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
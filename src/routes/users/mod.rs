@@ -1,4 +1,5 @@
 pub mod dto;
 pub mod route;
+pub mod service;
 
 pub use route::create_route;
@@ -4,12 +4,11 @@ use axum::{
     http::StatusCode,
     routing::{get, post},
 };
-use chrono::Utc;
 use do_an_lib::structs::token_claims::UserRole;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
 use std::fs::File;
 use tokio::task;
 use uuid::Uuid;
+use sea_orm::TransactionTrait;
 use serde::Serialize;
 use utoipa::ToSchema;
 
@@ -17,25 +16,20 @@ use super::dto::{
     BulkUserResponse, CreateUserRequest, CreateUserRequestBulk, UpdateUserRequest, UserCsvColumn,
     UserDetailResponse, UserListResponse, UserQueryParams, UserResponse,
 };
-use crate::blockchain::{BlockchainService, get_user_private_key};
-use crate::config::APP_CONFIG;
+use super::service::UserService;
 use crate::entities::sea_orm_active_enums::RoleEnum;
-use crate::entities::{major, user_major};
 use crate::extractor::AuthClaims;
-use crate::middleware::permission;
-use crate::rabbitmq_service::consumers::RABBITMQ_CONNECTION;
-use crate::rabbitmq_service::rabbitmq_service::RabbitMQService;
+use crate::rabbitmq_service::topology::{Priority, REGISTER_NEW_USER_CHANNEL};
+use crate::rabbitmq_service::outbox::OutboxRelay;
 use crate::rabbitmq_service::structs::{
-    AssignRoleMessage, DeactivateStudentMessage, RegisterNewManagerMessage, RegisterNewUserMessage,
-    RemoveManagerMessage,
+    MESSAGE_SCHEMA_VERSION, RegisterNewUserMessage, new_idempotency_key,
 };
 use crate::redis_service::redis_service::{
     helper_get_blockchain_registration_progress, helper_get_current_file_progress,
-    BlockchainRegistrationProgress, FileHandleTrackProgress,
+    BlockchainRegistrationProgress,
 };
 use crate::repositories::file_upload_repository::FileUploadRepository;
-use crate::repositories::{UserRepository, WalletRepository, user_repository::UserUpdate};
-use crate::utils::encryption::encrypt_private_key;
+use crate::repositories::{OutboxRepository, UserRepository};
 
 #[derive(Debug, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -59,28 +53,6 @@ pub struct CreateUserProgressResponse {
     pub failed: u64,
 }
 
-async fn fetch_major_names(
-    db: &DatabaseConnection,
-    major_ids: &[Uuid],
-) -> Result<Vec<String>, (StatusCode, String)> {
-    if major_ids.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    let majors = major::Entity::find()
-        .filter(major::Column::MajorId.is_in(major_ids.to_vec()))
-        .all(db)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Database error: {}", e),
-            )
-        })?;
-
-    Ok(majors.into_iter().map(|m| m.name).collect())
-}
-
 pub fn create_route() -> Router {
     Router::new()
         .route("/api/v1/users", post(create_user).get(get_all_users))
@@ -120,240 +92,7 @@ pub async fn create_user(
     AuthClaims(auth_claims): AuthClaims,
     Json(payload): Json<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<UserResponse>), (StatusCode, String)> {
-    if auth_claims.role != UserRole::ADMIN && auth_claims.role != UserRole::MANAGER {
-        return Err((
-            StatusCode::FORBIDDEN,
-            "Only admin or manager can create users".to_string(),
-        ));
-    }
-
-    if auth_claims.role == UserRole::MANAGER && payload.role != RoleEnum::Student {
-        return Err((
-            StatusCode::FORBIDDEN,
-            "Managers can only create student accounts".to_string(),
-        ));
-    }
-
-    if auth_claims.role != UserRole::ADMIN 
-        && (payload.role == RoleEnum::Admin || payload.role == RoleEnum::Manager) {
-        return Err((
-            StatusCode::FORBIDDEN,
-            "Only admin can create manager or admin accounts".to_string(),
-        ));
-    }
-
-    let user_repo = UserRepository::new();
-    
-    // Check if email is already used by a user with Sync status
-    if user_repo.is_email_used_by_sync_user(&payload.email).await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to check email: {}", e),
-        )
-    })? {
-        return Err((
-            StatusCode::CONFLICT,
-            format!("Email {} is already used by an active account", payload.email),
-        ));
-    }
-    let wallet_repo = WalletRepository::new();
-    let user_uuid = Uuid::parse_str(&auth_claims.user_id).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Invalid user_id: {}", e),
-        )
-    })?;
-    // All blockchain transactions use admin key to pay for gas
-    // Permission checking is handled in backend routes
-    let user_private_key = APP_CONFIG.admin_private_key.clone();
-
-    let hashed_password = bcrypt::hash(&payload.password, bcrypt::DEFAULT_COST).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to hash password: {}", e),
-        )
-    })?;
-
-    let (wallet_address, wallet_private_key) =
-        BlockchainService::generate_wallet().map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to generate wallet: {}", e),
-            )
-        })?;
-
-    
-    let encrypted_private_key =
-        encrypt_private_key(&wallet_private_key, &APP_CONFIG.encryption_key).map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to encrypt private key: {}", e),
-            )
-        })?;
-
-    let user_id = Uuid::new_v4();
-    let wallet_id = Uuid::new_v4();
-
-    let student_code = if payload.role == RoleEnum::Student {
-        let lastest_student_code = UserRepository::get_latest_student_code()
-            .await
-            .expect("Failed to get student code");
-        let student_code_i64 = lastest_student_code.parse::<i64>().unwrap_or_default();
-        let student_code = student_code_i64 + 1;
-        let formated_student_code: String = format!("{:06}", student_code);
-        Some(formated_student_code)
-    } else {
-        None
-    };
-
-    let user = user_repo
-        .create(
-            user_id,
-            payload.first_name.clone(),
-            payload.last_name.clone(),
-            payload.address.clone(),
-            payload.email.clone(),
-            hashed_password,
-            payload.cccd.clone(),
-            payload.phone_number.clone(),
-            payload.role.clone(),
-            false,
-            student_code.clone(),
-        )
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to create user: {}", e),
-            )
-        })?;
-
-    wallet_repo
-        .create(
-            wallet_id,
-            user_id,
-            wallet_address.clone(),
-            encrypted_private_key.clone(),
-            APP_CONFIG.chain_type.clone(),
-            wallet_address.clone(),
-            "active".to_string(),
-            APP_CONFIG.chain_id.clone(),
-        )
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to create wallet: {}", e),
-            )
-        })?;
-
-    match payload.role {
-        RoleEnum::Student => {
-            let full_name = format!("{} {}", payload.first_name, payload.last_name);
-
-            let rabbit_mq_conn = RABBITMQ_CONNECTION
-                .get()
-                .expect("Failed to get rabbitmq connection");
-
-            let register_user_msg = RegisterNewUserMessage {
-                private_key: user_private_key,
-                wallet_address: wallet_address.clone(),
-                student_code: student_code.unwrap_or_default(),
-                full_name,
-                email: payload.email,
-                creator_user_id: auth_claims.user_id.clone(),
-                file_upload_history_id: None,
-            };
-            RabbitMQService::publish_to_register_new_user(rabbit_mq_conn, register_user_msg)
-                .await
-                .map_err(|e| tracing::error!("Failed to publish to register new user: {e}"))
-                .ok();
-        }
-
-        RoleEnum::Manager => {
-            let rabbit_mq_conn = RABBITMQ_CONNECTION
-                .get()
-                .expect("Failed to get rabbitmq connection");
-
-            let register_new_manager = RegisterNewManagerMessage {
-                private_key: user_private_key,
-                wallet_address: wallet_address.clone(),
-                email: payload.email,
-                creator_user_id: auth_claims.user_id.clone(),
-            };
-
-            RabbitMQService::publish_to_register_new_manager(rabbit_mq_conn, register_new_manager)
-                .await
-                .map_err(|e| tracing::error!("Failed to publish to register new manager: {e}"))
-                .ok();
-        }
-
-        RoleEnum::Teacher | RoleEnum::Admin => {
-            let role_code = match payload.role {
-                RoleEnum::Admin => 3,
-                RoleEnum::Teacher => 2,
-                _ => 0,
-            };
-
-            let rabbit_mq_conn = RABBITMQ_CONNECTION.get().ok_or_else(|| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "RabbitMQ connection not initialized".to_string(),
-                )
-            })?;
-
-            let assign_role_msg = AssignRoleMessage {
-                private_key: user_private_key,
-                user_address: wallet_address.clone(),
-                role: role_code,
-                email: payload.email.clone(),
-                creator_user_id: auth_claims.user_id.clone(),
-            };
-
-            RabbitMQService::publish_to_assign_role(rabbit_mq_conn, assign_role_msg)
-                .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("Failed to publish assign role message: {}", e),
-                    )
-                })?;
-        }
-    }
-
-
-    if let Some(major_ids) = payload.major_ids {
-        let db = user_repo.get_connection();
-        let now = Utc::now().naive_utc();
-        for major_id in major_ids.iter() {
-            let relationship_model = user_major::ActiveModel {
-                user_id: Set(user_id),
-                major_id: Set(*major_id),
-                create_at: Set(now),
-                updated_at: Set(now),
-            };
-
-            relationship_model.insert(db).await.map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to create user-major relationship: {}", e),
-                )
-            })?;
-        }
-    }
-
-    let response = UserResponse {
-        user_id: user.user_id,
-        first_name: user.first_name,
-        last_name: user.last_name,
-        email: user.email,
-        role: user.role,
-        wallet_address: wallet_address.clone(),
-        wallet_private_key: encrypted_private_key,
-        is_first_login: user.is_first_login,
-        created_at: user.create_at,
-    };
-
+    let response = UserService::create_user(&auth_claims, payload).await?;
     Ok((StatusCode::CREATED, Json(response)))
 }
 
@@ -364,180 +103,18 @@ pub async fn create_user(
     responses(
         (status = 201, description = "Bulk user creation completed", body = BulkUserResponse),
         (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden - Admin/Manager only"),
         (status = 500, description = "Internal server error")
     ),
     security(("bearer_auth" = [])),
     tag = "Users"
 )]
 pub async fn create_users_bulk(
-    AuthClaims(_claims): AuthClaims,
+    AuthClaims(claims): AuthClaims,
     Json(payload): Json<CreateUserRequestBulk>,
 ) -> Result<(StatusCode, String), (StatusCode, String)> {
-    let file_history_repo = FileUploadRepository::new();
-    let file_upload = file_history_repo
-        .find_by_id(&payload.history_file_upload_id)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to get file infomation {e}"),
-            )
-        })?;
-
-    // Check if file has already been synced to DB
-    // Block sync if status indicates the file has already been synced
-    if file_upload.status == "sync_db" || file_upload.status == "sync_blockchain" || file_upload.status == "sync" {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            format!(
-                "File has already been synced to database. Current status: {}. Cannot sync again.",
-                file_upload.status
-            ),
-        ));
-    }
-
-    // Check if there's already a process running (another sync might be in progress)
-    use crate::redis_service::redis_service::helper_get_current_file_progress;
-    let existing_progress = helper_get_current_file_progress(&file_upload.file_name)
-        .await
-        .ok();
-    
-    if let Some(progress) = existing_progress {
-        // Check if process is still running (processed < total means still in progress)
-        let processed = progress.success + progress.failed;
-        if progress.total > 0 && processed < progress.total {
-            return Err((
-                StatusCode::CONFLICT,
-                format!(
-                    "File is already being processed. Progress: {}/{} (success: {}, failed: {})",
-                    processed, progress.total, progress.success, progress.failed
-                ),
-            ));
-        }
-    }
-
-    let file_name = file_upload.file_name.clone();
-    let file_name_for_task = file_name.clone();
-
-    let result = task::spawn_blocking(move || {
-        let file_path = format!("./uploads/{}", file_name_for_task);
-        let file = File::open(file_path).map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to open file: {}", e),
-            )
-        })?;
-
-        let mut rdr = csv::Reader::from_reader(file);
-        let mut users = Vec::new();
-
-        for result in rdr.deserialize() {
-            let record: UserCsvColumn = result.map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to parse CSV: {}", e),
-                )
-            })?;
-            users.push(record);
-        }
-        Ok::<Vec<UserCsvColumn>, (StatusCode, String)>(users)
-    })
-    .await;
-
-    match result {
-        Ok(inner_result) => match inner_result {
-            Ok(users) => {
-                let rabbitmq_conn = match RABBITMQ_CONNECTION.get() {
-                    Some(conn) => conn,
-                    None => {
-                        // Update status to failed if RabbitMQ not initialized
-                        let _ = file_history_repo
-                            .update_status_file_upload(
-                                &payload.history_file_upload_id,
-                                crate::repositories::file_upload_repository::FileUploadStatus::Failed,
-                            )
-                            .await;
-                        return Err((
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            "RabbitMQ connection not initialized".to_string(),
-                        ));
-                    }
-                };
-
-                let total_records = users.len() as u64;
-
-                if let Err(err) =
-                    FileHandleTrackProgress::set_total_file_handle(&file_name, total_records).await
-                {
-                    tracing::error!("Failed to set total file handle for {}: {}", file_name, err);
-                }
-
-                if let Err(err) =
-                    FileHandleTrackProgress::set_current_file_progress(&file_name, 0).await
-                {
-                    tracing::error!("Failed to reset file progress for {}: {}", file_name, err);
-                }
-
-                if let Err(err) =
-                    FileHandleTrackProgress::reset_success_failed(&file_name).await
-                {
-                    tracing::error!(
-                        "Failed to reset success/failed counters for {}: {}",
-                        file_name,
-                        err
-                    );
-                }
-
-                for (index, mut user) in users.into_iter().enumerate() {
-                    user.file_name = Some(file_name.clone());
-                    user.row_number = Some((index + 1) as u64);
-
-                    if let Err(e) = RabbitMQService::publish_to_create_user_db(rabbitmq_conn, user).await {
-                        // Update status to failed if publish fails
-                        let _ = file_history_repo
-                            .update_status_file_upload(
-                                &payload.history_file_upload_id,
-                                crate::repositories::file_upload_repository::FileUploadStatus::Failed,
-                            )
-                            .await;
-                        return Err((
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            format!("Failed to publish create user message: {}", e),
-                        ));
-                    }
-                }
-
-                Ok((
-                    StatusCode::OK,
-                    "Publish batch user to msg queue success".to_string(),
-                ))
-            }
-            Err((status, msg)) => {
-                // Update status to failed if CSV parsing fails
-                let _ = file_history_repo
-                    .update_status_file_upload(
-                        &payload.history_file_upload_id,
-                        crate::repositories::file_upload_repository::FileUploadStatus::Failed,
-                    )
-                    .await;
-                Err((status, msg))
-            }
-        },
-
-        Err(join_error) => {
-            // Update status to failed if task join fails
-            let _ = file_history_repo
-                .update_status_file_upload(
-                    &payload.history_file_upload_id,
-                    crate::repositories::file_upload_repository::FileUploadStatus::Failed,
-                )
-                .await;
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("System error: Task panicked or cancelled - {}", join_error),
-            ))
-        }   
-    }
+    let message = UserService::import_users(&claims, payload).await?;
+    Ok((StatusCode::OK, message))
 }
 
 /// Get all users with pagination and filtering  
@@ -558,91 +135,8 @@ pub async fn get_all_users(
     AuthClaims(auth_claims): AuthClaims,
     Query(params): Query<UserQueryParams>,
 ) -> Result<(StatusCode, Json<UserListResponse>), (StatusCode, String)> {
-    let user_repo = UserRepository::new();
-    let wallet_repo = WalletRepository::new();
-    
-    permission::is_admin_or_manager(&auth_claims)?;
-
-    let manager_only_students = auth_claims.role == UserRole::MANAGER;
-    let (users, total) = user_repo
-        .find_all_with_pagination(
-            params.page as u32,
-            params.page_size as u32,
-            params.role.clone(),
-            params.search.clone(),
-            manager_only_students,
-        )
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Database error: {}", e),
-            )
-        })?;
-
-    // Convert to response DTOs
-    let mut user_responses = Vec::new();
-    for user_model in users {
-        // Get wallet info
-        let wallet_info = wallet_repo
-            .find_by_user_id(user_model.user_id)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Database error: {}", e),
-                )
-            })?;
-
-        // Get major IDs
-        let db = user_repo.get_connection();
-        let major_relationships = user_major::Entity::find()
-            .filter(user_major::Column::UserId.eq(user_model.user_id))
-            .all(db)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Database error: {}", e),
-                )
-            })?;
-
-        let major_ids = major_relationships
-            .into_iter()
-            .map(|m| m.major_id)
-            .collect::<Vec<_>>();
-
-        let major_names = fetch_major_names(db, &major_ids).await?;
-
-        user_responses.push(UserDetailResponse {
-            user_id: user_model.user_id,
-            first_name: user_model.first_name,
-            last_name: user_model.last_name,
-            address: user_model.address,
-            email: user_model.email,
-            cccd: user_model.cccd,
-            phone_number: user_model.phone_number,
-            role: user_model.role,
-            is_priority: user_model.is_priority,
-            is_first_login: user_model.is_first_login,
-            wallet_address: wallet_info.map(|w| w.address),
-            major_ids,
-            major_names,
-            created_at: user_model.create_at,
-            updated_at: user_model.update_at,
-            student_code: user_model.student_code.unwrap_or("Not Student".to_string())
-        });
-    }
-
-    Ok((
-        StatusCode::OK,
-        Json(UserListResponse {
-            users: user_responses,
-            total: total as usize,
-            page: params.page,
-            page_size: params.page_size,
-        }),
-    ))
+    let response = UserService::list_users(&auth_claims, params).await?;
+    Ok((StatusCode::OK, Json(response)))
 }
 
 /// Get user by ID
@@ -666,62 +160,7 @@ pub async fn get_user_by_id(
     AuthClaims(auth_claims): AuthClaims,
     Path(user_id): Path<Uuid>,
 ) -> Result<(StatusCode, Json<UserDetailResponse>), (StatusCode, String)> {
-    let user_repo = UserRepository::new();
-    let db = user_repo.get_connection();
-
-    // Check permission first
-    let user_id_str = user_id.to_string();
-    if auth_claims.role != UserRole::ADMIN {
-        // Non-admin/manager can only see themselves
-        if auth_claims.role != UserRole::MANAGER && auth_claims.user_id != user_id_str {
-            return Err((
-                StatusCode::FORBIDDEN,
-                "You can only view your own profile".to_string(),
-            ));
-        }
-    }
-
-    // Get user with wallet and majors
-    let (target_user, wallet_info, major_ids) = user_repo
-        .get_user_with_wallet_and_majors(user_id)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Database error: {}", e),
-            )
-        })?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
-
-    // Manager can only see students
-    if auth_claims.role == UserRole::MANAGER && target_user.role != RoleEnum::Student {
-        return Err((
-            StatusCode::FORBIDDEN,
-            "Managers can only view student accounts".to_string(),
-        ));
-    }
-
-    let major_names = fetch_major_names(db, &major_ids).await?;
-
-    let response = UserDetailResponse {
-        user_id: target_user.user_id,
-        first_name: target_user.first_name,
-        last_name: target_user.last_name,
-        address: target_user.address,
-        email: target_user.email,
-        cccd: target_user.cccd,
-        major_names,
-        phone_number: target_user.phone_number,
-        role: target_user.role,
-        is_priority: target_user.is_priority,
-        is_first_login: target_user.is_first_login,
-        wallet_address: wallet_info.map(|w| w.address),
-        major_ids,
-        created_at: target_user.create_at,
-        updated_at: target_user.update_at,
-        student_code: target_user.student_code.unwrap_or("Not Student".to_string()),
-    };
-
+    let response = UserService::get_user(&auth_claims, user_id).await?;
     Ok((StatusCode::OK, Json(response)))
 }
 
@@ -747,138 +186,7 @@ pub async fn update_user(
     Path(user_id): Path<Uuid>,
     Json(payload): Json<UpdateUserRequest>,
 ) -> Result<(StatusCode, Json<UserDetailResponse>), (StatusCode, String)> {
-    let user_repo = UserRepository::new();
-    let db = user_repo.get_connection();
-
-    // Get target user
-    let target_user = user_repo
-        .find_by_id(user_id)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Database error: {}", e),
-            )
-        })?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
-
-    // Convert RoleEnum to UserRole for permission check
-    let target_role = match target_user.role {
-        RoleEnum::Admin => UserRole::ADMIN,
-        RoleEnum::Manager => UserRole::MANAGER,
-        RoleEnum::Student => UserRole::STUDENT,
-        RoleEnum::Teacher => UserRole::TEACHER,
-    };
-
-    // Check permission
-    permission::can_modify_user(&auth_claims, &target_role)?;
-
-    let hashed_password = if let Some(password) = &payload.password {
-        Some(bcrypt::hash(password, bcrypt::DEFAULT_COST).map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to hash password: {}", e),
-            )
-        })?)
-    } else {
-        None
-    };
-
-    if let Some(_role) = &payload.role {
-        // Only admin can change roles
-        if auth_claims.role != UserRole::ADMIN {
-            return Err((
-                StatusCode::FORBIDDEN,
-                "Only admin can change user roles".to_string(),
-            ));
-        }
-    }
-
-    let updates = UserUpdate {
-        first_name: payload.first_name.clone(),
-        last_name: payload.last_name.clone(),
-        address: payload.address.clone(),
-        email: payload.email.clone(),
-        password: hashed_password,
-        cccd: payload.cccd.clone(),
-        phone_number: payload.phone_number.clone(),
-        role: payload.role.clone(),
-        is_priority: None,
-        is_first_login: None,
-    };
-
-    let updated_user = user_repo.update(user_id, updates).await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to update user: {}", e),
-        )
-    })?;
-
-    // Update major relationships if provided
-    if let Some(major_ids) = payload.major_ids {
-        // Delete existing relationships
-        user_major::Entity::delete_many()
-            .filter(user_major::Column::UserId.eq(user_id))
-            .exec(db)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to update majors: {}", e),
-                )
-            })?;
-
-        // Create new relationships
-        let now = Utc::now().naive_utc();
-        for major_id in major_ids.iter() {
-            let relationship = user_major::ActiveModel {
-                user_id: Set(user_id),
-                major_id: Set(*major_id),
-                create_at: Set(now),
-                updated_at: Set(now),
-            };
-            relationship.insert(db).await.map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to create major relationship: {}", e),
-                )
-            })?;
-        }
-    }
-
-    // Get updated user with full details
-    let (_, wallet_info, major_ids) = user_repo
-        .get_user_with_wallet_and_majors(user_id)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Database error: {}", e),
-            )
-        })?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
-
-    let major_names = fetch_major_names(db, &major_ids).await?;
-
-    let response = UserDetailResponse {
-        user_id: updated_user.user_id,
-        first_name: updated_user.first_name,
-        last_name: updated_user.last_name,
-        address: updated_user.address,
-        email: updated_user.email,
-        cccd: updated_user.cccd,
-        major_names,
-        phone_number: updated_user.phone_number,
-        role: updated_user.role,
-        is_priority: updated_user.is_priority,
-        is_first_login: updated_user.is_first_login,
-        wallet_address: wallet_info.map(|w| w.address),
-        major_ids,
-        created_at: updated_user.create_at,
-        updated_at: updated_user.update_at,
-        student_code: target_user.student_code.unwrap_or("Not Student".to_string()),
-    };
-
+    let response = UserService::update_user(&auth_claims, user_id, payload).await?;
     Ok((StatusCode::OK, Json(response)))
 }
 
@@ -901,142 +209,7 @@ pub async fn delete_user(
     AuthClaims(auth_claims): AuthClaims,
     Path(user_id): Path<Uuid>,
 ) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, String)> {
-    let user_repo = UserRepository::new();
-    let wallet_repo = WalletRepository::new();
-
-    let target_user = user_repo
-        .find_by_id(user_id)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Database error: {}", e),
-            )
-        })?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
-
-    if target_user.user_id.to_string() == auth_claims.user_id.to_string() {
-        return Err((
-            StatusCode::FORBIDDEN,
-            "You can't delete yourself".to_string(),
-        ));
-    }
-
-    let target_role = match target_user.role {
-        RoleEnum::Admin => UserRole::ADMIN,
-        RoleEnum::Manager => UserRole::MANAGER,
-        RoleEnum::Student => UserRole::STUDENT,
-        RoleEnum::Teacher => UserRole::TEACHER,
-    };
-
-    // Check permission
-    permission::can_modify_user(&auth_claims, &target_role)?;
-
-    // Get wallet address for blockchain operations
-    let wallet_info = wallet_repo.find_by_user_id(user_id).await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to get wallet: {}", e),
-        )
-    })?;
-
-    let wallet_address = wallet_info.map(|w| w.address).ok_or_else(|| {
-        (
-            StatusCode::NOT_FOUND,
-            "Wallet not found for user".to_string(),
-        )
-    })?;
-
-    // Get admin/current user private key for blockchain operations
-    let db = user_repo.get_connection();
-    let admin_user_id = Uuid::parse_str(&auth_claims.user_id).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Invalid user_id: {}", e),
-        )
-    })?;
-
-    // All blockchain transactions use admin key to pay for gas
-    // Permission checking is handled in backend routes
-    let private_key = APP_CONFIG.admin_private_key.clone();
-
-    // Publish blockchain message based on role
-    let rabbit_mq_conn = RABBITMQ_CONNECTION.get().ok_or_else(|| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "RabbitMQ connection not initialized".to_string(),
-        )
-    })?;
-
-    match target_user.role {
-        RoleEnum::Manager => {
-            // Remove manager from blockchain
-            let message = RemoveManagerMessage {
-                private_key,
-                manager_address: wallet_address,
-                email: target_user.email.clone(),
-                creator_user_id: auth_claims.user_id.clone(),
-            };
-
-            RabbitMQService::publish_to_remove_manager(rabbit_mq_conn, message)
-                .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("Failed to publish remove manager message: {}", e),
-                    )
-                })?;
-        }
-        RoleEnum::Student => {
-            // Get student_id from blockchain by wallet address
-            let blockchain = BlockchainService::new().await.map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to initialize blockchain service: {}", e),
-                )
-            })?;
-
-            let student_id = blockchain
-                .get_student_id_by_address(&wallet_address)
-                .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("Failed to get student ID: {}", e),
-                    )
-                })?;
-
-            if student_id > 0 {
-                // Deactivate student on blockchain
-                let message = DeactivateStudentMessage {
-                    private_key,
-                    student_id,
-                    email: target_user.email.clone(),
-                    creator_user_id: auth_claims.user_id.clone(),
-                };
-
-                RabbitMQService::publish_to_deactivate_student(rabbit_mq_conn, message)
-                    .await
-                    .map_err(|e| {
-                        (
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            format!("Failed to publish deactivate student message: {}", e),
-                        )
-                    })?;
-            }
-        }
-        _ => {
-            // Admin and Teacher don't need blockchain operations for deletion
-        }
-    }
-
-    // Soft delete user (set deleted_at instead of hard delete)
-    user_repo.soft_delete(user_id).await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to soft delete user: {}", e),
-        )
-    })?;
+    UserService::delete_user(&auth_claims, user_id).await?;
 
     Ok((
         StatusCode::OK,
@@ -1217,46 +390,30 @@ pub async fn activate_blockchain_registration(
         ));
     }
 
-    let private_key = APP_CONFIG.admin_private_key.clone();
-
-    // Set total for progress tracking
+    // Start progress tracking
     let total_students = students.len() as u64;
-    if let Err(err) = BlockchainRegistrationProgress::set_total(
+    if let Err(err) = BlockchainRegistrationProgress::start(
         &payload.history_file_upload_id,
         total_students,
     )
     .await
     {
         tracing::error!(
-            "Failed to set total blockchain registration for {}: {}",
-            payload.history_file_upload_id,
-            err
-        );
-    }
-
-    if let Err(err) = BlockchainRegistrationProgress::reset_progress(
-        &payload.history_file_upload_id,
-    )
-    .await
-    {
-        tracing::error!(
-            "Failed to reset blockchain registration progress for {}: {}",
+            "Failed to start blockchain registration progress for {}: {}",
             payload.history_file_upload_id,
             err
         );
     }
 
-    // Publish each student individually to blockchain registration queue
-    let rabbitmq_conn = RABBITMQ_CONNECTION.get().ok_or_else(|| {
+    // Queue every student in one transaction: either the whole file is queued or none of it
+    let outbox_repo = OutboxRepository::new();
+    let txn = outbox_repo.get_connection().begin().await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            "RabbitMQ connection not initialized".to_string(),
+            format!("Failed to start transaction: {}", e),
         )
     })?;
 
-    let mut success_count = 0;
-    let mut failed_count = 0;
-
     for (user, wallet) in students.iter() {
         let student_code = user
             .student_code
@@ -1265,7 +422,8 @@ pub async fn activate_blockchain_registration(
         let full_name = format!("{} {}", user.first_name, user.last_name);
 
         let message = RegisterNewUserMessage {
-            private_key: private_key.clone(),
+            schema_version: MESSAGE_SCHEMA_VERSION,
+            idempotency_key: Some(new_idempotency_key()),
             wallet_address: wallet.address.clone(),
             student_code: student_code.clone(),
             full_name: full_name.clone(),
@@ -1274,42 +432,39 @@ pub async fn activate_blockchain_registration(
             file_upload_history_id: Some(payload.history_file_upload_id.clone()),
         };
 
-        match RabbitMQService::publish_to_register_new_user(rabbitmq_conn, message).await {
-            Ok(_) => {
-                success_count += 1;
-                tracing::info!(
-                    "Published blockchain registration message for student: {} ({})",
-                    student_code,
-                    user.email
-                );
-            }
-            Err(e) => {
-                failed_count += 1;
-                tracing::error!(
-                    "Failed to publish blockchain registration message for student {} ({}): {}",
-                    student_code,
-                    user.email,
-                    e
-                );
-            }
-        }
+        outbox_repo
+            .enqueue(&txn, REGISTER_NEW_USER_CHANNEL, Priority::Low, &message)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!(
+                        "Failed to queue blockchain registration for student {} ({}): {}",
+                        student_code, user.email, e
+                    ),
+                )
+            })?;
     }
 
-    if failed_count > 0 {
-        return Err((
-            StatusCode::PARTIAL_CONTENT,
-            format!(
-                "Blockchain registration activated for {} students, {} failed",
-                success_count, failed_count
-            ),
-        ));
-    }
+    txn.commit().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to commit blockchain registration messages: {}", e),
+        )
+    })?;
+    OutboxRelay::wake();
+
+    tracing::info!(
+        "Queued blockchain registration for {} students of {}",
+        students.len(),
+        payload.history_file_upload_id
+    );
 
     Ok((
         StatusCode::OK,
         format!(
             "Blockchain registration activated for {} students",
-            success_count
+            students.len()
         ),
     ))
 }
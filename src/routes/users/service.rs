@@ -0,0 +1,934 @@
+//! User management shared by the REST handlers in `route` and the gRPC `UserAdminService`.
+//!
+//! Every operation takes the caller's claims and enforces the role rules itself, so both
+//! transports stay in sync.
+
+use axum::http::StatusCode;
+use chrono::Utc;
+use do_an_lib::structs::token_claims::{TokenClaims, UserRole};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set,
+    TransactionTrait,
+};
+use std::fs::File;
+use tokio::task;
+use uuid::Uuid;
+
+use super::dto::{
+    CreateUserRequest, CreateUserRequestBulk, UpdateUserRequest, UserCsvColumn,
+    UserDetailResponse, UserListResponse, UserQueryParams, UserResponse,
+};
+use crate::blockchain::BlockchainService;
+use crate::config::APP_CONFIG;
+use crate::entities::sea_orm_active_enums::RoleEnum;
+use crate::entities::{major, user_major};
+use crate::key_provider::get_key_provider;
+use crate::middleware::permission;
+use crate::rabbitmq_service::outbox::OutboxRelay;
+use crate::rabbitmq_service::structs::{
+    AssignRoleMessage, DeactivateStudentMessage, MESSAGE_SCHEMA_VERSION, RegisterNewManagerMessage,
+    RegisterNewUserMessage, RemoveManagerMessage, new_idempotency_key,
+};
+use crate::rabbitmq_service::topology::{
+    ASSIGN_ROLE_CHANNEL, CREATE_USER_DB, DEACTIVATE_STUDENT_CHANNEL, Priority,
+    REGISTER_NEW_MANAGER_CHANNEL, REGISTER_NEW_USER_CHANNEL, REMOVE_MANAGER_CHANNEL,
+};
+use crate::redis_service::redis_service::FileHandleTrackProgress;
+use crate::repositories::file_upload_repository::FileUploadRepository;
+use crate::repositories::{
+    OutboxRepository, UserRepository, WalletRepository, user_repository::UserUpdate,
+};
+use crate::utils::envelope_encryption::CONTEXT_WALLET_PRIVATE_KEY;
+
+async fn fetch_major_names(
+    db: &DatabaseConnection,
+    major_ids: &[Uuid],
+) -> Result<Vec<String>, (StatusCode, String)> {
+    if major_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let majors = major::Entity::find()
+        .filter(major::Column::MajorId.is_in(major_ids.to_vec()))
+        .all(db)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Database error: {}", e),
+            )
+        })?;
+
+    Ok(majors.into_iter().map(|m| m.name).collect())
+}
+
+pub struct UserService;
+
+impl UserService {
+    /// Create one user with a fresh wallet and queue its on-chain registration
+    pub async fn create_user(
+        auth_claims: &TokenClaims,
+        payload: CreateUserRequest,
+    ) -> Result<UserResponse, (StatusCode, String)> {
+        if auth_claims.role != UserRole::ADMIN && auth_claims.role != UserRole::MANAGER {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Only admin or manager can create users".to_string(),
+            ));
+        }
+
+        if auth_claims.role == UserRole::MANAGER && payload.role != RoleEnum::Student {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Managers can only create student accounts".to_string(),
+            ));
+        }
+
+        if auth_claims.role != UserRole::ADMIN 
+            && (payload.role == RoleEnum::Admin || payload.role == RoleEnum::Manager) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Only admin can create manager or admin accounts".to_string(),
+            ));
+        }
+
+        let user_repo = UserRepository::new();
+
+        // Check if email is already used by a user with Sync status
+        if user_repo.is_email_used_by_sync_user(&payload.email).await.map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to check email: {}", e),
+            )
+        })? {
+            return Err((
+                StatusCode::CONFLICT,
+                format!("Email {} is already used by an active account", payload.email),
+            ));
+        }
+        let wallet_repo = WalletRepository::new();
+        let user_uuid = Uuid::parse_str(&auth_claims.user_id).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Invalid user_id: {}", e),
+            )
+        })?;
+
+        let hashed_password = bcrypt::hash(&payload.password, bcrypt::DEFAULT_COST).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to hash password: {}", e),
+            )
+        })?;
+
+        let (wallet_address, wallet_private_key) =
+            BlockchainService::generate_wallet().map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to generate wallet: {}", e),
+                )
+            })?;
+
+
+        let encrypted_private_key = get_key_provider()
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to load key provider: {}", e),
+                )
+            })?
+            .encrypt(&wallet_private_key, CONTEXT_WALLET_PRIVATE_KEY)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to encrypt private key: {}", e),
+                )
+            })?;
+
+        let user_id = Uuid::new_v4();
+        let wallet_id = Uuid::new_v4();
+
+        let student_code = if payload.role == RoleEnum::Student {
+            let lastest_student_code = UserRepository::get_latest_student_code()
+                .await
+                .expect("Failed to get student code");
+            let student_code_i64 = lastest_student_code.parse::<i64>().unwrap_or_default();
+            let student_code = student_code_i64 + 1;
+            let formated_student_code: String = format!("{:06}", student_code);
+            Some(formated_student_code)
+        } else {
+            None
+        };
+
+        // The user, its wallet and the message registering it on-chain commit together, so
+        // a user can no longer be left Pending with nothing queued to sync it
+        let txn = user_repo.get_connection().begin().await.map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to start transaction: {}", e),
+            )
+        })?;
+
+        let user = user_repo
+            .create(
+                &txn,
+                user_id,
+                payload.first_name.clone(),
+                payload.last_name.clone(),
+                payload.address.clone(),
+                payload.email.clone(),
+                hashed_password,
+                payload.cccd.clone(),
+                payload.phone_number.clone(),
+                payload.role.clone(),
+                false,
+                student_code.clone(),
+            )
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to create user: {}", e),
+                )
+            })?;
+
+        wallet_repo
+            .create(
+                &txn,
+                wallet_id,
+                user_id,
+                wallet_address.clone(),
+                encrypted_private_key.clone(),
+                APP_CONFIG.chain_type.clone(),
+                wallet_address.clone(),
+                "active".to_string(),
+                APP_CONFIG.chain_id.clone(),
+            )
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to create wallet: {}", e),
+                )
+            })?;
+
+        if let Some(major_ids) = payload.major_ids {
+            let now = Utc::now().naive_utc();
+            for major_id in major_ids.iter() {
+                let relationship_model = user_major::ActiveModel {
+                    user_id: Set(user_id),
+                    major_id: Set(*major_id),
+                    create_at: Set(now),
+                    updated_at: Set(now),
+                };
+
+                relationship_model.insert(&txn).await.map_err(|e| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to create user-major relationship: {}", e),
+                    )
+                })?;
+            }
+        }
+
+        let outbox_repo = OutboxRepository::new();
+        let enqueued = match payload.role {
+            RoleEnum::Student => {
+                let full_name = format!("{} {}", payload.first_name, payload.last_name);
+
+                let register_user_msg = RegisterNewUserMessage {
+                    schema_version: MESSAGE_SCHEMA_VERSION,
+                    idempotency_key: Some(new_idempotency_key()),
+                    wallet_address: wallet_address.clone(),
+                    student_code: student_code.unwrap_or_default(),
+                    full_name,
+                    email: payload.email,
+                    creator_user_id: auth_claims.user_id.clone(),
+                    file_upload_history_id: None,
+                };
+                outbox_repo
+                    .enqueue(
+                        &txn,
+                        REGISTER_NEW_USER_CHANNEL,
+                        Priority::High,
+                        &register_user_msg,
+                    )
+                    .await
+            }
+
+            RoleEnum::Manager => {
+                let register_new_manager = RegisterNewManagerMessage {
+                    schema_version: MESSAGE_SCHEMA_VERSION,
+                    idempotency_key: Some(new_idempotency_key()),
+                    wallet_address: wallet_address.clone(),
+                    email: payload.email,
+                    creator_user_id: auth_claims.user_id.clone(),
+                };
+
+                outbox_repo
+                    .enqueue(
+                        &txn,
+                        REGISTER_NEW_MANAGER_CHANNEL,
+                        Priority::High,
+                        &register_new_manager,
+                    )
+                    .await
+            }
+
+            RoleEnum::Teacher | RoleEnum::Admin => {
+                let role_code = match payload.role {
+                    RoleEnum::Admin => 3,
+                    RoleEnum::Teacher => 2,
+                    _ => 0,
+                };
+
+                let assign_role_msg = AssignRoleMessage {
+                    schema_version: MESSAGE_SCHEMA_VERSION,
+                    idempotency_key: Some(new_idempotency_key()),
+                    user_address: wallet_address.clone(),
+                    role: role_code,
+                    email: payload.email.clone(),
+                    creator_user_id: auth_claims.user_id.clone(),
+                };
+
+                outbox_repo
+                    .enqueue(&txn, ASSIGN_ROLE_CHANNEL, Priority::High, &assign_role_msg)
+                    .await
+            }
+        };
+
+        enqueued.map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to queue blockchain registration: {}", e),
+            )
+        })?;
+
+        txn.commit().await.map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to commit user creation: {}", e),
+            )
+        })?;
+        OutboxRelay::wake();
+
+        let response = UserResponse {
+            user_id: user.user_id,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            email: user.email,
+            role: user.role,
+            wallet_address: wallet_address.clone(),
+            wallet_private_key: encrypted_private_key,
+            is_first_login: user.is_first_login,
+            created_at: user.create_at,
+        };
+
+        Ok(response)
+    }
+
+    /// Queue every row of an uploaded CSV file for user creation
+    pub async fn import_users(
+        auth_claims: &TokenClaims,
+        payload: CreateUserRequestBulk,
+    ) -> Result<String, (StatusCode, String)> {
+        // CSV rows may carry any role, so importing needs the same rights as creating users
+        permission::is_admin_or_manager(auth_claims)?;
+
+        let file_history_repo = FileUploadRepository::new();
+        let file_upload = file_history_repo
+            .find_by_id(&payload.history_file_upload_id)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to get file infomation {e}"),
+                )
+            })?;
+
+        // Check if file has already been synced to DB
+        // Block sync if status indicates the file has already been synced
+        if file_upload.status == "sync_db" || file_upload.status == "sync_blockchain" || file_upload.status == "sync" {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "File has already been synced to database. Current status: {}. Cannot sync again.",
+                    file_upload.status
+                ),
+            ));
+        }
+
+        // Check if there's already a process running (another sync might be in progress)
+        use crate::redis_service::redis_service::helper_get_current_file_progress;
+        let existing_progress = helper_get_current_file_progress(&file_upload.file_name)
+            .await
+            .ok();
+
+        if let Some(progress) = existing_progress {
+            // Check if process is still running (processed < total means still in progress)
+            let processed = progress.success + progress.failed;
+            if progress.total > 0 && processed < progress.total {
+                return Err((
+                    StatusCode::CONFLICT,
+                    format!(
+                        "File is already being processed. Progress: {}/{} (success: {}, failed: {})",
+                        processed, progress.total, progress.success, progress.failed
+                    ),
+                ));
+            }
+        }
+
+        let file_name = file_upload.file_name.clone();
+        let file_name_for_task = file_name.clone();
+
+        let result = task::spawn_blocking(move || {
+            let file_path = format!("./uploads/{}", file_name_for_task);
+            let file = File::open(file_path).map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to open file: {}", e),
+                )
+            })?;
+
+            let mut rdr = csv::Reader::from_reader(file);
+            let mut users = Vec::new();
+
+            for result in rdr.deserialize() {
+                let record: UserCsvColumn = result.map_err(|e| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to parse CSV: {}", e),
+                    )
+                })?;
+                users.push(record);
+            }
+            Ok::<Vec<UserCsvColumn>, (StatusCode, String)>(users)
+        })
+        .await;
+
+        match result {
+            Ok(inner_result) => match inner_result {
+                Ok(users) => {
+                    let total_records = users.len() as u64;
+
+                    if let Err(err) =
+                        FileHandleTrackProgress::start(&file_name, total_records).await
+                    {
+                        tracing::error!("Failed to start file progress for {}: {}", file_name, err);
+                    }
+
+                    // Every row is queued in one transaction, so a failure never leaves part
+                    // of the file queued
+                    if let Err(e) = Self::enqueue_csv_rows(users, &file_name).await {
+                        // Update status to failed if queueing fails
+                        let _ = file_history_repo
+                            .update_status_file_upload(
+                                &payload.history_file_upload_id,
+                                crate::repositories::file_upload_repository::FileUploadStatus::Failed,
+                            )
+                            .await;
+                        return Err((
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("Failed to queue create user messages: {}", e),
+                        ));
+                    }
+                    OutboxRelay::wake();
+
+                    Ok("Publish batch user to msg queue success".to_string())
+                }
+                Err((status, msg)) => {
+                    // Update status to failed if CSV parsing fails
+                    let _ = file_history_repo
+                        .update_status_file_upload(
+                            &payload.history_file_upload_id,
+                            crate::repositories::file_upload_repository::FileUploadStatus::Failed,
+                        )
+                        .await;
+                    Err((status, msg))
+                }
+            },
+
+            Err(join_error) => {
+                // Update status to failed if task join fails
+                let _ = file_history_repo
+                    .update_status_file_upload(
+                        &payload.history_file_upload_id,
+                        crate::repositories::file_upload_repository::FileUploadStatus::Failed,
+                    )
+                    .await;
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("System error: Task panicked or cancelled - {}", join_error),
+                ))
+            }   
+        }
+    }
+
+    async fn enqueue_csv_rows(users: Vec<UserCsvColumn>, file_name: &str) -> anyhow::Result<()> {
+        let outbox_repo = OutboxRepository::new();
+        let txn = outbox_repo.get_connection().begin().await?;
+
+        for (index, mut user) in users.into_iter().enumerate() {
+            user.file_name = Some(file_name.to_string());
+            user.row_number = Some((index + 1) as u64);
+            outbox_repo
+                .enqueue(&txn, CREATE_USER_DB, Priority::Low, &user)
+                .await?;
+        }
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Admin can see all, Manager can see students
+    pub async fn list_users(
+        auth_claims: &TokenClaims,
+        params: UserQueryParams,
+    ) -> Result<UserListResponse, (StatusCode, String)> {
+        let user_repo = UserRepository::new();
+        let wallet_repo = WalletRepository::new();
+
+        permission::is_admin_or_manager(auth_claims)?;
+
+        let manager_only_students = auth_claims.role == UserRole::MANAGER;
+        let (users, total) = user_repo
+            .find_all_with_pagination(
+                params.page as u32,
+                params.page_size as u32,
+                params.role.clone(),
+                params.search.clone(),
+                manager_only_students,
+            )
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Database error: {}", e),
+                )
+            })?;
+
+        // Convert to response DTOs
+        let mut user_responses = Vec::new();
+        for user_model in users {
+            // Get wallet info
+            let wallet_info = wallet_repo
+                .find_by_user_id(user_model.user_id)
+                .await
+                .map_err(|e| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Database error: {}", e),
+                    )
+                })?;
+
+            // Get major IDs
+            let db = user_repo.get_connection();
+            let major_relationships = user_major::Entity::find()
+                .filter(user_major::Column::UserId.eq(user_model.user_id))
+                .all(db)
+                .await
+                .map_err(|e| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Database error: {}", e),
+                    )
+                })?;
+
+            let major_ids = major_relationships
+                .into_iter()
+                .map(|m| m.major_id)
+                .collect::<Vec<_>>();
+
+            let major_names = fetch_major_names(db, &major_ids).await?;
+
+            user_responses.push(UserDetailResponse {
+                user_id: user_model.user_id,
+                first_name: user_model.first_name,
+                last_name: user_model.last_name,
+                address: user_model.address,
+                email: user_model.email,
+                cccd: user_model.cccd,
+                phone_number: user_model.phone_number,
+                role: user_model.role,
+                is_priority: user_model.is_priority,
+                is_first_login: user_model.is_first_login,
+                wallet_address: wallet_info.map(|w| w.address),
+                major_ids,
+                major_names,
+                created_at: user_model.create_at,
+                updated_at: user_model.update_at,
+                student_code: user_model.student_code.unwrap_or("Not Student".to_string())
+            });
+        }
+
+        Ok(UserListResponse {
+            users: user_responses,
+            total: total as usize,
+            page: params.page,
+            page_size: params.page_size,
+        })
+    }
+
+    /// Admin can see all, Manager can see students, users can see themselves
+    pub async fn get_user(
+        auth_claims: &TokenClaims,
+        user_id: Uuid,
+    ) -> Result<UserDetailResponse, (StatusCode, String)> {
+        let user_repo = UserRepository::new();
+        let db = user_repo.get_connection();
+
+        // Check permission first
+        let user_id_str = user_id.to_string();
+        if auth_claims.role != UserRole::ADMIN {
+            // Non-admin/manager can only see themselves
+            if auth_claims.role != UserRole::MANAGER && auth_claims.user_id != user_id_str {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    "You can only view your own profile".to_string(),
+                ));
+            }
+        }
+
+        // Get user with wallet and majors
+        let (target_user, wallet_info, major_ids) = user_repo
+            .get_user_with_wallet_and_majors(user_id)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Database error: {}", e),
+                )
+            })?
+            .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
+
+        // Manager can only see students
+        if auth_claims.role == UserRole::MANAGER && target_user.role != RoleEnum::Student {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Managers can only view student accounts".to_string(),
+            ));
+        }
+
+        let major_names = fetch_major_names(db, &major_ids).await?;
+
+        let response = UserDetailResponse {
+            user_id: target_user.user_id,
+            first_name: target_user.first_name,
+            last_name: target_user.last_name,
+            address: target_user.address,
+            email: target_user.email,
+            cccd: target_user.cccd,
+            major_names,
+            phone_number: target_user.phone_number,
+            role: target_user.role,
+            is_priority: target_user.is_priority,
+            is_first_login: target_user.is_first_login,
+            wallet_address: wallet_info.map(|w| w.address),
+            major_ids,
+            created_at: target_user.create_at,
+            updated_at: target_user.update_at,
+            student_code: target_user.student_code.unwrap_or("Not Student".to_string()),
+        };
+
+        Ok(response)
+    }
+
+    /// Update user information (UC10, UC17)
+    pub async fn update_user(
+        auth_claims: &TokenClaims,
+        user_id: Uuid,
+        payload: UpdateUserRequest,
+    ) -> Result<UserDetailResponse, (StatusCode, String)> {
+        let user_repo = UserRepository::new();
+        let db = user_repo.get_connection();
+
+        // Get target user
+        let target_user = user_repo
+            .find_by_id(user_id)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Database error: {}", e),
+                )
+            })?
+            .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
+
+        // Convert RoleEnum to UserRole for permission check
+        let target_role = match target_user.role {
+            RoleEnum::Admin => UserRole::ADMIN,
+            RoleEnum::Manager => UserRole::MANAGER,
+            RoleEnum::Student => UserRole::STUDENT,
+            RoleEnum::Teacher => UserRole::TEACHER,
+        };
+
+        // Check permission
+        permission::can_modify_user(auth_claims, &target_role)?;
+
+        let hashed_password = if let Some(password) = &payload.password {
+            Some(bcrypt::hash(password, bcrypt::DEFAULT_COST).map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to hash password: {}", e),
+                )
+            })?)
+        } else {
+            None
+        };
+
+        if let Some(_role) = &payload.role {
+            // Only admin can change roles
+            if auth_claims.role != UserRole::ADMIN {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    "Only admin can change user roles".to_string(),
+                ));
+            }
+        }
+
+        let updates = UserUpdate {
+            first_name: payload.first_name.clone(),
+            last_name: payload.last_name.clone(),
+            address: payload.address.clone(),
+            email: payload.email.clone(),
+            password: hashed_password,
+            cccd: payload.cccd.clone(),
+            phone_number: payload.phone_number.clone(),
+            role: payload.role.clone(),
+            is_priority: None,
+            is_first_login: None,
+        };
+
+        let updated_user = user_repo.update(user_id, updates).await.map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to update user: {}", e),
+            )
+        })?;
+
+        // Update major relationships if provided
+        if let Some(major_ids) = payload.major_ids {
+            // Delete existing relationships
+            user_major::Entity::delete_many()
+                .filter(user_major::Column::UserId.eq(user_id))
+                .exec(db)
+                .await
+                .map_err(|e| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to update majors: {}", e),
+                    )
+                })?;
+
+            // Create new relationships
+            let now = Utc::now().naive_utc();
+            for major_id in major_ids.iter() {
+                let relationship = user_major::ActiveModel {
+                    user_id: Set(user_id),
+                    major_id: Set(*major_id),
+                    create_at: Set(now),
+                    updated_at: Set(now),
+                };
+                relationship.insert(db).await.map_err(|e| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to create major relationship: {}", e),
+                    )
+                })?;
+            }
+        }
+
+        // Get updated user with full details
+        let (_, wallet_info, major_ids) = user_repo
+            .get_user_with_wallet_and_majors(user_id)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Database error: {}", e),
+                )
+            })?
+            .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
+
+        let major_names = fetch_major_names(db, &major_ids).await?;
+
+        let response = UserDetailResponse {
+            user_id: updated_user.user_id,
+            first_name: updated_user.first_name,
+            last_name: updated_user.last_name,
+            address: updated_user.address,
+            email: updated_user.email,
+            cccd: updated_user.cccd,
+            major_names,
+            phone_number: updated_user.phone_number,
+            role: updated_user.role,
+            is_priority: updated_user.is_priority,
+            is_first_login: updated_user.is_first_login,
+            wallet_address: wallet_info.map(|w| w.address),
+            major_ids,
+            created_at: updated_user.create_at,
+            updated_at: updated_user.update_at,
+            student_code: target_user.student_code.unwrap_or("Not Student".to_string()),
+        };
+
+        Ok(response)
+    }
+
+    /// Soft delete a user and revoke its on-chain role (UC18)
+    pub async fn delete_user(
+        auth_claims: &TokenClaims,
+        user_id: Uuid,
+    ) -> Result<(), (StatusCode, String)> {
+        let user_repo = UserRepository::new();
+        let wallet_repo = WalletRepository::new();
+
+        let target_user = user_repo
+            .find_by_id(user_id)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Database error: {}", e),
+                )
+            })?
+            .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
+
+        if target_user.user_id.to_string() == auth_claims.user_id.to_string() {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "You can't delete yourself".to_string(),
+            ));
+        }
+
+        let target_role = match target_user.role {
+            RoleEnum::Admin => UserRole::ADMIN,
+            RoleEnum::Manager => UserRole::MANAGER,
+            RoleEnum::Student => UserRole::STUDENT,
+            RoleEnum::Teacher => UserRole::TEACHER,
+        };
+
+        // Check permission
+        permission::can_modify_user(auth_claims, &target_role)?;
+
+        // Get wallet address for blockchain operations
+        let wallet_info = wallet_repo.find_by_user_id(user_id).await.map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to get wallet: {}", e),
+            )
+        })?;
+
+        let wallet_address = wallet_info.map(|w| w.address).ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                "Wallet not found for user".to_string(),
+            )
+        })?;
+
+        // Get admin/current user private key for blockchain operations
+        let db = user_repo.get_connection();
+        let admin_user_id = Uuid::parse_str(&auth_claims.user_id).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Invalid user_id: {}", e),
+            )
+        })?;
+
+        // The blockchain message and the soft delete commit together
+        let txn = db.begin().await.map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to start transaction: {}", e),
+            )
+        })?;
+        let outbox_repo = OutboxRepository::new();
+
+        // Queue blockchain message based on role
+
+        match target_user.role {
+            RoleEnum::Manager => {
+                // Remove manager from blockchain
+                let message = RemoveManagerMessage {
+                    schema_version: MESSAGE_SCHEMA_VERSION,
+                    idempotency_key: Some(new_idempotency_key()),
+                    manager_address: wallet_address,
+                    email: target_user.email.clone(),
+                    creator_user_id: auth_claims.user_id.clone(),
+                };
+
+                outbox_repo
+                    .enqueue(&txn, REMOVE_MANAGER_CHANNEL, Priority::High, &message)
+                    .await
+                    .map_err(|e| {
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("Failed to queue remove manager message: {}", e),
+                        )
+                    })?;
+            }
+            RoleEnum::Student => {
+                // Get student_id from blockchain by wallet address
+                let blockchain = BlockchainService::new().await.map_err(|e| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to initialize blockchain service: {}", e),
+                    )
+                })?;
+
+                let student_id = blockchain
+                    .get_student_id_by_address(&wallet_address)
+                    .await
+                    .map_err(|e| {
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("Failed to get student ID: {}", e),
+                        )
+                    })?;
+
+                if student_id > 0 {
+                    // Deactivate student on blockchain
+                    let message = DeactivateStudentMessage {
+                        schema_version: MESSAGE_SCHEMA_VERSION,
+                        idempotency_key: Some(new_idempotency_key()),
+                        student_id,
+                        email: target_user.email.clone(),
+                        creator_user_id: auth_claims.user_id.clone(),
+                    };
+
+                    outbox_repo
+                        .enqueue(&txn, DEACTIVATE_STUDENT_CHANNEL, Priority::High, &message)
+                        .await
+                        .map_err(|e| {
+                            (
+                                StatusCode::INTERNAL_SERVER_ERROR,
+                                format!("Failed to queue deactivate student message: {}", e),
+                            )
+                        })?;
+                }
+            }
+            _ => {
+                // Admin and Teacher don't need blockchain operations for deletion
+            }
+        }
+
+        // Soft delete user (set deleted_at instead of hard delete)
+        user_repo.soft_delete(&txn, user_id).await.map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to soft delete user: {}", e),
+            )
+        })?;
+
+        txn.commit().await.map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to commit user deletion: {}", e),
+            )
+        })?;
+        OutboxRelay::wake();
+
+        Ok(())
+    }
+}
@@ -12,6 +12,8 @@ use scrypt::{Params, scrypt};
 
 const NONCE_SIZE: usize = 12; // 96 bits for AES-GCM
 
+/// Legacy wallet scheme (AES-GCM with a zero-padded key). New values are sealed with
+/// `key_provider::KeyProvider::encrypt`; this is only kept to read and migrate old rows.
 pub fn encrypt_private_key(private_key: &str, encryption_key: &str) -> Result<String> {
     let key_bytes = if encryption_key.len() >= 32 {
         encryption_key.as_bytes()[..32].to_vec()
@@ -81,6 +83,8 @@ impl RsaAes {
     }
 }
 
+/// Legacy MFA secret scheme (scrypt + AES-CBC, unauthenticated). Only kept to read and
+/// migrate old rows, see `envelope_encryption`.
 pub fn decrypt(encryption_key: &str, encrypted_text: &str) -> anyhow::Result<String> {
     let encryption_key_bytes = encryption_key.as_bytes();
     let encrypted_bytes = general_purpose::STANDARD.decode(encrypted_text)?;
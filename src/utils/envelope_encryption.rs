@@ -0,0 +1,288 @@
+//! Versioned envelope encryption for secrets stored in the database.
+//!
+//! Every value is encrypted with a fresh random data key (DEK) using AES-256-GCM.
+//! The DEK itself is wrapped with a key-encryption key (KEK) derived with scrypt
+//! from one of the configured master keys. The serialized form is
+//!
+//! ```text
+//! ev1:<key_id>:base64(dek_nonce || wrapped_dek || data_nonce || ciphertext)
+//! ```
+//!
+//! The header and a per-column context string are bound as AEAD associated data,
+//! so a ciphertext cannot be moved to another column or relabelled with another key id.
+//! Several master keys can be configured at once: new values are always sealed with the
+//! active key, older ones stay readable until `reencrypt_secrets` rewraps them.
+//! Callers go through `key_provider::KeyProvider` rather than using this module directly.
+
+use crate::config::APP_CONFIG;
+use crate::utils::encryption::{decrypt, decrypt_private_key};
+use aes_gcm::{
+    Aes256Gcm, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+};
+use anyhow::{Context, Result, anyhow};
+use base64::{Engine, engine::general_purpose};
+use rand::RngCore;
+use scrypt::{Params, scrypt};
+use std::collections::HashMap;
+
+pub const ENVELOPE_VERSION: &str = "ev1";
+
+/// Context strings bound to the ciphertext of each encrypted column
+pub const CONTEXT_WALLET_PRIVATE_KEY: &str = "wallet.private_key";
+pub const CONTEXT_MFA_SECRET: &str = "user_mfa.secret";
+
+/// Key id used when only the legacy `ENCRYPTION_KEY` is configured
+pub const DEFAULT_KEY_ID: &str = "default";
+
+const NONCE_SIZE: usize = 12;
+const KEY_SIZE: usize = 32;
+const WRAPPED_KEY_SIZE: usize = KEY_SIZE + 16; // DEK + GCM tag
+
+pub struct KeyRing {
+    active_key_id: String,
+    keys: HashMap<String, [u8; KEY_SIZE]>,
+}
+
+impl KeyRing {
+    /// Build the key ring from `ENCRYPTION_KEYS` (`id:secret,id:secret`) and
+    /// `ENCRYPTION_ACTIVE_KEY_ID`, falling back to the single `ENCRYPTION_KEY`.
+    pub fn from_config() -> Result<Self> {
+        let master_keys: Vec<(String, String)> = match APP_CONFIG.encryption_keys.as_deref() {
+            Some(value) if !value.trim().is_empty() => value
+                .split(',')
+                .map(|entry| {
+                    let (key_id, secret) = entry
+                        .trim()
+                        .split_once(':')
+                        .ok_or_else(|| anyhow!("ENCRYPTION_KEYS entries must be id:secret"))?;
+                    Ok((key_id.to_string(), secret.to_string()))
+                })
+                .collect::<Result<_>>()?,
+            _ => vec![(DEFAULT_KEY_ID.to_string(), APP_CONFIG.encryption_key.clone())],
+        };
+
+        let active_key_id = APP_CONFIG
+            .encryption_active_key_id
+            .clone()
+            .filter(|key_id| !key_id.is_empty())
+            .unwrap_or_else(|| master_keys[0].0.clone());
+
+        Self::new(&active_key_id, &master_keys)
+    }
+
+    pub fn new(active_key_id: &str, master_keys: &[(String, String)]) -> Result<Self> {
+        let mut keys = HashMap::new();
+        for (key_id, secret) in master_keys {
+            if key_id.is_empty() || key_id.contains(':') {
+                anyhow::bail!("Invalid encryption key id '{}'", key_id);
+            }
+            if secret.len() < KEY_SIZE {
+                tracing::warn!(
+                    "Master key '{}' is shorter than {} characters, consider rotating it",
+                    key_id,
+                    KEY_SIZE
+                );
+            }
+            keys.insert(key_id.clone(), derive_kek(key_id, secret)?);
+        }
+
+        if !keys.contains_key(active_key_id) {
+            anyhow::bail!("Active encryption key '{}' is not configured", active_key_id);
+        }
+
+        Ok(Self {
+            active_key_id: active_key_id.to_string(),
+            keys,
+        })
+    }
+
+    pub fn active_key_id(&self) -> &str {
+        &self.active_key_id
+    }
+
+    /// Encrypt `plaintext` with a fresh data key wrapped by the active master key
+    pub fn seal(&self, plaintext: &str, context: &str) -> Result<String> {
+        let kek = self.kek(&self.active_key_id)?;
+        let header = format!("{}:{}", ENVELOPE_VERSION, self.active_key_id);
+        let aad = format!("{}:{}", header, context);
+
+        let mut dek = [0u8; KEY_SIZE];
+        rand::rng().fill_bytes(&mut dek);
+
+        let dek_nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let wrapped_dek = Aes256Gcm::new_from_slice(kek)
+            .context("Failed to create KEK cipher")?
+            .encrypt(
+                &dek_nonce,
+                Payload {
+                    msg: &dek,
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("Failed to wrap data key"))?;
+
+        let data_nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new_from_slice(&dek)
+            .context("Failed to create DEK cipher")?
+            .encrypt(
+                &data_nonce,
+                Payload {
+                    msg: plaintext.as_bytes(),
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("Failed to encrypt secret"))?;
+
+        let mut body = Vec::with_capacity(2 * NONCE_SIZE + WRAPPED_KEY_SIZE + ciphertext.len());
+        body.extend_from_slice(&dek_nonce);
+        body.extend_from_slice(&wrapped_dek);
+        body.extend_from_slice(&data_nonce);
+        body.extend_from_slice(&ciphertext);
+
+        Ok(format!("{}:{}", header, general_purpose::STANDARD.encode(&body)))
+    }
+
+    /// Decrypt an envelope produced by [`KeyRing::seal`] with any configured master key
+    pub fn open(&self, envelope: &str, context: &str) -> Result<String> {
+        let (key_id, encoded) = parse_envelope(envelope)?;
+        let kek = self.kek(key_id)?;
+        let aad = format!("{}:{}:{}", ENVELOPE_VERSION, key_id, context);
+
+        let body = general_purpose::STANDARD
+            .decode(encoded)
+            .context("Failed to decode envelope body")?;
+        if body.len() < 2 * NONCE_SIZE + WRAPPED_KEY_SIZE {
+            anyhow::bail!("Envelope too short");
+        }
+
+        let (dek_nonce, rest) = body.split_at(NONCE_SIZE);
+        let (wrapped_dek, rest) = rest.split_at(WRAPPED_KEY_SIZE);
+        let (data_nonce, ciphertext) = rest.split_at(NONCE_SIZE);
+
+        let dek = Aes256Gcm::new_from_slice(kek)
+            .context("Failed to create KEK cipher")?
+            .decrypt(
+                Nonce::from_slice(dek_nonce),
+                Payload {
+                    msg: wrapped_dek,
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("Failed to unwrap data key"))?;
+
+        let plaintext = Aes256Gcm::new_from_slice(&dek)
+            .context("Failed to create DEK cipher")?
+            .decrypt(
+                Nonce::from_slice(data_nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("Failed to decrypt secret"))?;
+
+        String::from_utf8(plaintext).context("Failed to convert decrypted data to string")
+    }
+
+    /// Whether a stored value is legacy or sealed with a non-active master key
+    pub fn needs_reencryption(&self, value: &str) -> bool {
+        match parse_envelope(value) {
+            Ok((key_id, _)) => key_id != self.active_key_id,
+            Err(_) => true,
+        }
+    }
+
+    fn kek(&self, key_id: &str) -> Result<&[u8; KEY_SIZE]> {
+        self.keys
+            .get(key_id)
+            .ok_or_else(|| anyhow!("Unknown encryption key id '{}'", key_id))
+    }
+}
+
+fn derive_kek(key_id: &str, secret: &str) -> Result<[u8; KEY_SIZE]> {
+    let params = Params::new(14, 8, 1, KEY_SIZE).map_err(|e| anyhow!("[derive_kek] err={:?}", e))?;
+    let salt = format!("auth_service:{}:{}", ENVELOPE_VERSION, key_id);
+
+    let mut kek = [0u8; KEY_SIZE];
+    scrypt(secret.as_bytes(), salt.as_bytes(), &params, &mut kek)
+        .map_err(|e| anyhow!("[derive_kek] err={:?}", e))?;
+    Ok(kek)
+}
+
+fn parse_envelope(value: &str) -> Result<(&str, &str)> {
+    let mut parts = value.splitn(3, ':');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(ENVELOPE_VERSION), Some(key_id), Some(body)) => Ok((key_id, body)),
+        _ => Err(anyhow!("Value is not an {} envelope", ENVELOPE_VERSION)),
+    }
+}
+
+pub fn is_envelope(value: &str) -> bool {
+    parse_envelope(value).is_ok()
+}
+
+/// Read a value written before envelope encryption with the legacy scheme of its column.
+/// Kept until `reencrypt_secrets` has migrated every row.
+pub fn decrypt_legacy(value: &str, context: &str) -> Result<String> {
+    match context {
+        CONTEXT_WALLET_PRIVATE_KEY => decrypt_private_key(value, &APP_CONFIG.encryption_key),
+        CONTEXT_MFA_SECRET => decrypt(&APP_CONFIG.encryption_key, value),
+        _ => Err(anyhow!("No legacy scheme for context '{}'", context)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: &str = "key-a-secret-material-32-bytes-long!!";
+    const KEY_B: &str = "key-b-secret-material-32-bytes-long!!";
+
+    fn ring(active: &str, keys: &[(&str, &str)]) -> KeyRing {
+        let keys: Vec<(String, String)> = keys
+            .iter()
+            .map(|(id, secret)| (id.to_string(), secret.to_string()))
+            .collect();
+        KeyRing::new(active, &keys).unwrap()
+    }
+
+    #[test]
+    fn test_seal_open_round_trip() {
+        let ring = ring("a", &[("a", KEY_A)]);
+        let secret = "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
+
+        let sealed = ring.seal(secret, CONTEXT_WALLET_PRIVATE_KEY).unwrap();
+        assert!(sealed.starts_with("ev1:a:"));
+        assert_eq!(ring.open(&sealed, CONTEXT_WALLET_PRIVATE_KEY).unwrap(), secret);
+
+        // Context is bound as associated data
+        assert!(ring.open(&sealed, CONTEXT_MFA_SECRET).is_err());
+
+        // Relabelling the key id breaks authentication
+        let relabelled = sealed.replacen("ev1:a:", "ev1:b:", 1);
+        let both = ring_with_both("a");
+        assert!(both.open(&relabelled, CONTEXT_WALLET_PRIVATE_KEY).is_err());
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let old_ring = ring("a", &[("a", KEY_A)]);
+        let sealed = old_ring.seal("JBSWY3DPEHPK3PXP", CONTEXT_MFA_SECRET).unwrap();
+
+        let new_ring = ring_with_both("b");
+        assert!(new_ring.needs_reencryption(&sealed));
+        assert_eq!(
+            new_ring.open(&sealed, CONTEXT_MFA_SECRET).unwrap(),
+            "JBSWY3DPEHPK3PXP"
+        );
+
+        let resealed = new_ring.seal("JBSWY3DPEHPK3PXP", CONTEXT_MFA_SECRET).unwrap();
+        assert!(!new_ring.needs_reencryption(&resealed));
+        assert!(new_ring.needs_reencryption("bGVnYWN5LWNpcGhlcnRleHQ="));
+    }
+
+    fn ring_with_both(active: &str) -> KeyRing {
+        ring(active, &[("a", KEY_A), ("b", KEY_B)])
+    }
+}
@@ -0,0 +1,113 @@
+//! Wrapping of exported private keys for a client-supplied secp256k1 public key (ECIES).
+//!
+//! ```text
+//! base64(ephemeral_public_key(33, compressed SEC1) || nonce(12) || aes256gcm(private_key))
+//! ```
+//!
+//! The AES key is HKDF-SHA256 over the ECDH shared secret with info [`WRAPPING_SCHEME`],
+//! and the owner's user id is bound as associated data.
+
+use aes_gcm::{
+    Aes256Gcm,
+    aead::{Aead, AeadCore, KeyInit, Payload},
+};
+use anyhow::{Context, Result, anyhow};
+use base64::{Engine, engine::general_purpose};
+use k256::PublicKey;
+use k256::ecdh::EphemeralSecret;
+use k256::elliptic_curve::rand_core::OsRng;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use sha2::Sha256;
+
+pub const WRAPPING_SCHEME: &str = "ecies-secp256k1-hkdf-sha256-aes256gcm";
+
+const AES_KEY_SIZE: usize = 32;
+
+/// Parse a hex SEC1 (compressed or uncompressed) secp256k1 public key
+pub fn parse_recipient_public_key(value: &str) -> Result<PublicKey> {
+    let bytes = hex::decode(value.trim_start_matches("0x")).context("Public key is not hex")?;
+    PublicKey::from_sec1_bytes(&bytes).map_err(|_| anyhow!("Invalid secp256k1 public key"))
+}
+
+/// Encrypt `private_key` so only the holder of `recipient`'s secret key can read it
+pub fn wrap_private_key(private_key: &str, recipient: &PublicKey, user_id: &str) -> Result<String> {
+    let ephemeral = EphemeralSecret::random(&mut OsRng);
+    let ephemeral_public = ephemeral.public_key().to_encoded_point(true);
+
+    let shared_secret = ephemeral.diffie_hellman(recipient);
+    let mut aes_key = [0u8; AES_KEY_SIZE];
+    shared_secret
+        .extract::<Sha256>(None)
+        .expand(WRAPPING_SCHEME.as_bytes(), &mut aes_key)
+        .map_err(|_| anyhow!("Failed to derive wrapping key"))?;
+
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = Aes256Gcm::new_from_slice(&aes_key)
+        .context("Failed to create wrapping cipher")?
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: private_key.as_bytes(),
+                aad: user_id.as_bytes(),
+            },
+        )
+        .map_err(|_| anyhow!("Failed to wrap private key"))?;
+
+    let mut body = Vec::with_capacity(ephemeral_public.len() + nonce.len() + ciphertext.len());
+    body.extend_from_slice(ephemeral_public.as_bytes());
+    body.extend_from_slice(&nonce);
+    body.extend_from_slice(&ciphertext);
+
+    Ok(general_purpose::STANDARD.encode(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes_gcm::Nonce;
+    use k256::SecretKey;
+    use k256::ecdh::diffie_hellman;
+
+    fn unwrap_private_key(wrapped: &str, recipient: &SecretKey, user_id: &str) -> Result<String> {
+        let body = general_purpose::STANDARD.decode(wrapped)?;
+        let (ephemeral_public, rest) = body.split_at(33);
+        let (nonce, ciphertext) = rest.split_at(12);
+
+        let ephemeral_public = PublicKey::from_sec1_bytes(ephemeral_public)?;
+        let shared_secret =
+            diffie_hellman(recipient.to_nonzero_scalar(), ephemeral_public.as_affine());
+        let mut aes_key = [0u8; AES_KEY_SIZE];
+        shared_secret
+            .extract::<Sha256>(None)
+            .expand(WRAPPING_SCHEME.as_bytes(), &mut aes_key)
+            .map_err(|_| anyhow!("Failed to derive wrapping key"))?;
+
+        let plaintext = Aes256Gcm::new_from_slice(&aes_key)?
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: user_id.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("Failed to unwrap private key"))?;
+        Ok(String::from_utf8(plaintext)?)
+    }
+
+    #[test]
+    fn test_wrap_private_key_round_trip() {
+        let recipient = SecretKey::random(&mut OsRng);
+        let recipient_hex = hex::encode(recipient.public_key().to_encoded_point(false).as_bytes());
+        let public_key = parse_recipient_public_key(&recipient_hex).unwrap();
+
+        let private_key = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+        let wrapped = wrap_private_key(private_key, &public_key, "user-1").unwrap();
+
+        assert_eq!(
+            unwrap_private_key(&wrapped, &recipient, "user-1").unwrap(),
+            private_key
+        );
+        assert!(unwrap_private_key(&wrapped, &recipient, "user-2").is_err());
+        assert!(parse_recipient_public_key("not-hex").is_err());
+    }
+}
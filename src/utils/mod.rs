@@ -1,5 +1,10 @@
 pub mod encryption;
+pub mod envelope_encryption;
 pub mod gen_otp_code;
+pub mod key_wrapping;
 mod random;
+pub mod shutdown;
+pub mod step_up_token;
 pub mod tracing;
+pub mod trusted_device_token;
 pub mod upload;
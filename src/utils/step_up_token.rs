@@ -0,0 +1,93 @@
+use crate::config::MFA_STEP_UP_TOKEN_EXPIRED_TIME;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use serde::{Deserialize, Serialize};
+
+/// Token type stored in the `typ` claim so a step-up token can never be
+/// mistaken for a regular access token (and vice versa).
+pub const STEP_UP_TOKEN_TYPE: &str = "step_up";
+
+/// Header carrying the step-up token on sensitive requests
+pub const STEP_UP_TOKEN_HEADER: &str = "x-step-up-token";
+
+/// Authentication methods reference values (RFC 8176)
+pub const AMR_PASSWORD: &str = "pwd";
+pub const AMR_OTP: &str = "otp";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepUpClaims {
+    pub sub: String,
+    pub typ: String,
+    pub amr: Vec<String>,
+    pub auth_time: i64,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+impl StepUpClaims {
+    /// Seconds elapsed since the MFA verification that minted this token
+    pub fn age(&self) -> i64 {
+        Utc::now().timestamp() - self.auth_time
+    }
+}
+
+pub(crate) fn jwt_secret() -> String {
+    std::env::var("JWT_SECRET").unwrap_or_else(|_| "secret_key".to_string())
+}
+
+/// Create a short-lived elevated token after a successful MFA verification
+pub fn create_step_up_token(user_id: &str) -> Result<(String, StepUpClaims)> {
+    let now = Utc::now().timestamp();
+    let claims = StepUpClaims {
+        sub: user_id.to_string(),
+        typ: STEP_UP_TOKEN_TYPE.to_string(),
+        amr: vec![AMR_PASSWORD.to_string(), AMR_OTP.to_string()],
+        auth_time: now,
+        iat: now,
+        exp: now + MFA_STEP_UP_TOKEN_EXPIRED_TIME,
+    };
+
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(jwt_secret().as_bytes()),
+    )
+    .context("Failed to encode step-up token")?;
+
+    Ok((token, claims))
+}
+
+/// Decode and validate a step-up token (signature, expiry and token type)
+pub fn decode_step_up_token(token: &str) -> Result<StepUpClaims> {
+    let token_data = decode::<StepUpClaims>(
+        token,
+        &DecodingKey::from_secret(jwt_secret().as_bytes()),
+        &Validation::default(),
+    )
+    .context("Invalid step-up token")?;
+
+    if token_data.claims.typ != STEP_UP_TOKEN_TYPE {
+        anyhow::bail!("Token is not a step-up token");
+    }
+
+    Ok(token_data.claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_up_token_round_trip() {
+        let user_id = uuid::Uuid::new_v4().to_string();
+
+        let (token, claims) = create_step_up_token(&user_id).unwrap();
+        let decoded = decode_step_up_token(&token).unwrap();
+
+        assert_eq!(decoded.sub, user_id);
+        assert_eq!(decoded.auth_time, claims.auth_time);
+        assert!(decoded.amr.iter().any(|m| m == AMR_OTP));
+        assert!(decode_step_up_token("not-a-token").is_err());
+    }
+}
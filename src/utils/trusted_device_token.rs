@@ -0,0 +1,113 @@
+use crate::config::MFA_TRUSTED_DEVICE_TTL_SECONDS;
+use crate::utils::step_up_token::jwt_secret;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use serde::{Deserialize, Serialize};
+
+pub const TRUSTED_DEVICE_TOKEN_TYPE: &str = "trusted_device";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedDeviceClaims {
+    pub sub: String,
+    pub did: String,
+    pub typ: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// Create the signed value stored in the trusted-device cookie
+pub fn create_trusted_device_token(user_id: &str, device_id: &str) -> Result<String> {
+    let now = Utc::now().timestamp();
+    encode_trusted_device_claims(&TrustedDeviceClaims {
+        sub: user_id.to_string(),
+        did: device_id.to_string(),
+        typ: TRUSTED_DEVICE_TOKEN_TYPE.to_string(),
+        iat: now,
+        exp: now + MFA_TRUSTED_DEVICE_TTL_SECONDS as i64,
+    })
+}
+
+fn encode_trusted_device_claims(claims: &TrustedDeviceClaims) -> Result<String> {
+    encode(
+        &Header::default(),
+        claims,
+        &EncodingKey::from_secret(jwt_secret().as_bytes()),
+    )
+    .context("Failed to encode trusted device token")
+}
+
+/// Decode the trusted-device cookie of `user_id`. Revocation is checked separately against
+/// Redis.
+pub fn decode_trusted_device_token(token: &str, user_id: &str) -> Result<TrustedDeviceClaims> {
+    let token_data = decode::<TrustedDeviceClaims>(
+        token,
+        &DecodingKey::from_secret(jwt_secret().as_bytes()),
+        &Validation::default(),
+    )
+    .context("Invalid trusted device token")?;
+
+    if token_data.claims.typ != TRUSTED_DEVICE_TOKEN_TYPE {
+        anyhow::bail!("Token is not a trusted device token");
+    }
+    if token_data.claims.sub != user_id {
+        anyhow::bail!("Trusted device token belongs to another user");
+    }
+
+    Ok(token_data.claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::step_up_token::create_step_up_token;
+
+    #[test]
+    fn test_trusted_device_token_round_trip() {
+        let user_id = uuid::Uuid::new_v4().to_string();
+        let device_id = uuid::Uuid::new_v4().to_string();
+
+        let token = create_trusted_device_token(&user_id, &device_id).unwrap();
+        let claims = decode_trusted_device_token(&token, &user_id).unwrap();
+
+        assert_eq!(claims.sub, user_id);
+        assert_eq!(claims.did, device_id);
+        assert_eq!(
+            claims.exp - claims.iat,
+            MFA_TRUSTED_DEVICE_TTL_SECONDS as i64
+        );
+    }
+
+    #[test]
+    fn test_expired_trusted_device_token_is_rejected() {
+        let user_id = uuid::Uuid::new_v4().to_string();
+        let issued_at = Utc::now().timestamp() - MFA_TRUSTED_DEVICE_TTL_SECONDS as i64 - 3600;
+        let token = encode_trusted_device_claims(&TrustedDeviceClaims {
+            sub: user_id.clone(),
+            did: "device".to_string(),
+            typ: TRUSTED_DEVICE_TOKEN_TYPE.to_string(),
+            iat: issued_at,
+            exp: issued_at + MFA_TRUSTED_DEVICE_TTL_SECONDS as i64,
+        })
+        .unwrap();
+
+        assert!(decode_trusted_device_token(&token, &user_id).is_err());
+    }
+
+    #[test]
+    fn test_trusted_device_token_of_another_user_or_tampered_is_rejected() {
+        let user_id = uuid::Uuid::new_v4().to_string();
+        let other_user_id = uuid::Uuid::new_v4().to_string();
+        let token = create_trusted_device_token(&user_id, "device").unwrap();
+
+        assert!(decode_trusted_device_token(&token, &other_user_id).is_err());
+
+        let (payload_end, signature) = token.rsplit_once('.').unwrap();
+        let flipped = if signature.starts_with('A') { 'B' } else { 'A' };
+        let tampered = format!("{}.{}{}", payload_end, flipped, &signature[1..]);
+        assert!(decode_trusted_device_token(&tampered, &user_id).is_err());
+
+        let (step_up, _) = create_step_up_token(&user_id).unwrap();
+        assert!(decode_trusted_device_token(&step_up, &user_id).is_err());
+    }
+}